@@ -2,38 +2,46 @@ use std::{fs, time::{Duration, Instant}, collections::{HashMap, BTreeMap}};
 use multimap::MultiMap;
 use regex::Regex;
 
-use crate::{cdcl::{preprocess::preprocess, cdcl::{cdcl, Result}}, data_structures::{CDCLMatrix, Config, Statistics}, resolution::pre_resolution, util::read_instance_name};
+use crate::{log_normal, cdcl::{solve, preprocess::preprocess, cdcl::{cdcl, Result}}, data_structures::{CDCLMatrix, Config, Statistics}, resolution::pre_resolution, util::{read_instance_name, get_output_pathname, get_clause_length_histogram, format_clause_length_histogram, read_benchmark_paths}};
 
 /*
 A function to run a directory of files in QDIMACS format. 
-It will run each problem with an automatic timeout at 30 seconds.
+It will run each problem with an automatic timeout after the configured number of seconds.
 
-Stores detailed results in a file with the provided name "results-<filename_to_write>".
+Stores detailed results in a file with the provided name "output-<filename_to_write>" in the output directory. If stats_only is set, the 
+summary is printed to stdout instead and no file is written.
 */
-pub fn run_bench_group(group: String, config: Config, filename_to_write: &str) {
+pub fn run_bench_group(group: String, config: Config, filename_to_write: &str, stats_only: bool) {
     let mut test_times = BTreeMap::new();
-    let paths = fs::read_dir(&group).unwrap();
+    let paths = match read_benchmark_paths(&group, config.verbosity) {
+        Ok(paths) => paths,
+        Err(error) => {
+            log_normal!(config.verbosity, "{}", error);
+            return;
+        }
+    };
     let (mut total, mut satisfiable, mut unsatisfiable, mut timeout) = (0, 0, 0, 0);
     let bench_timer = Instant::now();
-    let mut statistic_database : HashMap<String, (i32,i32,i32, Result)> = HashMap::new();
-    for path in paths {
+    let mut clause_length_histograms = HashMap::new();
+    let mut statistic_database : HashMap<String, (i32,i32,i32, Result, String, i32, f32)> = HashMap::new();
+    for file_path in paths {
         let instance_timer = Instant::now();
-        let file_path = path.unwrap().path().display().to_string();
         
-        let matrix = &mut CDCLMatrix::new(file_path.clone(), config.clone());
         let instance_name = read_instance_name(&file_path);
+        let matrix = &mut CDCLMatrix::new(file_path.clone(), config.instance_config(&instance_name));
+        if config.verbose_benchmark_enabled() {
+            clause_length_histograms.insert(instance_name.clone(), format_clause_length_histogram(&get_clause_length_histogram(&matrix.core_data.clause_set)));
+        }
         let statistics = &mut Statistics::new();
-        if matrix.core_data.config.pre_process_enabled() { preprocess(matrix, statistics, instance_timer) };
-        if matrix.core_data.config.pre_resolution_enabled() { pre_resolution(&mut matrix.core_data, &mut matrix.original_clause_list) };
-        let (_invariant, _backtrack_level, result) = cdcl(matrix, None, statistics, instance_timer);
+        let result = solve(matrix, statistics, instance_timer);
         test_times.insert(instance_name.clone(), instance_timer.elapsed());
-        statistic_database.insert(instance_name, (statistics.propagation_count, statistics.backtrack_count, statistics.learned_clause_count, result.clone()));
+        statistic_database.insert(instance_name, (statistics.propagation_count, statistics.backtrack_count, statistics.learned_clause_count, result.clone(), statistics.format_simplification_statistics(), statistics.peak_clause_count, statistics.average_learned_clause_length()));
         total += 1;
         match &result {
             Result::UNSAT => unsatisfiable += 1,
             Result::SAT => satisfiable += 1,
-            Result::Timeout => timeout += 1,
-            Result::Restart => println!("ERROR WITH RESTARTS"),
+            Result::Timeout(_) | Result::ConflictLimit | Result::NoProgress => timeout += 1,
+            Result::Restart => log_normal!(config.verbosity, "ERROR WITH RESTARTS"),
         }
     }
     // Formatting to store overall results
@@ -42,13 +50,19 @@ pub fn run_bench_group(group: String, config: Config, filename_to_write: &str) {
     if config.pre_resolution_enabled() {
         output_string += &format!("\nPre-Resolution Config: [min_ratio: {}, max_ratio: {}, max_clause_length: {}, repeat_above: {}, iterations: {}]", config.pre_resolution.1.min_ratio, config.pre_resolution.1.max_ratio, config.pre_resolution.1.max_clause_length, config.pre_resolution.1.repeat_above, config.pre_resolution.1.iterations);
     }
-    output_string += &format!("\n--------------------------------------------------------------\nTotal: {}, Sat: {}, Unsat: {}, Timeout: {}\nComplete time: {:?}", total, 
-                                satisfiable, unsatisfiable, timeout, bench_timer.elapsed());
+    output_string += &format!("\n--------------------------------------------------------------\nTotal: {}, Sat: {}, Unsat: {}, Timeout: {}\nComplete time: {}", total, 
+                                satisfiable, unsatisfiable, timeout, config.format_duration(bench_timer.elapsed()));
+    if stats_only {
+        log_normal!(config.verbosity, "{}", output_string);
+        return;
+    }
     for (key, val) in test_times {
         let stats = statistic_database.get(&key).unwrap();
-        output_string += &format!("\nInstance: {} -- Runtime: {:?} -- Result: {:?}  -- Propagations: {}, Backtracks: {}, Learned Clauses: {}", key, val, stats.3, stats.0, stats.1, stats.2);
+        output_string += &format!("\nInstance: {} -- Runtime: {} -- Result: {:?}  -- Propagations: {}, Backtracks: {}, Learned Clauses: {}, Average Learned Clause Length: {:.2}, Peak Clauses: {} -- {}", key, config.format_duration(val), stats.3, stats.0, stats.1, stats.2, stats.6, stats.5, stats.4);
+        let histogram = clause_length_histograms.get(&key);
+        if !histogram.is_none() { output_string += &format!(" -- {}", histogram.unwrap()) };
     }
-    let pathname = format!("output-{}", filename_to_write);
+    let pathname = get_output_pathname(&config.output_dir, filename_to_write);
     fs::write(pathname, output_string).expect("Unable to write file");
 }
 
@@ -57,7 +71,7 @@ pub fn run_bench_group(group: String, config: Config, filename_to_write: &str) {
 A function to run the Tacchella data set suite. I've decided to separate this benchmark as I wanted to gather 
 separate information from other benchmarks. This function is not necessary for general usage of the solvers.
 
-Stores detailed results in a file with the provided name "results-<filename_to_write>".
+Stores detailed results in a file with the provided name "output-<filename_to_write>" in the output directory.
 */
 pub fn run_clause_variable_ratio_instances(config: Config, filename_to_write: &str) {
     let paths = fs::read_dir("./benchmarks/tacchella").unwrap();
@@ -69,25 +83,27 @@ pub fn run_clause_variable_ratio_instances(config: Config, filename_to_write: &s
 
         let matrix = &mut CDCLMatrix::new(file_path, config.clone());
         let statistics = &mut Statistics::new();
+        let resolution_first = matrix.core_data.config.resolution_before_preprocess_enabled();
+        if resolution_first && matrix.core_data.config.pre_resolution_enabled() { pre_resolution(&mut matrix.core_data, &mut matrix.original_clause_list) };
         if matrix.core_data.config.pre_process_enabled() { preprocess(matrix, statistics, timer) };
-        if matrix.core_data.config.pre_resolution_enabled() { pre_resolution(&mut matrix.core_data, &mut matrix.original_clause_list) };
+        if !resolution_first && matrix.core_data.config.pre_resolution_enabled() { pre_resolution(&mut matrix.core_data, &mut matrix.original_clause_list) };
         let (_invariant, _backtrack_level, result) = cdcl(matrix, None, statistics, timer);
         match &result {
-            Result::UNSAT | Result::SAT | Result::Timeout => output.insert(problem_setup, timer.elapsed()),
-            Result::Restart => println!("Error occurred with restart functionality."),
+            Result::UNSAT | Result::SAT | Result::Timeout(_) | Result::ConflictLimit | Result::NoProgress => output.insert(problem_setup, timer.elapsed()),
+            Result::Restart => log_normal!(config.verbosity, "Error occurred with restart functionality."),
         }
     }
     let mut ratios = MultiMap::new();
     let mut output_string = format!("------ CDCL ------ \n(<quantifier alternation number>, <variable number>, <clause number>): <average time per solved instance>");
     for (key, value) in output {
         ratios.insert((key.1, key.2), value.iter().sum::<Duration>());
-        output_string += &format!("\n({}qbf, {}var, {}cl): {:?}", key.0, key.1, key.2, value.iter().sum::<Duration>())
+        output_string += &format!("\n({}qbf, {}var, {}cl): {}", key.0, key.1, key.2, config.format_duration(value.iter().sum::<Duration>()))
     }
     output_string += &format!("\n(<Clause-variable values>) -> Combined time");
     for (key, value) in ratios {
-        output_string += &format!("\nSums: ({}, {}) -> {:?}", key.0, key.1, value.iter().sum::<Duration>());
+        output_string += &format!("\nSums: ({}, {}) -> {}", key.0, key.1, config.format_duration(value.iter().sum::<Duration>()));
     }
-    let pathname = format!("output-{}", filename_to_write);
+    let pathname = get_output_pathname(&config.output_dir, filename_to_write);
     fs::write(pathname, output_string).expect("Unable to write file");
 }
 
@@ -2,22 +2,26 @@ use std::collections::HashMap;
 use std::time::Instant;
 use multimap::MultiMap;
 
-use crate::{cdcl::{unit_propagate::unit_propagate, conflict_analysis::analyse_conflict, preprocess::preprocess}, data_structures::{CDCLMatrix, Clause, QuantifierType, ClauseSet, Quantifier, Assignment, Statistics, LiteralSelection}, literal_selection::{select_literal_vss, select_literal}};
+use crate::{log_normal, log_debug, cdcl::{unit_propagate::{unit_propagate, propagate_implied_literal}, conflict_analysis::analyse_conflict, sat_tail::solve_existential_tail, preprocess::{preprocess, simplify_constraints}}, data_structures::{CDCLMatrix, Clause, QuantifierType, ClauseSet, Quantifier, Assignment, Trail, Statistics, LiteralSelection, TimeoutPhase, SearchEvent}, literal_selection::{select_literal_vss, select_literal, select_literal_dlis}, util::get_unit_literals};
 
 /*
 A struct to store the result of the CDCL procedure.
 
 SAT => Satisfiable at current decision branch.
 UNSAT => Unsatisfiable at current decision branch.
-Timeout => Instance timeout, stop running the current instance.
+Timeout => Instance timeout, stop running the current instance. Stores the phase of solving that timed out.
 Restart => Indicates that a restart should be performed, return to top of the decision tree.
+ConflictLimit => The maximum number of conflicts was exceeded, so the result is unknown.
+NoProgress => The same clause kept being learned without restarts to escape it, so the result is unknown.
 */
 #[derive(Clone, Debug, PartialEq)]
 pub enum Result {
     SAT,
     UNSAT,
-    Timeout,
+    Timeout(TimeoutPhase),
     Restart,
+    ConflictLimit,
+    NoProgress,
 }
 
 /*
@@ -25,33 +29,88 @@ A function that will perform the Conflict Driven Clause Learning (CDCL) algorith
 from the set {Universal Reduction, Pre-Resolution (done prior), Pre-Process (done prior)}.
 Has one of the literal selection schemes {Ordered, Variable State Sum}.
 
-Returns SAT (satisfiable), UNSAT (unsatisfiable), Timeout, or Restart.
+Returns SAT (satisfiable), UNSAT (unsatisfiable), Timeout, Restart, ConflictLimit, or NoProgress.
 */
 pub fn cdcl(matrix: &mut CDCLMatrix, decision_branch: Option<i32>, statistics: &mut Statistics, timer: Instant) -> (Clause, i32, Result) {
+    if decision_branch.is_none() {
+        // Unit clauses in the input are forced assignments, so propagate them before any decision even without pre-processing.
+        let unit_literals = get_unit_literals(&matrix.core_data.clause_set);
+        if !unit_literals.is_empty() {
+            unit_propagate(matrix, unit_literals, false, statistics);
+            simplify_constraints(matrix);
+            statistics.update_peak_clause_count(matrix.core_data.clause_set.clause_count);
+            if matrix.core_data.clause_set.contains_empty_set() {
+                if matrix.core_data.config.verify_enabled() || matrix.core_data.config.certificate_enabled() { matrix.sat_assignments = matrix.assignments.clone() };
+                return satisfiable();
+            } else if matrix.core_data.clause_set.contains_empty_clause() {
+                return unsatisfiable();
+            }
+        }
+    }
     loop {
-        if timer.elapsed().as_secs() > 30 {
+        if matrix.core_data.config.timed_out(timer) {
+            // Structures are restored whilst returning up the decision tree, so keep the trail for debugging.
+            if matrix.core_data.config.debug_enabled() { matrix.timeout_trail = matrix.trail.clone() };
             return timeout();
         }
         if !decision_branch.is_none() {
             unit_propagate(matrix, vec![decision_branch.unwrap()], true, statistics);
         }
         if matrix.core_data.clause_set.contains_empty_set() { // Current assignment is satisfiable.
+            // Structures are restored whilst returning up the decision tree, so keep the assignments for verification.
+            if matrix.core_data.config.verify_enabled() || matrix.core_data.config.certificate_enabled() { matrix.sat_assignments = matrix.assignments.clone() };
             return satisfiable();
         } else if matrix.core_data.clause_set.contains_empty_clause() { // Current assignment is unsatisfiable.
+            statistics.increment_conflict_count();
+            if statistics.conflict_count as usize > matrix.core_data.config.max_conflicts {
+                return conflict_limit();
+            }
             if matrix.core_data.config.restarts_enabled() && matrix.restart_data.should_restart() {
-                return perform_restart(matrix);
+                // A new max decision level since the last restart is a sign of progress, so the restart can be postponed.
+                if matrix.core_data.config.restart_blocking_enabled() && matrix.restart_data.new_depth_record {
+                    matrix.restart_data.block_restart();
+                } else {
+                    return perform_restart(matrix);
+                }
             }
             // Analyse conflict here.
             let (learned_clause, backtrack_level) = analyse_conflict(matrix, statistics);
-            if !learned_clause.is_empty() && matrix.core_data.config.restarts_enabled() {matrix.restart_data.increment_current_conflicts()};
+            if matrix.core_data.config.dump_first_learned_enabled() && !learned_clause.is_empty() && matrix.first_learned_clause.is_none() {
+                dump_first_learned_clause(matrix, &learned_clause, backtrack_level);
+            }
+            if !learned_clause.is_empty() && matrix.core_data.config.restarts_enabled() {
+                matrix.restart_data.increment_current_conflicts();
+                let lbd = matrix.literal_block_distance(&learned_clause);
+                matrix.restart_data.record_lbd(lbd);
+            };
             return (learned_clause, backtrack_level, Result::UNSAT);
         }
+        if matrix.core_data.config.sat_tail_enabled() && matrix.core_data.only_existentials_remain() {
+            let tail_result = solve_existential_tail(matrix, statistics, timer);
+            if !tail_result.is_none() { return tail_result.unwrap() };
+        }
         let pre_selection_quantifier_list = matrix.core_data.quantifier_list.clone();
 
-        let (literal, quantifier_type) = if matrix.core_data.config.literal_selection.eq(&LiteralSelection::Ordered) 
-                                                        {select_literal(&mut matrix.core_data)} else {select_literal_vss(&mut matrix.core_data)};
+        let selection = match matrix.core_data.config.literal_selection {
+            LiteralSelection::Ordered => select_literal(&mut matrix.core_data),
+            LiteralSelection::VariableStateSum => select_literal_vss(&mut matrix.core_data),
+            LiteralSelection::DLIS => select_literal_dlis(&mut matrix.core_data),
+        };
+        if selection.is_none() {
+            if matrix.core_data.remaining_clauses_satisfiable() {
+                if matrix.core_data.config.verify_enabled() || matrix.core_data.config.certificate_enabled() { matrix.sat_assignments = matrix.assignments.clone() };
+                return satisfiable();
+            }
+            // No conflict clause to learn from, so naively backtrack.
+            return (Clause::new_empty_clause(), matrix.decision_level, Result::UNSAT);
+        }
+        let (literal, quantifier_type) = selection.unwrap();
 
         matrix.increment_decision_level();
+        statistics.update_max_decision_level(matrix.decision_level);
+        matrix.restart_data.record_decision_level(matrix.decision_level);
+        matrix.record_event(SearchEvent::Decision { literal, decision_level: matrix.decision_level });
+        log_debug!(matrix.core_data.config.verbosity, "Decision: {} at decision level {}", literal, matrix.decision_level);
         // Necessary copying of data as they are all edited and propagated back up with edited data.
         let stored_structures = cache_necessary_structures(matrix);
 
@@ -61,27 +120,23 @@ pub fn cdcl(matrix: &mut CDCLMatrix, decision_branch: Option<i32>, statistics: &
 
         match (&result, &quantifier_type) {
             (Result::UNSAT, QuantifierType::Universal) | (Result::UNSAT, QuantifierType::Existential) => {
-                if backtrack_level == matrix.decision_level {
-                    if learned_clause.is_empty() {
-                        if quantifier_type.eq(&QuantifierType::Universal) {
-                            return (learned_clause, backtrack_level - 1, result);
-                        } else {
-                            matrix.decision_level -= 1;
-                            statistics.increment_backtrack_count();
-                            return cdcl(matrix, Some(-literal), statistics, timer);
-                        }
+                if learned_clause.is_empty() && backtrack_level == matrix.decision_level {
+                    if quantifier_type.eq(&QuantifierType::Universal) {
+                        return (learned_clause, backtrack_level - 1, result);
+                    } else {
+                        matrix.decision_level -= 1;
+                        statistics.increment_backtrack_count();
+                        return cdcl(matrix, Some(-literal), statistics, timer);
                     }
+                } else if !learned_clause.is_empty() && backtrack_level == 0 && matrix.decision_level == 1 {
+                    // Conflict analysis returns backtrack_level 0 for clauses that are unit at decision level 0.
                     statistics.increment_backtrack_count();
+                    matrix.add_clause(&learned_clause, statistics);
+                    statistics.update_peak_clause_count(matrix.core_data.clause_set.clause_count);
                     matrix.core_data.quantifier_list = pre_selection_quantifier_list;
                     matrix.decision_level -= 1;
-                    matrix.add_clause(&learned_clause); // Adding new learned clause
-                    continue;
-                } else if !learned_clause.is_unit_clause().is_none() && matrix.decision_level == 1 {
-                    // Conflict analysis returns backtrack_level 0 for unit clauses.
-                    statistics.increment_backtrack_count();
-                    matrix.add_clause(&learned_clause);
-                    matrix.core_data.quantifier_list = pre_selection_quantifier_list;
-                    matrix.decision_level -= 1;
+                    debug_assert!(matrix.is_asserting_clause(&learned_clause), "Learned clause {:?} is not asserting after backjumping to decision level 0", learned_clause);
+                    matrix.record_event(SearchEvent::Backjump { decision_level: matrix.decision_level });
                     preprocess(matrix, statistics, timer); // Simplify problem permanently.
                     if matrix.core_data.clause_set.contains_empty_set() {
                         return satisfiable();
@@ -90,6 +145,41 @@ pub fn cdcl(matrix: &mut CDCLMatrix, decision_branch: Option<i32>, statistics: &
                     } else {
                         continue;
                     }
+                } else if !learned_clause.is_empty() && backtrack_level == matrix.decision_level - 1 {
+                    if matrix.record_learned_clause_progress(&learned_clause) > matrix.core_data.config.repeated_conflict_limit {
+                        // The same clause keeps being learned without the search progressing.
+                        matrix.repeated_conflicts = 0;
+                        if matrix.core_data.config.restarts_enabled() {
+                            let restart = perform_restart(matrix);
+                            if matrix.decision_level != 1 {
+                                return restart;
+                            }
+                            // Already at the top of the decision tree, so restart here.
+                            matrix.reduce_clause_database();
+                            statistics.update_peak_clause_count(matrix.core_data.clause_set.clause_count);
+                            matrix.core_data.quantifier_list = pre_selection_quantifier_list;
+                            matrix.decision_level -= 1;
+                            continue;
+                        }
+                        log_normal!(matrix.core_data.config.verbosity, "CDCL aborted: learned clause {:?} was re-derived more than {} times at decision level {}.", learned_clause.literals(), matrix.core_data.config.repeated_conflict_limit, matrix.decision_level);
+                        if matrix.core_data.config.debug_enabled() { log_normal!(matrix.core_data.config.verbosity, "State at abort:\n{}", matrix.debug_state()) };
+                        return no_progress();
+                    }
+                    // Undo the decision at this level, keeping the assignments up to the backtrack level.
+                    statistics.increment_backtrack_count();
+                    matrix.core_data.quantifier_list = pre_selection_quantifier_list;
+                    matrix.decision_level -= 1;
+                    matrix.record_event(SearchEvent::Backjump { decision_level: matrix.decision_level });
+                    matrix.add_clause(&learned_clause, statistics); // Adding new learned clause
+                    statistics.update_peak_clause_count(matrix.core_data.clause_set.clause_count);
+                    debug_assert!(matrix.is_asserting_clause(&learned_clause), "Learned clause {:?} is not asserting after backjumping to decision level {}", learned_clause, matrix.decision_level);
+                    // The learned clause is unit at the backtrack level, so its asserting literal is implied by it.
+                    let asserting_literal = matrix.asserting_literal(&learned_clause);
+                    if !asserting_literal.is_none() && matrix.core_data.variable_of(asserting_literal.unwrap()).is_existential() {
+                        let learned_clause_index = matrix.core_data.clause_set.clause_list.len() as i32 - 1;
+                        propagate_implied_literal(matrix, asserting_literal.unwrap(), learned_clause_index, statistics);
+                    }
+                    continue;
                 } else {
                     return (learned_clause, backtrack_level, result);
                 }
@@ -112,11 +202,12 @@ pub fn cdcl(matrix: &mut CDCLMatrix, decision_branch: Option<i32>, statistics: &
                     return (learned_clause, backtrack_level, result);
                 }
                 matrix.reduce_clause_database();
+                statistics.update_peak_clause_count(matrix.core_data.clause_set.clause_count);
                 matrix.core_data.quantifier_list = pre_selection_quantifier_list;
                 matrix.decision_level -= 1;
                 continue;
             },
-            (Result::Timeout, _) => {
+            (Result::Timeout(_), _) | (Result::ConflictLimit, _) | (Result::NoProgress, _) => {
                 return (learned_clause, backtrack_level, result);
             }
         }
@@ -128,7 +219,7 @@ A function to cache the data structures that will need to be restored upon backt
 
 Returns a cache of the current {clause database, clause references, quantifier prefix, trail, assignments, decision level).
 */
-pub fn cache_necessary_structures(matrix: &CDCLMatrix) -> (ClauseSet, MultiMap<i32, i32>, Vec<Quantifier>, Vec<Assignment>, HashMap<i32, Assignment>, i32) {
+pub fn cache_necessary_structures(matrix: &CDCLMatrix) -> (ClauseSet, MultiMap<i32, i32>, Vec<Quantifier>, Trail, HashMap<i32, Assignment>, i32) {
     let current_clause_set = matrix.core_data.clause_set.clone();
     let current_clause_references = matrix.core_data.clause_references.clone();
     let current_quantifier_list = matrix.core_data.quantifier_list.clone();
@@ -143,7 +234,7 @@ A function to restore the matrix with cached data structures during back-jumping
 
 Modifies the matrix and re-adds learned clauses so they're not lost upon back-jumping/backtracking.
 */
-pub fn restore_necessary_structures(matrix: &mut CDCLMatrix, cached_structures: (ClauseSet, MultiMap<i32, i32>, Vec<Quantifier>, Vec<Assignment>, HashMap<i32, Assignment>, i32)) {
+pub fn restore_necessary_structures(matrix: &mut CDCLMatrix, cached_structures: (ClauseSet, MultiMap<i32, i32>, Vec<Quantifier>, Trail, HashMap<i32, Assignment>, i32)) {
     matrix.core_data.clause_set = cached_structures.0;
     matrix.core_data.clause_references = cached_structures.1;
     matrix.core_data.quantifier_list = cached_structures.2;
@@ -151,6 +242,22 @@ pub fn restore_necessary_structures(matrix: &mut CDCLMatrix, cached_structures:
     matrix.assignments = cached_structures.4;
     matrix.decision_level = cached_structures.5;
     matrix.readd_learned_clauses();
+    matrix.core_data.debug_verify_structural_invariants("restoring the cached structures");
+}
+
+/*
+A function to print the first non-empty learned clause with its backtrack level, and whether it is asserting - exactly one 
+of its existential literals is assigned at the highest decision level among them. The clause is stored in the matrix, 
+which stops any later learned clause being printed.
+*/
+pub fn dump_first_learned_clause(matrix: &mut CDCLMatrix, learned_clause: &Clause, backtrack_level: i32) {
+    let decision_levels: Vec<i32> = learned_clause.e_literals.iter()
+                                        .filter_map(|literal| matrix.assignments.get(&literal.abs()).map(|assignment| assignment.decision_level))
+                                        .collect();
+    let highest_decision_level = decision_levels.iter().max();
+    let is_asserting = !highest_decision_level.is_none() && decision_levels.iter().filter(|level| *level == highest_decision_level.unwrap()).count() == 1;
+    log_normal!(matrix.core_data.config.verbosity, "First learned clause: {:?} -- Backtrack level: {} -- Asserting: {}", learned_clause.literals(), backtrack_level, is_asserting);
+    matrix.first_learned_clause = Some((learned_clause.literals(), backtrack_level, is_asserting));
 }
 
 /*
@@ -171,7 +278,21 @@ pub fn unsatisfiable() -> (Clause, i32, Result) {
 A function that defines the invariant to be returned within the cdcl procedure that signifies a timeout.
 */
 pub fn timeout() -> (Clause, i32, Result) {
-    return (Clause::new_empty_clause(), -1, Result::Timeout);
+    return (Clause::new_empty_clause(), -1, Result::Timeout(TimeoutPhase::Search));
+}
+
+/*
+A function that defines the invariant to be returned within the cdcl procedure that signifies the conflict limit was exceeded.
+*/
+pub fn conflict_limit() -> (Clause, i32, Result) {
+    return (Clause::new_empty_clause(), -1, Result::ConflictLimit);
+}
+
+/*
+A function that defines the invariant to be returned within the cdcl procedure that signifies the search stopped making progress.
+*/
+pub fn no_progress() -> (Clause, i32, Result) {
+    return (Clause::new_empty_clause(), -1, Result::NoProgress);
 }
 
 /*
@@ -180,9 +301,13 @@ A function to perform a restart on the matrix and update necessary data structur
 Returns an invariant to be returned within the cdcl procedure that signifies it should handle a Restart.
 */
 pub fn perform_restart(matrix: &mut CDCLMatrix) -> (Clause, i32, Result) {
+    matrix.record_event(SearchEvent::Restart { decision_level: matrix.decision_level });
+    log_debug!(matrix.core_data.config.verbosity, "Restart from decision level {}", matrix.decision_level);
     matrix.restart_data.increment_restart_counter();
     matrix.restart_data.update_conflicts_until_restart(matrix.restart_data.restart_counter);
     matrix.restart_data.reset_current_conflicts();
+    matrix.restart_data.reset_recent_lbds();
+    matrix.restart_data.start_restart_interval();
     matrix.reset_conflict_clause();
     return (Clause::new_empty_clause(), -1, Result::Restart);
 }
\ No newline at end of file
@@ -1,25 +1,13 @@
 #[cfg(test)]
 mod test {
-    use std::time::Instant;
+    use std::{fs, time::Instant};
+    use multimap::MultiMap;
 
 
-    use crate::{cdcl::{preprocess::preprocess, cdcl::{cdcl, Result}}, data_structures::{CDCLMatrix, ResolutionConfig, LiteralSelection, Config, Statistics}, resolution::pre_resolution};
+    use crate::{cdcl::{solve, retry_with_fallback, write_unsat_qrat_proof, preprocess::{preprocess, simplify_constraints}, sat_tail::{solve_sat, SATResult}, unit_propagate::{unit_propagate, propagate_implied_literal}, conflict_analysis::{analyse_conflict, calculate_backtrack_level, all_previous_universals_assigned_correctly}, cdcl::{cdcl, Result}}, data_structures::{Matrix, CDCLMatrix, LiteralSelection, Config, RestartPolicy, RestartData, LearningScheme, Statistics, Clause, SearchEvent, Assignment}, resolution::pre_resolution, verify::verify_assignment, qrat::{QratStep, format_qrat_proof, write_qrat_proof, check_qrat_proof}, util::hash_instance_file};
     
     fn config() -> Config {
-        Config {
-            literal_selection: LiteralSelection::VariableStateSum,
-            pre_resolution: (false, ResolutionConfig {
-                min_ratio: 0.25,
-                max_ratio: 0.5,
-                max_clause_length: usize::MAX,
-                repeat_above: 3,
-                iterations: 1,
-            }),
-            pre_process: true,
-            universal_reduction: true,
-            pure_literal_deletion: true,
-            restarts: true,
-        }
+        Config::default()
     }
 
     fn timer() -> Instant {
@@ -46,5 +34,915 @@ mod test {
         assert_eq!(Result::SAT, result);
     }
     
+    /*
+    Tests an instance where conflict analysis learns clauses, so the debug assertion that each learned clause is
+    asserting after backjumping is checked on a correct derivation.
+    */
+    #[test]
+    fn asserting_learned_clause_test() {
+        let filename = "./test_files/asserting_learned_clause_test.qdimacs".to_string();
+        let matrix = &mut CDCLMatrix::new(filename, config());
+        let statistics = &mut Statistics::new();
+        let timer = timer();
+        preprocess(matrix, statistics, timer);
+        let (_invariant, _backtrack_level, result) = cdcl(matrix, None, statistics, timer);
+        assert_eq!(Result::UNSAT, result);
+        assert!(statistics.learned_clause_count > 0);
+    }
+    
+    /*
+    Tests that the Glucose restart policy reaches the same result as the Luby policy on a sample instance, while 
+    restarting more often when the window and margin are small.
+    */
+    #[test]
+    fn glucose_restart_policy_test() {
+        let filename = "./test_files/conflict_heavy_test.qdimacs".to_string();
+        let mut restart_counts = Vec::new();
+        for restart_policy in [RestartPolicy::Luby, RestartPolicy::Glucose { window: 2, margin: 0.5 }] {
+            let mut solver_config = config();
+            solver_config.restart_policy = restart_policy;
+            let matrix = &mut CDCLMatrix::new(filename.clone(), solver_config);
+            let statistics = &mut Statistics::new();
+            let timer = timer();
+            preprocess(matrix, statistics, timer);
+            let (_invariant, _backtrack_level, result) = cdcl(matrix, None, statistics, timer);
+            assert_eq!(Result::UNSAT, result);
+            // The conflict that triggers a restart is counted without being learned from.
+            assert!(statistics.conflict_count >= statistics.learned_clause_count + matrix.restart_data.restart_counter as i32 - 1);
+            restart_counts.push(matrix.restart_data.restart_counter);
+        }
+        assert!(restart_counts[1] > restart_counts[0]);
+    }
+    
+    /*
+    Tests that restart blocking postpones restarts while the search keeps reaching a new max decision level, restarting
+    less often than without blocking whilst reaching the same result.
+    */
+    #[test]
+    fn restart_blocking_test() {
+        let filename = "./test_files/conflict_heavy_test.qdimacs".to_string();
+        let mut restart_counts = Vec::new();
+        for restart_blocking in [false, true] {
+            let mut solver_config = config();
+            solver_config.restart_policy = RestartPolicy::Glucose { window: 2, margin: 0.5 };
+            solver_config.restart_blocking = restart_blocking;
+            let matrix = &mut CDCLMatrix::new(filename.clone(), solver_config);
+            let statistics = &mut Statistics::new();
+            let timer = timer();
+            preprocess(matrix, statistics, timer);
+            let (_invariant, _backtrack_level, result) = cdcl(matrix, None, statistics, timer);
+            assert_eq!(Result::UNSAT, result);
+            assert!(statistics.max_decision_level > 0);
+            assert_eq!(restart_blocking, matrix.restart_data.blocked_restarts > 0);
+            restart_counts.push(matrix.restart_data.restart_counter);
+        }
+        assert!(restart_counts[1] < restart_counts[0]);
+    }
+
+    /*
+    Tests that a depth record is only noted once the search goes deeper than in the previous restart interval, rather 
+    than deeper than ever before.
+    */
+    #[test]
+    fn restart_interval_depth_record_test() {
+        let mut restart_data = RestartData::new(100, RestartPolicy::Luby);
+        restart_data.record_decision_level(5);
+        assert_eq!(true, restart_data.new_depth_record);
+        restart_data.start_restart_interval();
+        restart_data.record_decision_level(3);
+        assert_eq!(false, restart_data.new_depth_record);
+        restart_data.record_decision_level(5);
+        assert_eq!(false, restart_data.new_depth_record);
+        restart_data.block_restart();
+        restart_data.record_decision_level(3);
+        restart_data.start_restart_interval();
+        // Shallower than the max of 5 two intervals ago, but deeper than the 3 of the previous interval.
+        restart_data.record_decision_level(4);
+        assert_eq!(true, restart_data.new_depth_record);
+    }
+
+    /*
+    Tests that the clause references updated incrementally when simplifying the constraints and reducing the clause 
+    database are identical to the clause references rebuilt from every clause.
+    */
+    #[test]
+    fn incremental_clause_references_test() {
+        let filename = "./test_files/incremental_clause_references_test.qdimacs".to_string();
+        let matrix = &mut CDCLMatrix::new(filename, config());
+        let statistics = &mut Statistics::new();
+        preprocess(matrix, statistics, timer());
+        assert_eq!(4, matrix.core_data.clause_set.clause_list.len());
+        assert_eq!(sorted_references(&rebuilt_clause_references(matrix)), sorted_references(&matrix.core_data.clause_references));
+
+        for literals in vec![vec![2, 3], vec![-2, 4], vec![3, -4], vec![2, 4]] {
+            let clause = Clause::new(&literals, &matrix.core_data.variable_quantification, &matrix.core_data.quantification_order);
+            matrix.add_clause(&clause, statistics);
+        }
+        matrix.reduce_clause_database();
+        assert_eq!(vec![4, 5], matrix.learned_clause_refs);
+        assert_eq!(vec![3, -4], matrix.core_data.clause_set.clause_list[4].e_literals);
+        assert_eq!(sorted_references(&rebuilt_clause_references(matrix)), sorted_references(&matrix.core_data.clause_references));
+    }
+
+    /*
+    Tests that reducing the clause database while learned clauses are satisfied under the current assignments only counts
+    the live clauses it deletes, and that the satisfied clauses it keeps are not referenced again.
+    */
+    #[test]
+    fn reduce_clause_database_satisfied_clauses_test() {
+        let filename = "./test_files/incremental_clause_references_test.qdimacs".to_string();
+        let matrix = &mut CDCLMatrix::new(filename, config());
+        let statistics = &mut Statistics::new();
+        preprocess(matrix, statistics, timer());
+        for literals in vec![vec![2, 3], vec![-2, 4], vec![3, -4], vec![2, 4]] {
+            let clause = Clause::new(&literals, &matrix.core_data.variable_quantification, &matrix.core_data.quantification_order);
+            matrix.add_clause(&clause, statistics);
+        }
+        // Satisfy the first learned clause, which is deleted, and the third, which is kept, as unit propagation would.
+        for reference in [4, 6] {
+            matrix.core_data.clause_set.clause_list[reference as usize].is_removed = true;
+            matrix.core_data.clause_set.decrement_counter();
+            matrix.core_data.clause_references.retain(|&_key, &value| value != reference);
+        }
+        matrix.reduce_clause_database();
+        assert_eq!(6, matrix.core_data.clause_set.clause_list.len());
+        assert_eq!(matrix.core_data.clause_set.active_clauses().count() as i32, matrix.core_data.clause_set.clause_count);
+        assert_eq!(false, matrix.core_data.clause_references.iter_all().any(|(_literal, references)| references.contains(&4)));
+        assert_eq!(sorted_references(&rebuilt_clause_references(matrix)), sorted_references(&matrix.core_data.clause_references));
+    }
+
+    /*
+    Tests that a clause emptied purely by universal reduction during unit propagation is detected as the empty clause,
+    and propagation stops without propagating further unit literals.
+    */
+    #[test]
+    fn universal_reduction_empty_clause_test() {
+        let filename = "./test_files/universal_reduction_propagation_test.qdimacs".to_string();
+        let matrix = &mut CDCLMatrix::new(filename, config());
+        matrix.increment_decision_level();
+        unit_propagate(matrix, vec![-3], true, &mut Statistics::new());
+        assert_eq!(true, matrix.core_data.clause_set.contains_empty_clause());
+        assert_eq!(vec![1, 3], matrix.core_data.clause_set.clause_list[1].e_literals);
+        assert_eq!(1, matrix.trail.len());
+    }
+
+    /*
+    Tests that two clauses implying opposite values for one variable in a single propagation give a conflict, and that a
+    unit literal whose variable is already assigned the opposite value is caught before it is enqueued.
+    */
+    #[test]
+    fn inconsistent_unit_literal_test() {
+        let filename = "./test_files/inconsistent_unit_test.qdimacs".to_string();
+        let matrix = &mut CDCLMatrix::new(filename.clone(), config());
+        matrix.increment_decision_level();
+        unit_propagate(matrix, vec![-1], true, &mut Statistics::new());
+        assert_eq!(true, matrix.core_data.clause_set.contains_empty_clause());
+        assert_eq!(false, matrix.conflict_clause.is_none());
+
+        let matrix = &mut CDCLMatrix::new(filename, config());
+        matrix.increment_decision_level();
+        matrix.assignments.insert(2, Assignment { value: 2, decision_level: 1, clause_responsible: None });
+        unit_propagate(matrix, vec![-1], true, &mut Statistics::new());
+        assert_eq!(true, matrix.core_data.clause_set.contains_empty_clause());
+        assert_eq!(vec![1, -2], matrix.conflict_clause.as_ref().unwrap().e_literals);
+        assert_eq!(1, matrix.trail.len());
+    }
+
+    /*
+    Tests that the unassigned variables are given in prefix order, across interleaved quantifier blocks, and that 
+    assigned variables are left out after a partial assignment.
+    */
+    #[test]
+    fn unassigned_variables_test() {
+        let filename = "./test_files/interleaved_prefix_test.qdimacs".to_string();
+        let matrix = &mut CDCLMatrix::new(filename, config());
+        assert_eq!(vec![1, 2, 3, 4], matrix.unassigned_variables());
+        matrix.increment_decision_level();
+        matrix.assignments.insert(1, Assignment { value: -1, decision_level: 1, clause_responsible: None });
+        matrix.assignments.insert(3, Assignment { value: 3, decision_level: 1, clause_responsible: None });
+        assert_eq!(vec![2, 4], matrix.unassigned_variables());
+    }
+
+    /*
+    Tests that the decision learning scheme resolves past the first unique implication point, learning a longer clause 
+    made up of only decision literals.
+    */
+    #[test]
+    fn learning_scheme_test() {
+        let filename = "./test_files/learning_scheme_test.qdimacs".to_string();
+        let mut learned_clauses = Vec::new();
+        for learning_scheme in [LearningScheme::FirstUIP, LearningScheme::Decision] {
+            let mut scheme_config = config();
+            scheme_config.learning_scheme = learning_scheme;
+            let matrix = &mut CDCLMatrix::new(filename.clone(), scheme_config);
+            let statistics = &mut Statistics::new();
+            for decision in [1, 7, 2] {
+                matrix.increment_decision_level();
+                unit_propagate(matrix, vec![decision], true, statistics);
+            }
+            assert_eq!(true, matrix.core_data.clause_set.contains_empty_clause());
+            let (learned_clause, _backtrack_level) = analyse_conflict(matrix, statistics);
+            learned_clauses.push(learned_clause);
+        }
+        assert_eq!(vec![-3, -4], learned_clauses[0].e_literals);
+        assert_eq!(vec![-1, -2, -7], learned_clauses[1].e_literals);
+        assert!(learned_clauses[1].get_clause_length() > learned_clauses[0].get_clause_length());
+    }
+
+    /*
+    A function to assign a literal at the given decision level without propagating it.
+    */
+    fn assign(matrix: &mut CDCLMatrix, value: i32, decision_level: i32) {
+        let assignment = Assignment { value, decision_level, clause_responsible: None };
+        matrix.trail.push(assignment.clone());
+        matrix.assignments.insert(value.abs(), assignment);
+    }
+
+    /*
+    Tests that universal literals quantified inside every existential literal of a clause derived during conflict 
+    analysis are reduced - deciding 1 and then -2 implies 3 by (2 3) and conflicts on (-1 2 -3), and resolving on 3 
+    derives (-1 2). The universal 2 is then reduced, as it would otherwise raise the backtrack level to the decision
+    level of the conflict, so the unit clause (-1) is learned with a backtrack level of 0.
+    */
+    #[test]
+    fn learned_clause_reduction_test() {
+        let filename = "./test_files/learned_clause_reduction_test.qdimacs".to_string();
+        let matrix = &mut CDCLMatrix::new(filename, config());
+        let statistics = &mut Statistics::new();
+        for decision in [1, -2i32] {
+            // Selecting a literal takes its variable out of the prefix before it is propagated.
+            matrix.core_data.quantifier_list.retain(|quantifier| quantifier.literal != decision.abs());
+            matrix.increment_decision_level();
+            unit_propagate(matrix, vec![decision], true, statistics);
+        }
+        assert_eq!(true, matrix.core_data.clause_set.contains_empty_clause());
+        let (learned_clause, backtrack_level) = analyse_conflict(matrix, statistics);
+        assert_eq!(vec![-1], learned_clause.literals());
+        assert_eq!(0, backtrack_level);
+    }
+
+    /*
+    Tests that the backtrack level of a learned clause is the highest decision level among its other literals, even when
+    that is decision level 0, and that universal literals quantified inside the asserting literal don't raise it.
+    */
+    #[test]
+    fn calculate_backtrack_level_test() {
+        let filename = "./test_files/backjump_test.qdimacs".to_string();
+        let matrix = &mut CDCLMatrix::new(filename.clone(), config());
+        assign(matrix, 1, 0);
+        assign(matrix, 3, 1);
+        assert_eq!(0, calculate_backtrack_level(matrix, &vec![-1, -3], -3, 1));
+
+        let matrix = &mut CDCLMatrix::new(filename, config());
+        assign(matrix, 4, 1);
+        assign(matrix, 3, 2);
+        assign(matrix, -2, 3);
+        assert_eq!(1, calculate_backtrack_level(matrix, &vec![-3, 2, -4], -3, 2));
+    }
+
+    /*
+    Tests that the third stopping constraint only holds when the universal literals quantified before the asserting 
+    literal are assigned false at a decision level below it, rather than at the same or a later decision level.
+    */
+    #[test]
+    fn previous_universals_decision_level_test() {
+        let filename = "./test_files/backjump_test.qdimacs".to_string();
+        for (universal_value, universal_level, asserting_level, expected_constraint) in [(-2, 1, 2, true), (-2, 1, 1, false), (-2, 2, 1, false), (2, 1, 2, false)] {
+            let matrix = &mut CDCLMatrix::new(filename.clone(), config());
+            assign(matrix, universal_value, universal_level);
+            assign(matrix, 4, asserting_level);
+            assert_eq!(expected_constraint, all_previous_universals_assigned_correctly(matrix, &vec![2, -4], -4));
+        }
+    }
+
+    /*
+    Tests that an implied literal, such as the asserting literal of a learned clause after backjumping, is put on the 
+    trail with the clause implying it as its reason, and that it is propagated further - 1 is implied by (1 3), which 
+    then implies -3 by (-1 -3).
+    */
+    #[test]
+    fn propagate_implied_literal_test() {
+        let filename = "./test_files/backjump_test.qdimacs".to_string();
+        let matrix = &mut CDCLMatrix::new(filename, config());
+        let statistics = &mut Statistics::new();
+        matrix.increment_decision_level();
+        propagate_implied_literal(matrix, 1, 0, statistics);
+        let trail: Vec<(i32, i32, Option<i32>)> = matrix.trail.iter().map(|assignment| (assignment.value, assignment.decision_level, assignment.clause_responsible)).collect();
+        assert_eq!(vec![(1, 1, Some(0)), (-3, 1, Some(1))], trail);
+    }
+
+    /*
+    Tests that learning the last learned clause again is counted as a conflict without progress at the same decision
+    level, and that learning a new distinct clause, or learning at another decision level, resets the count.
+    */
+    #[test]
+    fn record_learned_clause_progress_test() {
+        let filename = "./test_files/repeated_conflict_test.qdimacs".to_string();
+        let matrix = &mut CDCLMatrix::new(filename, config());
+        let learned_clause = Clause::new(&[1, -2], &matrix.core_data.variable_quantification, &matrix.core_data.quantification_order);
+        matrix.increment_decision_level();
+        assert_eq!(0, matrix.record_learned_clause_progress(&learned_clause));
+        assert_eq!(1, matrix.record_learned_clause_progress(&learned_clause));
+        assert_eq!(2, matrix.record_learned_clause_progress(&learned_clause));
+        matrix.increment_decision_level();
+        assert_eq!(0, matrix.record_learned_clause_progress(&learned_clause));
+        assert_eq!(1, matrix.record_learned_clause_progress(&learned_clause));
+        let new_clause = Clause::new(&[4, 7], &matrix.core_data.variable_quantification, &matrix.core_data.quantification_order);
+        assert_eq!(0, matrix.record_learned_clause_progress(&new_clause));
+        // Only the last learned clause is tracked, so an earlier clause learned again is progress.
+        assert_eq!(0, matrix.record_learned_clause_progress(&learned_clause));
+        assert_eq!(Some(learned_clause), matrix.last_learned_clause);
+    }
+
+    /*
+    Tests that CDCL without restarts stops without progress once the last learned clause is learned again at the same
+    decision level more times than the repeated conflict limit allows, and solves the instance when no clause repeats.
+    */
+    #[test]
+    fn repeated_conflict_limit_test() {
+        let filename = "./test_files/conflict_heavy_test.qdimacs".to_string();
+        let mut limit_config = config();
+        limit_config.restarts = false;
+        limit_config.repeated_conflict_limit = 0;
+        limit_config.dump_first_learned = true;
+        let matrix = &mut CDCLMatrix::new(filename.clone(), limit_config.clone());
+        assert_eq!(Result::UNSAT, solve(matrix, &mut Statistics::new(), timer()));
+        let (first_learned_clause, backtrack_level, _is_asserting) = matrix.first_learned_clause.clone().unwrap();
+
+        // Learning the first clause again at the same decision level is a conflict without progress.
+        let matrix = &mut CDCLMatrix::new(filename.clone(), limit_config);
+        let clause = Clause::new(&first_learned_clause, &matrix.core_data.variable_quantification, &matrix.core_data.quantification_order);
+        matrix.last_learned_clause = Some(clause);
+        matrix.repeated_conflict_level = backtrack_level + 1;
+        assert_eq!(Result::NoProgress, solve(matrix, &mut Statistics::new(), timer()));
+    }
+
+    /*
+    Tests that CDCL stops with an inconclusive result once more conflicts than the conflict limit allows have been 
+    learned from, and solves the instance when the limit is not reached.
+    */
+    #[test]
+    fn max_conflicts_test() {
+        let filename = "./test_files/conflict_heavy_test.qdimacs".to_string();
+        for (max_conflicts, expected_result) in [(1, Result::ConflictLimit), (usize::MAX, Result::UNSAT)] {
+            let mut limit_config = config();
+            limit_config.max_conflicts = max_conflicts;
+            let matrix = &mut CDCLMatrix::new(filename.clone(), limit_config);
+            let statistics = &mut Statistics::new();
+            let (_invariant, _backtrack_level, result) = cdcl(matrix, None, statistics, timer());
+            assert_eq!(expected_result, result);
+            if result.eq(&Result::ConflictLimit) { assert_eq!(2, statistics.conflict_count) };
+        }
+    }
+
+    /*
+    Tests that the first non-empty learned clause is only dumped when enabled, and that it matches the clause learned by 
+    hand - deciding 1 implies 2 by (-1 2) and conflicts on (-1 -2), so resolving on 2 learns the asserting unit clause (-1)
+    with a backtrack level of 0.
+    */
+    #[test]
+    fn dump_first_learned_clause_test() {
+        let filename = "./test_files/first_learned_clause_test.qdimacs".to_string();
+        for dump_first_learned in [false, true] {
+            let mut dump_config = config();
+            dump_config.literal_selection = LiteralSelection::Ordered;
+            dump_config.pre_process = false;
+            dump_config.dump_first_learned = dump_first_learned;
+            let matrix = &mut CDCLMatrix::new(filename.clone(), dump_config);
+            let statistics = &mut Statistics::new();
+            let (_invariant, _backtrack_level, result) = cdcl(matrix, None, statistics, timer());
+            assert_eq!(Result::UNSAT, result);
+            let expected_clause = if dump_first_learned { Some((vec![-1], 0, true)) } else { None };
+            assert_eq!(expected_clause, matrix.first_learned_clause);
+        }
+    }
+
+    /*
+    Tests that the QRAT proof of a small unsatisfiable instance adds the learned unit clause (-1), and is ended with the
+    empty clause when formatted as the contradiction is then found by propagation, and that nothing is recorded when
+    QRAT proof output is disabled.
+    */
+    #[test]
+    fn qrat_proof_test() {
+        let filename = "./test_files/first_learned_clause_test.qdimacs".to_string();
+        for qrat in [false, true] {
+            let mut qrat_config = config();
+            qrat_config.literal_selection = LiteralSelection::Ordered;
+            qrat_config.pre_process = false;
+            qrat_config.qrat = qrat;
+            let matrix = &mut CDCLMatrix::new(filename.clone(), qrat_config);
+            assert_eq!(Result::UNSAT, solve(matrix, &mut Statistics::new(), timer()));
+            let expected_proof = if qrat { vec![QratStep::Add(vec![-1])] } else { Vec::new() };
+            assert_eq!(expected_proof, matrix.qrat_proof);
+        }
+        assert_eq!("-1 0\n0\n", format_qrat_proof(&[QratStep::Add(vec![-1])]));
+        assert_eq!("d 1 -2 0\n2 -3 0\n0\n", format_qrat_proof(&[QratStep::Delete(vec![1, -2]), QratStep::addition(&[-3, 2], Some(2)), QratStep::Add(Vec::new())]));
+    }
+
+    /*
+    Tests that a QRAT proof written for an unsatisfiable instance that needs several conflicts is non-empty and 
+    syntactically valid - every line is an addition, a deletion, or a universal reduction of non-zero literals terminated
+    by 0, each addition with an existential literal has an existential pivot first, and the proof ends with the empty 
+    clause.
+    */
+    #[test]
+    fn write_qrat_proof_test() {
+        let filenames = vec!["./test_files/repeated_conflict_test.qdimacs".to_string()];
+        let mut qrat_config = config();
+        qrat_config.qrat = true;
+        qrat_config.output_dir = std::env::temp_dir().to_str().unwrap().to_string();
+        let matrix = &mut CDCLMatrix::new(filenames[0].clone(), qrat_config.clone());
+        assert_eq!(Result::UNSAT, solve(matrix, &mut Statistics::new(), timer()));
+        let pathname = write_qrat_proof(&filenames, &matrix.qrat_proof, &qrat_config.output_dir);
+        let proof = fs::read_to_string(&pathname).expect("Unable to read file");
+        fs::remove_file(&pathname).expect("Unable to remove file");
+        let lines: Vec<&str> = proof.lines().collect();
+        assert_eq!(true, lines.len() > 1);
+        assert_eq!(Some(&"0"), lines.last());
+        for line in lines {
+            let is_addition = !line.starts_with("d ") && !line.starts_with("u ");
+            let tokens: Vec<i32> = line.trim_start_matches("d ").trim_start_matches("u ").split(' ').map(|token| token.parse::<i32>().unwrap()).collect();
+            assert_eq!(Some(&0), tokens.last());
+            let literals = &tokens[..tokens.len() - 1];
+            assert_eq!(false, literals.contains(&0));
+            let has_existential = literals.iter().any(|literal| matrix.core_data.variable_of(*literal).is_existential());
+            if is_addition && has_existential {
+                assert_eq!(true, matrix.core_data.variable_of(literals[0]).is_existential());
+            }
+        }
+    }
+
+    /*
+    Tests that the QRAT proof of an unsatisfiable instance solved with pre-processing on is still written, even though 
+    the steps of pre-processing aren't recorded, leaving a proof that doesn't check against the parsed clauses.
+    */
+    #[test]
+    fn qrat_proof_pre_process_test() {
+        let filenames = vec!["./test_files/repeated_conflict_test.qdimacs".to_string()];
+        let mut qrat_config = config();
+        qrat_config.pre_process = true;
+        qrat_config.qrat = true;
+        qrat_config.output_dir = std::env::temp_dir().to_str().unwrap().to_string();
+        let matrix = &mut CDCLMatrix::new(filenames[0].clone(), qrat_config);
+        let statistics = &mut Statistics::new();
+        assert_eq!(Result::UNSAT, solve(matrix, statistics, timer()));
+        let parsed_literals: Vec<Vec<i32>> = matrix.parsed_clause_list.iter().map(|clause| clause.literals()).collect();
+        assert_eq!(true, check_qrat_proof(&parsed_literals, &matrix.core_data.variable_quantification, &matrix.qrat_proof).is_err());
+        let pathname = write_unsat_qrat_proof(&filenames, matrix, statistics).unwrap();
+        let proof = fs::read_to_string(&pathname).expect("Unable to read file");
+        fs::remove_file(&pathname).expect("Unable to remove file");
+        assert_eq!(format_qrat_proof(&matrix.qrat_proof), proof);
+    }
+
+    /*
+    Tests that the QRAT proofs of unsatisfiable instances solved without pre-processing check against the parsed clauses,
+    including the universal reductions of the parsed and learned clauses, and that the proof of an instance refuted by
+    universal reduction alone no longer checks once its universal reduction steps are dropped.
+    */
+    #[test]
+    fn qrat_proof_check_test() {
+        let filenames = ["first_learned_clause_test", "universal_reduction_test", "qrat_learned_reduction_test", "convert_literals_to_clause_test", "ordered_literal_selection_test", "pure_literal_universal_test"];
+        for filename in filenames {
+            let mut qrat_config = config();
+            qrat_config.pre_process = false;
+            qrat_config.qrat = true;
+            let matrix = &mut CDCLMatrix::new(format!("./test_files/{}.qdimacs", filename), qrat_config);
+            assert_eq!(Result::UNSAT, solve(matrix, &mut Statistics::new(), timer()));
+            let parsed_literals: Vec<Vec<i32>> = matrix.parsed_clause_list.iter().map(|clause| clause.literals()).collect();
+            assert_eq!(Ok(()), check_qrat_proof(&parsed_literals, &matrix.core_data.variable_quantification, &matrix.qrat_proof));
+            if filename == "qrat_learned_reduction_test" {
+                assert_eq!(true, matrix.qrat_proof.contains(&QratStep::UniversalReduction(vec![-1, -7, -6])));
+            }
+            if filename == "universal_reduction_test" {
+                assert_eq!(true, matrix.qrat_proof.contains(&QratStep::UniversalReduction(vec![4])));
+                let proof_without_reductions: Vec<QratStep> = matrix.qrat_proof.iter().filter(|step| !matches!(step, QratStep::UniversalReduction(_))).cloned().collect();
+                assert_eq!(true, check_qrat_proof(&parsed_literals, &matrix.core_data.variable_quantification, &proof_without_reductions).is_err());
+            }
+        }
+    }
+
+    /*
+    Tests that a QRAT proof step that doesn't hold is rejected - adding a clause that isn't an asymmetric tautology, 
+    deleting a clause that isn't in the clause database, and reducing a universal literal quantified outside an 
+    existential literal of its clause - and that a proof without the empty clause is rejected.
+    */
+    #[test]
+    fn qrat_proof_check_invalid_step_test() {
+        let matrix = CDCLMatrix::new("./test_files/universal_reduction_test.qdimacs".to_string(), config());
+        let parsed_literals: Vec<Vec<i32>> = matrix.parsed_clause_list.iter().map(|clause| clause.literals()).collect();
+        let variable_quantification = &matrix.core_data.variable_quantification;
+        assert_eq!(Err("QRAT proof step 1 does not check: 1 0".to_string()), check_qrat_proof(&parsed_literals, variable_quantification, &[QratStep::Add(vec![1])]));
+        assert_eq!(Err("QRAT proof step 1 does not check: d 1 5 0".to_string()), check_qrat_proof(&parsed_literals, variable_quantification, &[QratStep::Delete(vec![1, 5])]));
+        assert_eq!(Err("QRAT proof step 1 does not check: u 2 1 -5 0".to_string()), check_qrat_proof(&parsed_literals, variable_quantification, &[QratStep::universal_reduction(&[1, 2, -5], 2)]));
+        assert_eq!(Err("QRAT proof does not derive the empty clause".to_string()), check_qrat_proof(&parsed_literals, variable_quantification, &[QratStep::universal_reduction(&[2, 3, 1], 2)]));
+        assert_eq!(Ok(()), check_qrat_proof(&parsed_literals, variable_quantification, &[QratStep::universal_reduction(&[2, 3, 4], 2), QratStep::universal_reduction(&[3, 4], 3), QratStep::universal_reduction(&[4], 4)]));
+    }
+
+    /*
+    Tests that the implication graph of the first conflict is written as DOT when enabled - deciding 1 implies 2 by 
+    (-1 2), and both falsify the conflict clause (-1 -2).
+    */
+    #[test]
+    fn dump_implication_graph_test() {
+        let filename = "./test_files/first_learned_clause_test.qdimacs".to_string();
+        let output_dir = std::env::temp_dir().join("dump_implication_graph_test");
+        let _ = fs::remove_dir_all(&output_dir);
+        let mut dump_config = config();
+        dump_config.literal_selection = LiteralSelection::Ordered;
+        dump_config.pre_process = false;
+        dump_config.dump_implication_graph = true;
+        dump_config.output_dir = output_dir.to_str().unwrap().to_string();
+        let matrix = &mut CDCLMatrix::new(filename, dump_config);
+        let (_invariant, _backtrack_level, result) = cdcl(matrix, None, &mut Statistics::new(), timer());
+        assert_eq!(Result::UNSAT, result);
+        let expected_graph = ["digraph implication_graph {",
+                              "    \"1\" [label=\"1 @ 1\", shape=box];",
+                              "    \"2\" [label=\"2 @ 1\"];",
+                              "    \"1\" -> \"2\";",
+                              "    \"conflict\" [label=\"conflict\", color=red, style=filled];",
+                              "    \"1\" -> \"conflict\";",
+                              "    \"2\" -> \"conflict\";",
+                              "}"].join("\n");
+        assert_eq!(Some(expected_graph.clone()), matrix.implication_graph);
+        let output = fs::read_to_string(output_dir.join("output-implication-graph.dot")).expect("Implication graph should be written");
+        assert_eq!(expected_graph, output);
+        fs::remove_dir_all(&output_dir).expect("Unable to remove directory");
+    }
+
+    /*
+    Tests that the clauses learned by one solve are written to a file, including the unit clause (-1) that is removed 
+    from the clause database once propagated, and reloaded into the clause database of a new matrix for the same 
+    instance as learned clauses, which is still solved correctly.
+    */
+    #[test]
+    fn learned_clauses_round_trip_test() {
+        let filename = "./test_files/first_learned_clause_test.qdimacs".to_string();
+        let mut warm_restart_config = config();
+        warm_restart_config.literal_selection = LiteralSelection::Ordered;
+        warm_restart_config.pre_process = false;
+        warm_restart_config.warm_restart = true;
+        let matrix = &mut CDCLMatrix::new(filename.clone(), warm_restart_config.clone());
+        let (_invariant, _backtrack_level, result) = cdcl(matrix, None, &mut Statistics::new(), timer());
+        assert_eq!(Result::UNSAT, result);
+        assert_eq!(true, matrix.learned_clause_history.contains(&vec![-1]));
+
+        let learned_clause_path = std::env::temp_dir().join("learned_clauses_round_trip_test.learned");
+        let learned_clause_path = learned_clause_path.to_str().unwrap();
+        let instance_hash = hash_instance_file(&filename).unwrap();
+        matrix.save_learned_clauses(learned_clause_path, instance_hash).unwrap();
+        let reloaded_matrix = &mut CDCLMatrix::new(filename, warm_restart_config);
+        let original_clause_count = reloaded_matrix.core_data.clause_set.clause_list.len();
+        assert_eq!(Ok(matrix.learned_clause_history.len()), reloaded_matrix.load_learned_clauses(learned_clause_path, instance_hash));
+        fs::remove_file(learned_clause_path).expect("Unable to remove file");
+
+        assert_eq!(matrix.learned_clause_history, reloaded_matrix.learned_clause_history);
+        assert_eq!(original_clause_count + matrix.learned_clause_history.len(), reloaded_matrix.core_data.clause_set.clause_list.len());
+        assert_eq!(reloaded_matrix.core_data.clause_set.clause_list.len(), reloaded_matrix.original_clause_list.len());
+        let reloaded_clause = reloaded_matrix.core_data.clause_set.clause_list.iter().position(|clause| clause.literals() == vec![-1]).unwrap();
+        assert_eq!(true, reloaded_matrix.learned_clause_refs.contains(&(reloaded_clause as i32)));
+        assert_eq!(true, reloaded_matrix.core_data.clause_references.get_vec(&-1).unwrap().contains(&(reloaded_clause as i32)));
+        let (_invariant, _backtrack_level, result) = cdcl(reloaded_matrix, None, &mut Statistics::new(), timer());
+        assert_eq!(Result::UNSAT, result);
+    }
+
+    /*
+    Tests that learned clauses written for one instance are not loaded for an instance with different contents, even if 
+    it has the same name, leaving the clause database unchanged.
+    */
+    #[test]
+    fn learned_clauses_instance_mismatch_test() {
+        let filename = "./test_files/first_learned_clause_test.qdimacs".to_string();
+        let mut warm_restart_config = config();
+        warm_restart_config.pre_process = false;
+        warm_restart_config.warm_restart = true;
+        let matrix = &mut CDCLMatrix::new(filename.clone(), warm_restart_config.clone());
+        let (_invariant, _backtrack_level, result) = cdcl(matrix, None, &mut Statistics::new(), timer());
+        assert_eq!(Result::UNSAT, result);
+
+        let output_dir = std::env::temp_dir().join("learned_clauses_instance_mismatch_test");
+        fs::create_dir_all(&output_dir).expect("Unable to create directory");
+        let edited_filename = output_dir.join("first_learned_clause_test.qdimacs");
+        let edited_filename = edited_filename.to_str().unwrap();
+        fs::write(edited_filename, fs::read_to_string(&filename).unwrap() + "c edited\n").expect("Unable to write file");
+        let learned_clause_path = output_dir.join("first_learned_clause_test.learned");
+        let learned_clause_path = learned_clause_path.to_str().unwrap();
+        matrix.save_learned_clauses(learned_clause_path, hash_instance_file(&filename).unwrap()).unwrap();
+
+        let edited_hash = hash_instance_file(edited_filename).unwrap();
+        assert_ne!(hash_instance_file(&filename).unwrap(), edited_hash);
+        let reloaded_matrix = &mut CDCLMatrix::new(edited_filename.to_string(), warm_restart_config);
+        let original_clause_count = reloaded_matrix.core_data.clause_set.clause_list.len();
+        assert_eq!(true, reloaded_matrix.load_learned_clauses(learned_clause_path, edited_hash).is_err());
+        fs::remove_dir_all(&output_dir).expect("Unable to remove directory");
+        assert_eq!(original_clause_count, reloaded_matrix.core_data.clause_set.clause_list.len());
+        assert_eq!(true, reloaded_matrix.learned_clause_history.is_empty());
+    }
+
+    /*
+    Tests that unit clauses in the input are propagated as fixed literals before the first decision when pre-processing 
+    is disabled.
+    */
+    #[test]
+    fn input_unit_clause_test() {
+        let filename = "./test_files/input_unit_clause_test.qdimacs".to_string();
+        let mut unit_config = config();
+        unit_config.pre_process = false;
+        let matrix = &mut CDCLMatrix::new(filename, unit_config);
+        let statistics = &mut Statistics::new();
+        let (_invariant, _backtrack_level, result) = cdcl(matrix, None, statistics, timer());
+        assert_eq!(Result::SAT, result);
+        assert_eq!(vec![-1, 2], matrix.fixed_literals);
+        assert_eq!(0, statistics.backtrack_count);
+    }
+
+    /*
+    Tests that the recorded search events are only kept when enabled, and form a coherent sequence - the search starts 
+    with a decision at level 1, every learned clause follows a conflict, and every decision after a backjump is made one 
+    level above it.
+    */
+    #[test]
+    fn record_events_test() {
+        let filename = "./test_files/repeated_conflict_test.qdimacs".to_string();
+        for record_events in [false, true] {
+            let mut events_config = config();
+            events_config.record_events = record_events;
+            let matrix = &mut CDCLMatrix::new(filename.clone(), events_config);
+            let statistics = &mut Statistics::new();
+            let (_invariant, _backtrack_level, _result) = cdcl(matrix, None, statistics, timer());
+            if !record_events {
+                assert!(matrix.events.is_empty());
+                continue;
+            }
+            assert!(matches!(matrix.events[0], SearchEvent::Decision { decision_level: 1, .. }));
+            assert!(matrix.events.iter().any(|event| matches!(event, SearchEvent::Learned { .. })));
+            for (i, event) in matrix.events.iter().enumerate().skip(1) {
+                match (&matrix.events[i - 1], event) {
+                    (_, SearchEvent::Learned { .. }) => assert!(matches!(matrix.events[i - 1], SearchEvent::Conflict { .. })),
+                    (SearchEvent::Backjump { decision_level: backjump_level }, SearchEvent::Decision { decision_level, .. }) => assert_eq!(backjump_level + 1, *decision_level),
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    /*
+    Tests that the peak clause count covers the clause database of the input, and is never lowered by the clauses 
+    removed during search.
+    */
+    #[test]
+    fn peak_clause_count_test() {
+        let filename = "./test_files/repeated_conflict_test.qdimacs".to_string();
+        let matrix = &mut CDCLMatrix::new(filename, config());
+        let input_clause_count = matrix.core_data.clause_set.clause_count;
+        let statistics = &mut Statistics::new();
+        assert_eq!(Result::UNSAT, solve(matrix, statistics, timer()));
+        assert!(statistics.learned_clause_count > 0);
+        assert_eq!(input_clause_count, statistics.peak_clause_count);
+        statistics.update_peak_clause_count(input_clause_count - 1);
+        assert_eq!(input_clause_count, statistics.peak_clause_count);
+    }
+
+    /*
+    Tests that the learned literal total grows by the length of each clause added to the clause database, and that the 
+    average learned clause length is taken over the learned clause count.
+    */
+    #[test]
+    fn learned_literal_total_test() {
+        let filename = "./test_files/incremental_clause_references_test.qdimacs".to_string();
+        let matrix = &mut CDCLMatrix::new(filename, config());
+        let statistics = &mut Statistics::new();
+        assert_eq!(0.0, statistics.average_learned_clause_length());
+        let mut expected_total = 0;
+        for literals in vec![vec![2, 3], vec![-2, 4, 3], vec![4]] {
+            let clause = Clause::new(&literals, &matrix.core_data.variable_quantification, &matrix.core_data.quantification_order);
+            matrix.add_clause(&clause, statistics);
+            statistics.increment_learned_clause_count();
+            expected_total += literals.len() as i32;
+            assert_eq!(expected_total, statistics.learned_literal_total);
+        }
+        assert_eq!(2.0, statistics.average_learned_clause_length());
+    }
+
+    /*
+    Tests that the debug state lists the decision level, the remaining prefix by quantifier block, the clauses that 
+    haven't been removed, the trail, and the assignments after a decision.
+    */
+    #[test]
+    fn debug_state_test() {
+        let filename = "./test_files/get_quantifier_type_test1.qdimacs".to_string();
+        let matrix = &mut CDCLMatrix::new(filename, config());
+        matrix.increment_decision_level();
+        unit_propagate(matrix, vec![1], true, &mut Statistics::new());
+        let expected_state = ["Decision level: 1",
+                              "Prefix:",
+                              "  e 2 3",
+                              "  a 4 5 6",
+                              "  e 7",
+                              "Clauses:",
+                              "  1: e [2, 3] a [4]",
+                              "  2: e [] a [4, 5, 6]",
+                              "  3: e [7] a [6]",
+                              "Trail:",
+                              "  Level 1: Decision 1",
+                              "Assignments:",
+                              "  1 = 1 (level 1)"].join("\n");
+        assert_eq!(expected_state, matrix.debug_state());
+    }
+
+    /*
+    Tests that removing several original and learned clauses at once remaps the learned clause references to the new 
+    positions of the surviving learned clauses.
+    */
+    #[test]
+    fn simplify_constraints_learned_clause_refs_test() {
+        let filename = "./test_files/incremental_clause_references_test.qdimacs".to_string();
+        let matrix = &mut CDCLMatrix::new(filename, config());
+        let statistics = &mut Statistics::new();
+        preprocess(matrix, statistics, timer());
+        for literals in vec![vec![2, 3], vec![-2, 4], vec![3, -4], vec![2, 4]] {
+            let clause = Clause::new(&literals, &matrix.core_data.variable_quantification, &matrix.core_data.quantification_order);
+            matrix.add_clause(&clause, statistics);
+        }
+        assert_eq!(vec![4, 5, 6, 7], matrix.learned_clause_refs);
+        let surviving_clauses = vec![matrix.core_data.clause_set.clause_list[5].clone(), matrix.core_data.clause_set.clause_list[7].clone()];
+        for index in [0, 2, 4, 6] {
+            matrix.core_data.clause_set.clause_list[index].is_removed = true;
+            matrix.core_data.clause_set.decrement_counter();
+        }
+        simplify_constraints(matrix);
+        assert_eq!(vec![2, 3], matrix.learned_clause_refs);
+        let learned_clauses: Vec<Clause> = matrix.learned_clause_refs.iter().map(|reference| matrix.core_data.clause_set.clause_list[*reference as usize].clone()).collect();
+        assert_eq!(surviving_clauses, learned_clauses);
+        assert_eq!(surviving_clauses, matrix.original_clause_list[2..].to_vec());
+        assert_eq!(sorted_references(&rebuilt_clause_references(matrix)), sorted_references(&matrix.core_data.clause_references));
+    }
+
+    /*
+    Tests that CDCL keeps the assignments made before the prefix is found empty when verification is enabled, so they 
+    are there to be verified. Variable 3 doesn't appear in any clause, so assigning it leaves the clauses unchanged.
+    */
+    #[test]
+    fn void_prefix_sat_assignments_test() {
+        let filename = "./test_files/void_prefix_test.qdimacs".to_string();
+        let mut void_config = config();
+        void_config.pre_process = false;
+        void_config.universal_reduction = false;
+        void_config.verify = true;
+        let matrix = &mut CDCLMatrix::new(filename, void_config);
+        let statistics = &mut Statistics::new();
+        matrix.increment_decision_level();
+        unit_propagate(matrix, vec![3], true, statistics);
+        matrix.core_data.quantifier_list.clear();
+        assert_eq!(Result::SAT, solve(matrix, statistics, timer()));
+        assert_eq!(vec![3], matrix.sat_assignments.values().map(|assignment| assignment.value).collect::<Vec<i32>>());
+    }
+
+    /*
+    Tests that solving the existential tail as SAT once the universals are assigned gives the same result as the QBF 
+    search, on a true and a false instance with two existential blocks after the universal block, and that the
+    satisfying assignment from the tail passes verification.
+    */
+    #[test]
+    fn sat_tail_test() {
+        let mut sat_tail_config = config();
+        sat_tail_config.sat_tail = true;
+        sat_tail_config.verify = true;
+        for (filename, expected_result) in [("./test_files/sat_tail_test1.qdimacs", Result::SAT), ("./test_files/sat_tail_test2.qdimacs", Result::UNSAT)] {
+            let matrix = &mut CDCLMatrix::new(filename.to_string(), config());
+            assert_eq!(expected_result, solve(matrix, &mut Statistics::new(), Instant::now()));
+
+            let matrix = &mut CDCLMatrix::new(filename.to_string(), sat_tail_config.clone());
+            let statistics = &mut Statistics::new();
+            assert_eq!(expected_result, solve(matrix, statistics, Instant::now()));
+            assert_eq!(true, statistics.sat_tail_count > 0);
+            if expected_result.eq(&Result::SAT) {
+                assert_eq!(true, verify_assignment(matrix, &matrix.sat_assignments));
+            }
+        }
+    }
+
+    /*
+    Tests that an instance with no universal quantifiers is solved by the SAT procedure from the root when SATTail is 
+    enabled, without any decision in the QBF search, and by the QBF search otherwise.
+    */
+    #[test]
+    fn sat_tail_propositional_test() {
+        let filename = "./test_files/pure_sat_test.qdimacs".to_string();
+        for sat_tail in [false, true] {
+            let mut sat_tail_config = config();
+            sat_tail_config.sat_tail = sat_tail;
+            sat_tail_config.verify = true;
+            let matrix = &mut CDCLMatrix::new(filename.clone(), sat_tail_config);
+            let statistics = &mut Statistics::new();
+            assert_eq!(Result::SAT, solve(matrix, statistics, Instant::now()));
+            assert_eq!(sat_tail, statistics.sat_tail_count == 1);
+            assert_eq!(sat_tail, statistics.max_decision_level == 0);
+            assert_eq!(true, verify_assignment(matrix, &matrix.sat_assignments));
+        }
+    }
+
+    /*
+    Tests that the SAT procedure used for the existential tail finds a model satisfying every clause, and proves that 
+    three pigeons don't fit in two holes, which needs clause learning and backjumping.
+    */
+    #[test]
+    fn solve_sat_test() {
+        let clauses = vec![vec![1, 2], vec![-1, 3], vec![-2, -3], vec![-3, 4, 5], vec![-4], vec![2, 5]];
+        let result = solve_sat(&clauses, &config(), Instant::now());
+        let model = match result {
+            SATResult::SAT(model) => model,
+            _ => panic!("Expected a model, got {:?}", result),
+        };
+        assert_eq!(true, clauses.iter().all(|clause| clause.iter().any(|literal| model.contains(literal))));
+
+        // Variable 2 * pigeon + hole - 2 is true if the pigeon is in the hole.
+        let mut pigeonhole_clauses = vec![vec![1, 2], vec![3, 4], vec![5, 6]];
+        for hole in 1..=2 {
+            for first in 1..=3 {
+                for second in (first + 1)..=3 {
+                    pigeonhole_clauses.push(vec![-(2 * first + hole - 2), -(2 * second + hole - 2)]);
+                }
+            }
+        }
+        assert_eq!(SATResult::UNSAT, solve_sat(&pigeonhole_clauses, &config(), Instant::now()));
+    }
+
+    fn rebuilt_clause_references(matrix: &CDCLMatrix) -> MultiMap<i32, i32> {
+        let mut rebuilt_matrix = matrix.clone();
+        rebuilt_matrix.refresh_clause_references();
+        return rebuilt_matrix.core_data.clause_references;
+    }
+
+    fn sorted_references(clause_references: &MultiMap<i32, i32>) -> Vec<(i32, Vec<i32>)> {
+        let mut references: Vec<(i32, Vec<i32>)> = clause_references.iter_all().map(|(literal, references)| {
+            let mut references = references.clone();
+            references.sort();
+            (*literal, references)
+        }).collect();
+        references.sort();
+        return references;
+    }
+
+    /*
+    Tests that an instance the Ordered selection can't solve within half of the conflict limit is retried and solved 
+    with the fallback VSS selection under the full limit.
+    */
+    #[test]
+    fn fallback_literal_selection_test() {
+        let filenames = vec!["./test_files/conflict_heavy_test.qdimacs".to_string()];
+        let mut config = config();
+        config.fallback_literal_selection = Some(LiteralSelection::VariableStateSum);
+        config.max_conflicts = 16;
+        config.deterministic = true;
+        let timer = timer();
+        let matrix = &mut CDCLMatrix::from_matrix(Matrix::from_files(&filenames, config.first_attempt_config()).unwrap());
+        assert_eq!(8, matrix.core_data.config.max_conflicts);
+        let statistics = &mut Statistics::new();
+        let first_result = solve(matrix, statistics, timer);
+        assert_eq!(Result::ConflictLimit, first_result);
+        let result = retry_with_fallback(&filenames, &config, matrix, statistics, first_result, timer, &None);
+        assert_eq!(Result::UNSAT, result);
+        assert_eq!(LiteralSelection::VariableStateSum, matrix.core_data.config.literal_selection);
+        assert_eq!(16, matrix.core_data.config.max_conflicts);
+    }
+
+    /*
+    Tests that a retry reloads the clauses of the learned clause file into the rebuilt matrix, as they were loaded for the
+    first attempt, and that the first result is kept when the instance can no longer be read for the retry.
+    */
+    #[test]
+    fn fallback_retry_reload_test() {
+        let output_dir = std::env::temp_dir().join("fallback_retry_reload_test");
+        fs::create_dir_all(&output_dir).expect("Unable to create directory");
+        let filename = output_dir.join("conflict_heavy_test.qdimacs").to_str().unwrap().to_string();
+        fs::copy("./test_files/conflict_heavy_test.qdimacs", &filename).expect("Unable to copy file");
+        let filenames = vec![filename.clone()];
+        let mut config = config();
+        config.fallback_literal_selection = Some(LiteralSelection::VariableStateSum);
+        config.max_conflicts = 16;
+        config.deterministic = true;
+        config.warm_restart = true;
+        let learned_clause_path = output_dir.join("conflict_heavy_test.learned").to_str().unwrap().to_string();
+        let learned_clause_file = Some((learned_clause_path.clone(), hash_instance_file(&filename).unwrap()));
+
+        let timer = timer();
+        let matrix = &mut CDCLMatrix::from_matrix(Matrix::from_files(&filenames, config.first_attempt_config()).unwrap());
+        let statistics = &mut Statistics::new();
+        let first_result = solve(matrix, statistics, timer);
+        assert_eq!(Result::ConflictLimit, first_result);
+        matrix.save_learned_clauses(&learned_clause_path, learned_clause_file.as_ref().unwrap().1).unwrap();
+        let saved_clause_count = matrix.learned_clause_history.len();
+        assert_eq!(true, saved_clause_count > 0);
+        let result = retry_with_fallback(&filenames, &config, matrix, statistics, first_result.clone(), timer, &learned_clause_file);
+        assert_eq!(Result::UNSAT, result);
+        assert_eq!(saved_clause_count, matrix.loaded_clause_count);
+
+        let matrix = &mut CDCLMatrix::from_matrix(Matrix::from_files(&filenames, config.first_attempt_config()).unwrap());
+        fs::remove_dir_all(&output_dir).expect("Unable to remove directory");
+        let result = retry_with_fallback(&filenames, &config, matrix, statistics, first_result.clone(), timer, &None);
+        assert_eq!(first_result, result);
+        assert_eq!(8, matrix.core_data.config.max_conflicts);
+    }
+
     /* END OF GENERAL INSTANCE TESTS */
 }
\ No newline at end of file
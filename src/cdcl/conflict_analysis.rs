@@ -1,5 +1,5 @@
-use std::cmp;
-use crate::{data_structures::{CDCLMatrix, QuantifierType, Clause, Statistics}, util::convert_literals_to_clause, resolution::resolve};
+use std::{cmp, fs};
+use crate::{log_normal, data_structures::{CDCLMatrix, Clause, Statistics, LearningScheme, SearchEvent, Trail}, qrat::QratStep, universal_reduction::detect_universal_literal, util::get_output_pathname};
 
 /*
 A function to get the literal with the highest decision level from a list of literals.
@@ -10,9 +10,8 @@ pub fn get_highest_decision_level(matrix: &CDCLMatrix, literals: &Vec<i32>) -> (
     let mut highest_decision_level = -1;
     let mut highest_decision_literal = -1;
     for literal in literals {
-        let quantification_type = &matrix.core_data.variable_quantification.get(&literal.abs()).expect("Variable quantification missing literal").q_type;
         let assignment = matrix.assignments.get(&literal.abs()).expect("Assignment store missing literal");
-        if quantification_type.eq(&QuantifierType::Existential) {
+        if matrix.core_data.variable_of(*literal).is_existential() {
             if highest_decision_level < assignment.decision_level {
                 highest_decision_level = assignment.decision_level;
                 highest_decision_literal = *literal;
@@ -33,9 +32,8 @@ pub fn contains_one_highest_decision_literal(matrix: &CDCLMatrix, literals: &Vec
     let (v, highest_decision_level) = get_highest_decision_level(matrix, literals);
     let mut two_highest_decision_literals = false;
     for literal in literals {
-        let quantification_type = &matrix.core_data.variable_quantification.get(&literal.abs()).expect("Variable quantification missing literal").q_type;
         let assignment = matrix.assignments.get(&literal.abs()).expect("Assignment store missing literal");
-        if quantification_type.eq(&QuantifierType::Existential) {
+        if matrix.core_data.variable_of(*literal).is_existential() {
             if assignment.decision_level == highest_decision_level && v != *literal {
                 two_highest_decision_literals = true;
                 break;
@@ -53,41 +51,38 @@ decision literal is at a decision level with an existential variable as its bran
 Returns (whether the constraint is met or not).
 */
 pub fn contains_highest_decision_level_decision(matrix: &CDCLMatrix, highest_decision_level: i32) -> bool {
-    let mut new_trail = matrix.trail.clone();
-    let mut is_existential = false;
-    loop {
-        let assignment = new_trail.pop().expect("Trail missing assignment literal");
-        if assignment.decision_level == highest_decision_level {
-            if assignment.is_decision() { 
-                let quantification = &matrix.core_data.variable_quantification.get(&assignment.value.abs()).expect("Variable quantification missing literal").q_type;
-                if quantification.eq(&QuantifierType::Existential) {
-                    is_existential = true;
-                }
-                break;
-            }
-        }
-
-        if assignment.decision_level < highest_decision_level { break };
+    let decision = matrix.trail.last_decision_at_level(highest_decision_level);
+    if decision.is_none() {
+        return false;
     }
-    return is_existential;
+    return matrix.core_data.variable_of(decision.unwrap().value).is_existential();
+}
+
+/*
+A function to check whether every literal in a list of literals is assigned false.
+
+Returns (whether all literals are false).
+*/
+fn is_falsified(matrix: &CDCLMatrix, literals: &Vec<i32>) -> bool {
+    return literals.iter().all(|literal| matrix.assignments.get(&literal.abs()).map_or(false, |assignment| assignment.value == -literal));
 }
 
 /*
 --- Stopping Constraint 3 ---
 This function checks the third stopping constraint for my conflict analysis procedure. It checks that all universally
-quantified literals with a smaller quantification level than the highest decision literal are assigned 0.
+quantified literals with a smaller quantification level than the highest decision literal are assigned 0
+at a decision level below it.
 
 Returns (whether the constraint is met or not).
 */
 pub fn all_previous_universals_assigned_correctly(matrix: &CDCLMatrix, literals: &Vec<i32>, highest_decision_literal: i32) -> bool {
     let mut is_valid = true;
-    let hdl_quantification_level = matrix.core_data.variable_quantification.get(&highest_decision_literal.abs()).expect("Variable quantification missing literal").q_level;
+    let hdl_quantification_level = matrix.core_data.level_of(highest_decision_literal).expect("Variable quantification missing literal");
     for literal in literals {
-        let quantification_variable = matrix.core_data.variable_quantification.get(&literal.abs()).expect("Variable quantification missing literal");
-        if quantification_variable.q_type.eq(&QuantifierType::Universal) {
-            if quantification_variable.q_level < hdl_quantification_level {
+        if matrix.core_data.variable_of(*literal).is_universal() {
+            if matrix.core_data.level_of(*literal).expect("Variable quantification missing literal") < hdl_quantification_level {
                 let assignment = matrix.assignments.get(&literal.abs()).expect("Assignment store missing literal");
-                if assignment.value != -literal {
+                if assignment.value != -literal || assignment.decision_level >= matrix.assignments.get(&highest_decision_literal.abs()).expect("Assignment store missing literal").decision_level {
                     is_valid = false;
                     break;
                 }
@@ -104,18 +99,24 @@ will fuel further implications.
 
 Returns (the backtrack level).
 */
-pub fn calculate_backtrack_level(matrix: &CDCLMatrix, literals: &Vec<i32>, highest_decision_level: i32) -> i32 {
+pub fn calculate_backtrack_level(matrix: &CDCLMatrix, literals: &Vec<i32>, highest_decision_literal: i32, highest_decision_level: i32) -> i32 {
     let mut backtrack_level = -1;
+    let highest_level_literals = matrix.trail.literals_at_level(highest_decision_level);
+    let hdl_quantification_level = matrix.core_data.level_of(highest_decision_literal).expect("Variable quantification missing literal");
     for literal in literals {
-        let assignment = matrix.assignments.get(&literal.abs()).expect("Assignment store missing literal");
-        if assignment.decision_level == highest_decision_level {
+        if highest_level_literals.iter().any(|assigned_literal| assigned_literal.abs() == literal.abs()) {
+            continue;
+        }
+        // Universals quantified inside the asserting literal are reduced once unassigned, so don't hold the backjump.
+        if matrix.core_data.variable_of(*literal).is_universal()
+            && matrix.core_data.level_of(*literal).expect("Variable quantification missing literal") > hdl_quantification_level {
             continue;
         }
+        let assignment = matrix.assignments.get(&literal.abs()).expect("Assignment store missing literal");
         backtrack_level = cmp::max(backtrack_level, assignment.decision_level);
     }
     // Catch edge cases.
     if backtrack_level == -1 { backtrack_level = highest_decision_level - 1 }
-    if literals.len() > 1 && backtrack_level == 0 { backtrack_level = 1 }
     return backtrack_level
 }
 
@@ -130,8 +131,7 @@ pub fn check_unsatisfiability_criteria(matrix: &CDCLMatrix, literals: &Vec<i32>)
     let mut only_universals = true;
     let mut existentials_at_level_0 = true;
     for literal in literals {
-        let quantification = &matrix.core_data.variable_quantification.get(&literal.abs()).expect("Variable quantification missing literal").q_type;
-        if quantification.eq(&QuantifierType::Existential) {
+        if matrix.core_data.variable_of(*literal).is_existential() {
             let assignment = matrix.assignments.get(&literal.abs()).expect("Assignment store missing literal");
             if assignment.decision_level > 0 {
                 existentials_at_level_0 = false;
@@ -146,11 +146,143 @@ pub fn check_unsatisfiability_criteria(matrix: &CDCLMatrix, literals: &Vec<i32>)
     }
 }
 
+/*
+A function to describe the implication graph leading to a conflict in Graphviz DOT. Each assigned literal on the trail is 
+a node labelled with its decision level, with decisions drawn as boxes. An implied literal has an edge from the assigned 
+complement of every other literal in the clause responsible for it, and the highlighted conflict node has an edge from 
+the assigned complement of every literal in the conflict clause.
+
+Returns the DOT description of the implication graph.
+*/
+pub fn format_implication_graph(matrix: &CDCLMatrix, conflict: &Clause) -> String {
+    let mut lines = vec!["digraph implication_graph {".to_string()];
+    let antecedents = |clause: &Clause, implied_literal: i32| -> Vec<i32> {
+        return clause.literals().iter()
+                    .filter(|literal| **literal != implied_literal)
+                    .filter(|literal| matrix.assignments.get(&literal.abs()).is_some_and(|assignment| assignment.value == -**literal))
+                    .map(|literal| -literal)
+                    .collect();
+    };
+    for assignment in matrix.trail.iter() {
+        let shape = if assignment.is_decision() {", shape=box"} else {""};
+        lines.push(format!("    \"{}\" [label=\"{} @ {}\"{}];", assignment.value, assignment.value, assignment.decision_level, shape));
+        if assignment.is_implication() {
+            let clause_responsible = &matrix.original_clause_list[assignment.responsible_clause().unwrap() as usize];
+            for antecedent in antecedents(clause_responsible, assignment.value) {
+                lines.push(format!("    \"{}\" -> \"{}\";", antecedent, assignment.value));
+            }
+        }
+    }
+    lines.push("    \"conflict\" [label=\"conflict\", color=red, style=filled];".to_string());
+    for antecedent in antecedents(conflict, 0) {
+        lines.push(format!("    \"{}\" -> \"conflict\";", antecedent));
+    }
+    lines.push("}".to_string());
+    return lines.join("\n");
+}
+
+/*
+A function to write the implication graph of the first conflict with a conflict clause as DOT to the output directory. 
+The graph is stored in the matrix, which stops the graph of any later conflict being written.
+*/
+pub fn dump_implication_graph(matrix: &mut CDCLMatrix, conflict: &Clause) {
+    let implication_graph = format_implication_graph(matrix, conflict);
+    let pathname = get_output_pathname(&matrix.core_data.config.output_dir, "implication-graph.dot");
+    fs::write(&pathname, &implication_graph).expect("Unable to write file");
+    log_normal!(matrix.core_data.config.verbosity, "Implication graph written to {}", pathname);
+    matrix.implication_graph = Some(implication_graph);
+}
+
+/*
+A function to apply universal reduction to a clause derived during conflict analysis, removing its universal literals 
+quantified inside all of its existential literals. Otherwise such a universal literal, assigned at a later decision 
+level than the asserting literal, would raise the backtrack level above the current decision level. A clause with no 
+existential literals is kept, as it already meets the unsatisfiability criteria.
+*/
+fn reduce_learned_clause(matrix: &CDCLMatrix, mut clause: Clause) -> Clause {
+    if clause.e_literals.is_empty() { return clause };
+    let universal_literals = detect_universal_literal(&clause, &matrix.core_data.variable_quantification);
+    clause.remove_a_literals(universal_literals);
+    return clause;
+}
+
+/*
+A function to record a learned clause in the QRAT proof, if QRAT proof output is enabled. The pivot is the existential
+literal at the highest decision level, which the clause is asserting on, or the first literal if the clause only has
+universal literals. This is only needed when the learned clause is a clause of the clause database, as every resolvent
+is already recorded as it is derived.
+*/
+fn record_qrat_learned_clause(matrix: &mut CDCLMatrix, literals: &Vec<i32>) {
+    if !matrix.core_data.config.qrat_enabled() { return };
+    let has_existential = literals.iter().any(|literal| matrix.core_data.variable_of(*literal).is_existential());
+    let pivot = if has_existential { Some(get_highest_decision_level(matrix, literals).0) } else { literals.first().copied() };
+    matrix.record_qrat_step(QratStep::addition(literals, pivot));
+}
+
+/*
+A function to delete a clause derived during conflict analysis from the QRAT proof once it has been resolved on or is
+given up on. A clause of the clause database, e.g. the conflict clause, is never deleted.
+*/
+fn discard_qrat_clause(matrix: &mut CDCLMatrix, literals: &Vec<i32>, is_derived: bool) {
+    if is_derived {
+        matrix.record_qrat_step(QratStep::Delete(literals.clone()));
+    }
+}
+
+/*
+A function to resolve the clause being learned with the reason clause of an implied literal and apply universal 
+reduction to the resolvent. The resolvent is recorded in the QRAT proof before any literal is reduced, as it is then an
+asymmetric tautology of the two clauses it was resolved from, and the clause it replaces is then deleted.
+
+Returns the reduced resolvent, or None if the resolvent is tautological.
+*/
+fn resolve_with_reason(matrix: &mut CDCLMatrix, clause: &Clause, is_derived: bool, implied_literal: i32, reason_index: usize) -> Option<Clause> {
+    let clause_responsible = matrix.original_clause_list[reason_index].clone();
+    let resolvent = clause.resolvent_with(&clause_responsible, implied_literal, &matrix.core_data.quantification_order);
+    if resolvent.is_none() { return None };
+    let resolvent = resolvent.unwrap();
+    if !matrix.core_data.config.qrat_enabled() { return Some(reduce_learned_clause(matrix, resolvent)) };
+    let resolvent_literals = resolvent.literals();
+    matrix.record_qrat_step(QratStep::Add(resolvent_literals.clone()));
+    discard_qrat_clause(matrix, &clause.literals(), is_derived);
+    let reduced_resolvent = reduce_learned_clause(matrix, resolvent);
+    matrix.record_qrat_reduction(&resolvent_literals, &reduced_resolvent.literals());
+    return Some(reduced_resolvent);
+}
+
+/*
+A function to record the rest of a refutation in the QRAT proof once the clause being learned meets the 
+unsatisfiability criteria, if QRAT proof output is enabled. Its existential literals, all assigned at decision level 0,
+are resolved away with the reason clauses further down the trail, and its universal literals are then removed by 
+universal reduction, leaving the empty clause.
+*/
+fn record_qrat_refutation(matrix: &mut CDCLMatrix, trail: &mut Trail, mut clause: Clause, mut is_derived: bool) {
+    if !matrix.core_data.config.qrat_enabled() { return };
+    while !clause.e_literals.is_empty() {
+        let assignment = trail.pop();
+        if assignment.is_none() { break };
+        let assignment = assignment.unwrap();
+        if !assignment.is_implication() || !clause.e_literals.contains(&-assignment.value) { continue };
+        let resolvent = resolve_with_reason(matrix, &clause, is_derived, assignment.value, assignment.responsible_clause().unwrap() as usize);
+        // The proof is left without the empty clause, so it won't check and isn't written.
+        if resolvent.is_none() { return };
+        clause = resolvent.unwrap();
+        is_derived = true;
+    }
+    if !clause.e_literals.is_empty() { return };
+    if !is_derived {
+        matrix.record_qrat_step(QratStep::Add(clause.literals()));
+    }
+    matrix.record_qrat_reduction(&clause.literals(), &Vec::new());
+    matrix.record_qrat_step(QratStep::Add(Vec::new()));
+}
+
 /*
 This function will analyse a given conflict given it occurs on an existential literal assignment. It will iteratively
 perform Q-Resolution on the conflict clause and its literals until certain stopping constraints are met. These ensure 
 that upon backjumping/backtracking the learned clause is unit, based upon 1UIP, and that we don't only undo universal
-decisions as they can't resolve a conflict.
+decisions as they can't resolve a conflict. With the Decision learning scheme, resolution instead continues until only 
+decision literals remain.
 
 Note: On unsatisfiability, it will return the empty clause and a backtrack level of -1. This will exit the procedure 
 and return unsatisfiable.
@@ -160,31 +292,68 @@ Returns (the learned clause, backtrack_level)
 pub fn analyse_conflict(matrix: &mut CDCLMatrix, statistics: &mut Statistics) -> (Clause, i32) {
     // If conflict hit as a direct result of a universal literal, conflict learning is not applicable so naively backtrack. 
     if matrix.conflict_clause.is_none() {
+        matrix.record_event(SearchEvent::Conflict { decision_level: matrix.decision_level, conflict_clause: Vec::new() });
         return (Clause::new_empty_clause(), matrix.decision_level);
     }
     statistics.increment_learned_clause_count();
     let conflict = matrix.conflict_clause.clone().expect("Conflict clause expected in analyse_conflict");
     matrix.reset_conflict_clause();
+    if matrix.core_data.config.dump_implication_graph_enabled() && matrix.implication_graph.is_none() {
+        dump_implication_graph(matrix, &conflict);
+    }
+    matrix.record_event(SearchEvent::Conflict { decision_level: matrix.decision_level, conflict_clause: conflict.literals() });
     let mut trail = matrix.trail.clone();
-    let mut current_literals = conflict.get_literal_list();
+    let conflict_literals = conflict.literals();
+    let mut current_clause = reduce_learned_clause(matrix, conflict);
+    let mut current_literals = current_clause.literals();
+    // The conflict clause stays in the clause database, so the proof reduces a copy of it.
+    let mut is_derived = current_literals.len() < conflict_literals.len();
+    if is_derived {
+        matrix.record_qrat_step(QratStep::Add(conflict_literals.clone()));
+        matrix.record_qrat_reduction(&conflict_literals, &current_literals);
+    }
+    // A clause made empty by reducing unassigned universals has no implication graph to learn from.
+    if !is_falsified(matrix, &current_literals) {
+        discard_qrat_clause(matrix, &current_literals, is_derived);
+        return (Clause::new_empty_clause(), matrix.decision_level);
+    }
     let mut backtrack_level;
     loop {
         if trail.len() == 0 {
-            let (_highest_decision_literal, highest_decision_level, _constraint_one) = contains_one_highest_decision_literal(matrix, &current_literals);
-            backtrack_level = calculate_backtrack_level(matrix, &current_literals, highest_decision_level);
+            let (highest_decision_literal, highest_decision_level, constraint_one) = contains_one_highest_decision_literal(matrix, &current_literals);
+            // Without a unique implication point the clause wouldn't be asserting after backjumping, e.g. when a flipped
+            // decision shares its decision level with the decision above it, so naively backtrack instead.
+            if !constraint_one || !contains_highest_decision_level_decision(matrix, highest_decision_level) 
+                || !all_previous_universals_assigned_correctly(matrix, &current_literals, highest_decision_literal) {
+                discard_qrat_clause(matrix, &current_literals, is_derived);
+                return (Clause::new_empty_clause(), matrix.decision_level);
+            }
+            backtrack_level = calculate_backtrack_level(matrix, &current_literals, highest_decision_literal, highest_decision_level);
             break;
         }
         let mut resolution_occurred = false;
         let assignment = trail.pop().unwrap();
-        if !assignment.is_decision() {
-            let quantification_type = &matrix.core_data.variable_quantification.get(&assignment.value.abs()).unwrap().q_type;
-            if quantification_type.eq(&QuantifierType::Existential) {
+        if assignment.is_implication() {
+            if matrix.core_data.variable_of(assignment.value).is_existential() {
                 if current_literals.contains(&assignment.value) || current_literals.contains(&-assignment.value) {
-                    let clause_responsible = matrix.original_clause_list[assignment.clause_responsible.unwrap() as usize].clone();
-                    let resolved_literals = resolve(current_literals, clause_responsible.get_literal_list(), assignment.value).expect("Resolution shouldn't be invalid here.");
-                    current_literals = resolved_literals;
+                    // A reason clause made unit by reducing an unassigned universal can carry that universal into the
+                    // resolvent, which is then tautological or no longer false, so naively backtrack instead.
+                    let resolvent = resolve_with_reason(matrix, &current_clause, is_derived, assignment.value, assignment.responsible_clause().unwrap() as usize);
+                    if resolvent.is_none() {
+                        discard_qrat_clause(matrix, &current_literals, is_derived);
+                        return (Clause::new_empty_clause(), matrix.decision_level);
+                    }
+                    current_clause = resolvent.unwrap();
+                    current_literals = current_clause.literals();
+                    is_derived = true;
+                    if !is_falsified(matrix, &current_literals) {
+                        discard_qrat_clause(matrix, &current_literals, is_derived);
+                        return (Clause::new_empty_clause(), matrix.decision_level);
+                    }
                     // Check unsatisfiability constraints.
                     if check_unsatisfiability_criteria(matrix, &current_literals) {
+                        record_qrat_refutation(matrix, &mut trail, current_clause, is_derived);
+                        matrix.record_event(SearchEvent::Learned { learned_clause: Vec::new(), backtrack_level: -1 });
                         return (Clause::new_empty_clause(), -1);
                     }
                     resolution_occurred = true;
@@ -193,6 +362,9 @@ pub fn analyse_conflict(matrix: &mut CDCLMatrix, statistics: &mut Statistics) ->
         }
         if !resolution_occurred { continue }; // If no new resolution, constraints still not met.
 
+        // The decision scheme resolves every implication, so only stops once the trail is exhausted.
+        if matrix.core_data.config.learning_scheme.eq(&LearningScheme::Decision) { continue };
+
         // Stopping constraint 1 - Among all its existential variables, only one of them has the highest decision level.
         let (highest_decision_literal, highest_decision_level, constraint_one) = contains_one_highest_decision_literal(matrix, &current_literals);
         if !constraint_one { continue };
@@ -207,14 +379,16 @@ pub fn analyse_conflict(matrix: &mut CDCLMatrix, statistics: &mut Statistics) ->
         if !constraint_three { continue };
 
         // Determine level to backtrack to.
-        backtrack_level = calculate_backtrack_level(matrix, &current_literals, highest_decision_level);
+        backtrack_level = calculate_backtrack_level(matrix, &current_literals, highest_decision_literal, highest_decision_level);
         break;
     }
     // If learned clause is a unit clause, I want to backtrack to level 0 and simplify the problem.
     if current_literals.len() == 1 {
         backtrack_level = 0;
     }
-    let clause = convert_literals_to_clause(&matrix.core_data.variable_quantification, &matrix.core_data.quantification_order, &current_literals);
-
-    return (clause, backtrack_level); // if backtrack_level = -1 --> return unsatisfiable
+    if !is_derived {
+        record_qrat_learned_clause(matrix, &current_literals);
+    }
+    matrix.record_event(SearchEvent::Learned { learned_clause: current_literals, backtrack_level });
+    return (current_clause, backtrack_level); // if backtrack_level = -1 --> return unsatisfiable
 }
\ No newline at end of file
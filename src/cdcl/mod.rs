@@ -3,41 +3,243 @@ mod unit_propagate;
 mod cdcl;
 mod bench;
 mod conflict_analysis;
+mod sat_tail;
 mod cdcl_tests;
 
-use std::time::Instant;
-use crate::{cdcl::{preprocess::preprocess, cdcl::{Result, cdcl}, bench::{run_clause_variable_ratio_instances, run_bench_group}}, data_structures::{CDCLMatrix, Statistics, Config}, resolution::pre_resolution};
+use std::{fs, path::Path, time::Instant};
+pub use crate::cdcl::cdcl::Result;
+use crate::{log_normal, log_debug, cdcl::{preprocess::{preprocess, simplify_constraints}, unit_propagate::unit_propagate, cdcl::cdcl, bench::{run_clause_variable_ratio_instances, run_bench_group}}, data_structures::{Matrix, CDCLMatrix, Statistics, Config, TimeoutPhase}, resolution::pre_resolution, universal_reduction::reduce_parsed_clauses, pure_literal_deletion::prune_void_quantifiers, util::{format_trail, get_output_pathname, read_instance_name, build_fallback_matrix, hash_instance_file}, verify::verify_assignment, certificate::format_certificate, statistics::write_statistics_json, qrat::{write_qrat_proof, check_qrat_proof}};
 
 /*
-A function to run pre-processing, pre-resolution, and dpll, checking for satisfiability and unsatisfiability.
+A function to run pre-processing, pre-resolution, and dpll, checking for satisfiability and unsatisfiability. Several
+instances are solved as their conjunction.
 */
-pub fn run_instance(filename: String, config: Config) {
+pub fn run_instance(filenames: &[String], config: Config) {
     let timer = Instant::now();
-    let matrix = &mut CDCLMatrix::new(filename, config);
+    match Matrix::from_files(filenames, config.first_attempt_config()) {
+        Ok(matrix) => run_parsed_instance(filenames, config, matrix, timer),
+        Err(error) => log_normal!(config.verbosity, "{}", error),
+    }
+}
+
+/*
+A function to run an instance as above once it has been parsed into a matrix with the first attempt config, so the
+instance isn't parsed again after being inspected, such as when selecting a solver.
+*/
+pub fn run_parsed_instance(filenames: &[String], config: Config, matrix: Matrix, timer: Instant) {
+    let verbosity = config.verbosity;
+    let matrix = &mut CDCLMatrix::from_matrix(matrix);
+    // Clauses learned under initial assignments only hold under them, so they aren't kept between runs.
+    let learned_clause_file = if matrix.core_data.config.warm_restart_enabled() && filenames.len() == 1 && matrix.core_data.config.initial_assignments.is_empty() {
+        // The learned clauses are tied to the contents of the instance, so they aren't loaded for an edited instance of the same name.
+        match hash_instance_file(&filenames[0]) {
+            Ok(instance_hash) => Some((get_output_pathname(&matrix.core_data.config.output_dir, &format!("{}.learned", read_instance_name(&filenames[0]))), instance_hash)),
+            Err(error) => {
+                log_normal!(verbosity, "{}", error);
+                None
+            },
+        }
+    } else {
+        None
+    };
+    load_warm_restart_clauses(matrix, &learned_clause_file);
     let statistics = &mut Statistics::new();
-    if matrix.core_data.config.pre_process_enabled() { preprocess(matrix, statistics, timer); };
-    if matrix.core_data.config.pre_resolution_enabled() { pre_resolution(&mut matrix.core_data, &mut matrix.original_clause_list) };
-    let (_invariant, _backtrack_level, result) = cdcl(matrix, None, statistics, timer);
+    let first_result = solve(matrix, statistics, timer);
+    let result = retry_with_fallback(filenames, &config, matrix, statistics, first_result, timer, &learned_clause_file);
     match &result {
-        Result::UNSAT => println!("Unsatisfiable"),
-        Result::SAT => println!("Satisfiable"),
-        Result::Timeout => println!("Runtime has timed out: > 30 seconds."),
-        Result::Restart => println!("ERROR WITH RESTARTS")
+        Result::UNSAT if !matrix.core_data.config.initial_assignments.is_empty() => log_normal!(verbosity, "Unsatisfiable under the initial assignments"),
+        Result::UNSAT => log_normal!(verbosity, "Unsatisfiable"),
+        Result::SAT => {
+            log_normal!(verbosity, "Satisfiable");
+            if matrix.core_data.config.verify_enabled() {
+                if verify_assignment(matrix, &matrix.sat_assignments) {
+                    log_normal!(verbosity, "Verification passed: the assignment satisfies every clause.");
+                } else {
+                    log_normal!(verbosity, "Verification failed: the assignment does not satisfy every clause.");
+                }
+            }
+        },
+        Result::Timeout(phase) if matrix.core_data.config.interrupted() => log_normal!(verbosity, "Interrupted during {:?}.", phase),
+        Result::Timeout(phase) => {
+            log_normal!(verbosity, "Runtime has timed out during {:?}: > {} seconds.", phase, matrix.core_data.config.timeout.as_secs());
+            if matrix.core_data.config.debug_enabled() { log_normal!(verbosity, "Trail at timeout:\n{}", format_trail(&matrix.timeout_trail)) };
+        },
+        Result::Restart => log_normal!(verbosity, "ERROR WITH RESTARTS"),
+        Result::ConflictLimit => log_normal!(verbosity, "Conflict limit of {} has been reached.", matrix.core_data.config.max_conflicts),
+        Result::NoProgress => log_normal!(verbosity, "Search stopped making progress, so the result is unknown.")
+    }
+    log_normal!(verbosity, "Runtime: {}", config.format_duration(timer.elapsed()));
+    if config.fallback_enabled() && (result.eq(&Result::SAT) || result.eq(&Result::UNSAT)) {
+        log_normal!(verbosity, "Solved with {:?} literal selection.", matrix.core_data.config.literal_selection);
+    }
+    if (result.eq(&Result::SAT) || result.eq(&Result::UNSAT)) && matrix.core_data.config.initial_assignments.is_empty() && !matrix.core_data.matches_expected_result(result.eq(&Result::SAT)) {
+        log_normal!(verbosity, "Result does not match the expected result of the instance.");
+    }
+    log_normal!(verbosity, "{}", statistics.format_simplification_statistics());
+    if matrix.core_data.config.certificate_enabled() && (result.eq(&Result::SAT) || result.eq(&Result::UNSAT)) {
+        log_normal!(verbosity, "{}", format_certificate(matrix, &result));
+    }
+    if matrix.core_data.config.statistics_json_enabled() {
+        let pathname = write_statistics_json(filenames, statistics, &matrix.core_data.config.output_dir);
+        log_normal!(verbosity, "Statistics written to {}", pathname);
+    }
+    // A proof under initial assignments would only refute the instance under them, so it isn't written.
+    if matrix.core_data.config.qrat_enabled() && result.eq(&Result::UNSAT) && matrix.core_data.config.initial_assignments.is_empty() {
+        write_unsat_qrat_proof(filenames, matrix, statistics);
+    }
+    if matrix.core_data.config.record_events_enabled() {
+        let pathname = get_output_pathname(&matrix.core_data.config.output_dir, "events.json");
+        fs::write(&pathname, serde_json::to_string_pretty(&matrix.events).unwrap()).expect("Unable to write file");
+        log_normal!(verbosity, "Search events written to {}", pathname);
     }
+    if !learned_clause_file.is_none() {
+        let (learned_clause_pathname, instance_hash) = learned_clause_file.unwrap();
+        match matrix.save_learned_clauses(&learned_clause_pathname, instance_hash) {
+            Ok(()) => log_normal!(verbosity, "Learned clauses written to {}", learned_clause_pathname),
+            Err(error) => log_normal!(verbosity, "{}", error),
+        }
+    }
+}
+
+/*
+A function to write the QRAT proof of an instance found unsatisfiable, unless loaded learned clauses or the SAT procedure
+leave it without the steps of the refutation. Pre-processing and pre-resolution don't record their steps either, so a 
+proof relying on them is checked against the parsed instance and still written, with a warning that it doesn't check.
+
+Returns the pathname the proof was written to, if it was written.
+*/
+fn write_unsat_qrat_proof(filenames: &[String], matrix: &CDCLMatrix, statistics: &Statistics) -> Option<String> {
+    let verbosity = matrix.core_data.config.verbosity;
+    if matrix.loaded_clause_count > 0 {
+        log_normal!(verbosity, "QRAT proof not written, as the loaded learned clauses have no derivation in it.");
+        return None;
+    }
+    if statistics.sat_tail_count > 0 {
+        log_normal!(verbosity, "QRAT proof not written, as the SAT procedure doesn't record its steps.");
+        return None;
+    }
+    let parsed_literals: Vec<Vec<i32>> = matrix.parsed_clause_list.iter().map(|clause| clause.literals()).collect();
+    let check = check_qrat_proof(&parsed_literals, &matrix.core_data.variable_quantification, &matrix.qrat_proof);
+    let pathname = write_qrat_proof(filenames, &matrix.qrat_proof, &matrix.core_data.config.output_dir);
+    match check {
+        Ok(()) => log_normal!(verbosity, "QRAT proof written to {}", pathname),
+        Err(error) => log_normal!(verbosity, "Warning: QRAT proof written to {}, but it won't check, as pre-processing and pre-resolution don't record their steps. {}", pathname, error),
+    }
+    return Some(pathname);
+}
+
+/*
+A function to load the clauses learned by an earlier solve of the instance into the matrix, if warm restarts gave a 
+learned clause file for it and the file exists.
+*/
+fn load_warm_restart_clauses(matrix: &mut CDCLMatrix, learned_clause_file: &Option<(String, u64)>) {
+    if learned_clause_file.is_none() || !Path::new(&learned_clause_file.as_ref().unwrap().0).exists() { return };
+    let (learned_clause_pathname, instance_hash) = learned_clause_file.as_ref().unwrap();
+    match matrix.load_learned_clauses(learned_clause_pathname, *instance_hash) {
+        Ok(loaded_count) => log_normal!(matrix.core_data.config.verbosity, "Loaded {} learned clauses from {}", loaded_count, learned_clause_pathname),
+        Err(error) => log_normal!(matrix.core_data.config.verbosity, "{}", error),
+    }
+}
+
+/*
+A function to retry an instance whose first attempt timed out or hit its conflict limit, solving it again with the 
+fallback literal selection if a retry is made. The statistics are reset so they describe the retry, and the clauses of
+the learned clause file, if any, are loaded into the rebuilt matrix as they were for the first attempt.
+
+Returns the result of the retry, or the given result if no retry is made or the instance can't be read again.
+*/
+pub fn retry_with_fallback(filenames: &[String], config: &Config, matrix: &mut CDCLMatrix, statistics: &mut Statistics, result: Result, timer: Instant, learned_clause_file: &Option<(String, u64)>) -> Result {
+    let fallback_matrix = match build_fallback_matrix(filenames, config, matches!(result, Result::Timeout(_) | Result::ConflictLimit), timer) {
+        Ok(fallback_matrix) => fallback_matrix,
+        Err(error) => {
+            log_normal!(config.verbosity, "{}", error);
+            return result;
+        },
+    };
+    if fallback_matrix.is_none() { return result };
+    *matrix = CDCLMatrix::from_matrix(fallback_matrix.unwrap());
+    load_warm_restart_clauses(matrix, learned_clause_file);
+    *statistics = Statistics::new();
+    return solve(matrix, statistics, timer);
+}
+
+/*
+A function to run pre-processing, pre-resolution, and cdcl on an already constructed CDCLMatrix. Pre-resolution is run 
+before pre-processing if configured to.
+
+Returns the result of the CDCL procedure.
+*/
+pub fn solve(matrix: &mut CDCLMatrix, statistics: &mut Statistics, timer: Instant) -> Result {
+    statistics.update_peak_clause_count(matrix.core_data.clause_set.clause_count);
+    if matrix.core_data.config.universal_reduction_enabled() {
+        let parsed_literals: Vec<Vec<i32>> = if matrix.core_data.config.qrat_enabled() { matrix.core_data.clause_set.clause_list.iter().map(|clause| clause.literals()).collect() } else { Vec::new() };
+        let unit_literals = reduce_parsed_clauses(&mut matrix.core_data, statistics);
+        // Reducing the parsed clauses keeps every clause at its index, so each is compared with its parsed literals.
+        for (clause_index, literals) in parsed_literals.iter().enumerate() {
+            let reduced_literals = matrix.core_data.clause_set.clause_list[clause_index].literals();
+            matrix.record_qrat_reduction(literals, &reduced_literals);
+        }
+        if matrix.core_data.clause_set.contains_empty_clause() { return Result::UNSAT };
+        log_debug!(matrix.core_data.config.verbosity, "Unit literals after universal reduction at parse: {:?}", unit_literals);
+        // Conflict analysis resolves on the reduced clauses, as they are the ones propagated.
+        matrix.original_clause_list = matrix.core_data.clause_set.clause_list.clone();
+    }
+    if !matrix.core_data.config.initial_assignments.is_empty() {
+        let initial_assignments = matrix.core_data.take_initial_assignments();
+        unit_propagate(matrix, initial_assignments, false, statistics);
+        if matrix.core_data.clause_set.contains_empty_clause() { return Result::UNSAT };
+        simplify_constraints(matrix);
+        if matrix.core_data.clause_set.contains_empty_set() {
+            if matrix.core_data.config.verify_enabled() || matrix.core_data.config.certificate_enabled() { matrix.sat_assignments = matrix.assignments.clone() };
+            return Result::SAT;
+        }
+    }
+    let resolution_first = matrix.core_data.config.resolution_before_preprocess_enabled();
+    if resolution_first && matrix.core_data.config.pre_resolution_enabled() {
+        pre_resolution(&mut matrix.core_data, &mut matrix.original_clause_list);
+        matrix.core_data.debug_verify_structural_invariants("pre-resolution");
+        statistics.update_peak_clause_count(matrix.core_data.clause_set.clause_count);
+        if matrix.core_data.config.timed_out(timer) { return Result::Timeout(TimeoutPhase::PreResolution) };
+    }
+    if matrix.core_data.config.pre_process_enabled() {
+        preprocess(matrix, statistics, timer);
+        matrix.core_data.debug_verify_structural_invariants("pre-processing");
+        if matrix.core_data.config.timed_out(timer) { return Result::Timeout(TimeoutPhase::Preprocess) };
+    }
+    if !resolution_first && matrix.core_data.config.pre_resolution_enabled() {
+        pre_resolution(&mut matrix.core_data, &mut matrix.original_clause_list);
+        matrix.core_data.debug_verify_structural_invariants("pre-resolution");
+        statistics.update_peak_clause_count(matrix.core_data.clause_set.clause_count);
+        if matrix.core_data.config.timed_out(timer) { return Result::Timeout(TimeoutPhase::PreResolution) };
+    }
+    if matrix.core_data.config.prune_void_quantifiers_enabled() {
+        prune_void_quantifiers(&mut matrix.core_data);
+    }
+    if matrix.core_data.config.verbose_preprocess_enabled() {
+        log_normal!(matrix.core_data.config.verbosity, "Pre-processed instance -- {}", matrix.core_data.format_instance_size());
+    }
+    // The prefix may no longer quantify the literals of the empty clause, so they can't be propagated again.
+    if matrix.core_data.clause_set.contains_empty_clause() { return Result::UNSAT };
+    if matrix.core_data.quantifier_list.is_empty() && !matrix.core_data.check_solved() {
+        if !matrix.core_data.remaining_clauses_satisfiable() { return Result::UNSAT };
+        if matrix.core_data.config.verify_enabled() || matrix.core_data.config.certificate_enabled() { matrix.sat_assignments = matrix.assignments.clone() };
+        return Result::SAT;
+    }
+    let (_invariant, _backtrack_level, result) = cdcl(matrix, None, statistics, timer);
+    return result;
 }
 
 /*
 A function to perform tests on a given set of benchmarks in qdimacs format.
 */
-pub fn run_bench_directory(path: String, config: Config, filename_to_write: &str) {
-    run_bench_group(path, config, filename_to_write);
+pub fn run_bench_directory(path: String, config: Config, filename_to_write: &str, stats_only: bool) {
+    run_bench_group(path, config, filename_to_write, stats_only);
 }
 
 /*
 A function to perform tests on a subset of the tacchella data set to measure CPU time
 as clause/variable ratio increases.
 */
-#[allow(dead_code)]
 pub fn run_clause_variable_ratio_bench_directory(config: Config, filename_to_write: &str) {
     run_clause_variable_ratio_instances(config, filename_to_write);
 }
\ No newline at end of file
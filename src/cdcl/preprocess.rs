@@ -1,8 +1,6 @@
 use std::time::Instant;
 
-use multimap::MultiMap;
-
-use crate::{cdcl::unit_propagate::unit_propagate, data_structures::{CDCLMatrix, Statistics}, universal_reduction::{get_universal_literals_for_reduction, remove_universal_literal}, pure_literal_deletion::{remove_pure_literals, get_pure_literals}, util::get_unit_literals};
+use crate::{cdcl::unit_propagate::unit_propagate, data_structures::{CDCLMatrix, Statistics}, universal_reduction::{get_universal_literals_for_reduction, remove_universal_literal}, pure_literal_deletion::{remove_pure_literals, get_pure_literals}, util::get_unit_literals, variable_elimination::eliminate_variables};
 
 /*
 A function to reduce the initial problem set by applying pre-processing techniques unit propagation, universal reduction,
@@ -13,12 +11,18 @@ pub fn preprocess(matrix: &mut CDCLMatrix, statistics: &mut Statistics, timer: I
     let mut pure_literals;
     let mut literals_for_universal_reduction;
     let mut unit_literals;
-    while !is_finished {
-        // Timeout the instance after 30 seconds 
-        if timer.elapsed().as_secs() > 30 { return; };
+    let mut iterations = 0;
+    statistics.is_preprocessing = true;
+    // Stop after the maximum number of iterations even if a fixpoint hasn't been reached.
+    while !is_finished && iterations < matrix.core_data.config.max_preprocess_iterations {
+        iterations += 1;
+        if matrix.core_data.config.timed_out(timer) {
+            statistics.is_preprocessing = false;
+            return;
+        }
 
         // Perform unit propagation on the set of clauses
-        unit_literals = get_unit_literals(&matrix.core_data.clause_set.clause_list);
+        unit_literals = get_unit_literals(&matrix.core_data.clause_set);
         if !unit_literals.is_empty() {
             unit_propagate(matrix, unit_literals, false, statistics);
         }
@@ -26,8 +30,10 @@ pub fn preprocess(matrix: &mut CDCLMatrix, statistics: &mut Statistics, timer: I
 
         // Perform pure literal deletion on the set of clauses
         if matrix.core_data.config.pure_literal_deletion_enabled() {
-            pure_literals = get_pure_literals(&matrix.core_data.clause_references);
+            pure_literals = get_pure_literals(&matrix.core_data.clause_references, matrix.core_data.config.deterministic_enabled());
             if !pure_literals.is_empty() {
+                statistics.add_pure_literal_removed_variables(pure_literals.len() as i32);
+                matrix.fixed_literals.extend(pure_literals.clone());
                 remove_pure_literals(&mut matrix.core_data, pure_literals);
             }
             if matrix.core_data.check_solved() { break; }
@@ -35,22 +41,30 @@ pub fn preprocess(matrix: &mut CDCLMatrix, statistics: &mut Statistics, timer: I
 
         // Perform universal reduction on the set of clauses
         if matrix.core_data.config.universal_reduction_enabled() {
-            literals_for_universal_reduction = get_universal_literals_for_reduction(&matrix.core_data.clause_set.clause_list, &matrix.core_data.variable_quantification);
+            literals_for_universal_reduction = get_universal_literals_for_reduction(&matrix.core_data.clause_set, &matrix.core_data.variable_quantification);
             if !literals_for_universal_reduction.is_empty() {
                 for literal_to_remove in literals_for_universal_reduction {
+                    statistics.add_universal_reduction_removed_literals(literal_to_remove.values.len() as i32);
                     remove_universal_literal(&mut matrix.core_data, literal_to_remove.values, literal_to_remove.clause_index);
                 }
             }
             if matrix.core_data.check_solved() { break; }
         }
-        pure_literals = if matrix.core_data.config.pure_literal_deletion_enabled() {get_pure_literals(&matrix.core_data.clause_references) } else { Vec::new() };
-        literals_for_universal_reduction = if matrix.core_data.config.universal_reduction_enabled() { get_universal_literals_for_reduction(&matrix.core_data.clause_set.clause_list, &matrix.core_data.variable_quantification) } else { Vec::new() };
-        unit_literals = get_unit_literals(&matrix.core_data.clause_set.clause_list);
+        pure_literals = if matrix.core_data.config.pure_literal_deletion_enabled() {get_pure_literals(&matrix.core_data.clause_references, matrix.core_data.config.deterministic_enabled()) } else { Vec::new() };
+        literals_for_universal_reduction = if matrix.core_data.config.universal_reduction_enabled() { get_universal_literals_for_reduction(&matrix.core_data.clause_set, &matrix.core_data.variable_quantification) } else { Vec::new() };
+        unit_literals = get_unit_literals(&matrix.core_data.clause_set);
         if pure_literals.is_empty() && literals_for_universal_reduction.is_empty() && unit_literals.is_empty() {
             is_finished = true;
         }
     }
+    if matrix.core_data.config.variable_elimination_enabled() {
+        let bound = matrix.core_data.config.variable_elimination.1;
+        let eliminated_variables = eliminate_variables(&mut matrix.core_data, bound);
+        matrix.eliminated_variables.extend(eliminated_variables);
+    }
     simplify_constraints(matrix);
+    statistics.is_preprocessing = false;
+    statistics.update_peak_clause_count(matrix.core_data.clause_set.clause_count);
 }
 
 /*
@@ -58,28 +72,9 @@ Function to simplify the problem set constraints. It will permanently remove any
 the problem, and it will update the clause references where appropriate.
 */
 pub fn simplify_constraints(matrix: &mut CDCLMatrix) {
-    let mut remove_clause_references = Vec::new();
-    for (index, clause) in matrix.core_data.clause_set.clause_list.iter().enumerate() {
-        if clause.is_removed {
-            remove_clause_references.push(index as usize);
-        }
-    }
-    for reference in remove_clause_references.iter().rev() {
-        matrix.core_data.clause_set.clause_list.remove(*reference);
-        matrix.learned_clause_refs.retain(|&x| x != *reference as i32);
-        for (index, learned_clause_reference) in matrix.learned_clause_refs.clone().iter().enumerate() {
-            if learned_clause_reference > &(*reference as i32) {
-                matrix.learned_clause_refs[index] -= 1;
-            }
-        }
-    }
-    let mut clause_references = MultiMap::new();
-    for (index, clause) in matrix.core_data.clause_set.clause_list.iter().enumerate() {
-        for literal in clause.clone().get_literal_list() {
-            clause_references.insert(literal, index as i32);
-        }
-    }
+    let remove_clause_references = matrix.core_data.clause_set.clause_list.iter().enumerate().filter(|(_index, clause)| clause.is_removed).map(|(index, _clause)| index).collect();
+    let index_remapping = matrix.core_data.remove_clauses(&remove_clause_references);
+    matrix.learned_clause_refs = matrix.learned_clause_refs.iter().filter_map(|&reference| index_remapping[reference as usize]).collect();
     matrix.restart_data.current_conflicts = 0; // Since we are refreshing the database, set current conflicts to 0.
-    matrix.core_data.clause_references = clause_references;
     matrix.original_clause_list = matrix.core_data.clause_set.clause_list.clone();
-}
\ No newline at end of file
+}
@@ -0,0 +1,372 @@
+use std::{collections::BTreeSet, time::Instant};
+
+use crate::{log_debug, cdcl::cdcl::{Result, satisfiable, timeout}, data_structures::{CDCLMatrix, Clause, Assignment, Config, DefaultPhase, Statistics}};
+
+/*
+A struct to store the result of the SAT procedure.
+
+SAT => Satisfiable. Stores a model, assigning every variable appearing in the clauses.
+UNSAT => Unsatisfiable.
+Timeout => Instance timeout, stop running the current instance.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub enum SATResult {
+    SAT(Vec<i32>),
+    UNSAT,
+    Timeout,
+}
+
+/*
+A struct to store the state of the SAT procedure run on the existential tail of the prefix.
+
+Variables are numbered from 0 in increasing order of their QDIMACS variable, and a literal is encoded as twice its
+variable number, plus one if it is negative, so the complement of a literal is found by flipping the lowest bit.
+
+- clauses stores each clause as encoded literals, with the two watched literals first. The first literal of a clause
+responsible for an implication is the implied literal.
+- watches stores, for each literal, the clauses in which it is watched.
+- values stores the value of each variable: 1 for true, -1 for false, and 0 when unassigned.
+- levels and reasons store the decision level of each assigned variable and the clause responsible for it, if implied.
+- trail stores the assigned literals in order, and trail_limits the trail length at each decision.
+- activities store the VSIDS score of each variable, and phases the polarity it was last assigned.
+*/
+struct SATSolver {
+    variables: Vec<i32>,
+    clauses: Vec<Vec<usize>>,
+    watches: Vec<Vec<usize>>,
+    values: Vec<i8>,
+    levels: Vec<usize>,
+    reasons: Vec<Option<usize>>,
+    trail: Vec<usize>,
+    trail_limits: Vec<usize>,
+    propagation_head: usize,
+    activities: Vec<f64>,
+    activity_increment: f64,
+    phases: Vec<bool>,
+}
+
+/*
+A function to get the value of an encoded literal: 1 for true, -1 for false, and 0 when unassigned.
+*/
+fn literal_value(values: &[i8], literal: usize) -> i8 {
+    let value = values[literal >> 1];
+    return if literal & 1 == 0 { value } else { -value };
+}
+
+impl SATSolver {
+    /*
+    Creates a SAT solver for the given clauses. The initial polarity of each variable follows the configured default
+    phase.
+
+    Returns the solver, or None if a clause is empty or two unit clauses contradict each other.
+    */
+    fn new(clauses: &Vec<Vec<i32>>, default_phase: &DefaultPhase) -> Option<Self> {
+        let variables: Vec<i32> = clauses.iter().flatten().map(|literal| literal.abs()).collect::<BTreeSet<i32>>().into_iter().collect();
+        let variable_count = variables.len();
+        let encode = |literal: i32| {
+            let index = variables.binary_search(&literal.abs()).unwrap();
+            return 2 * index + if literal < 0 { 1 } else { 0 };
+        };
+        let mut occurrences = vec![0; 2 * variable_count];
+        for literal in clauses.iter().flatten() {
+            occurrences[encode(*literal)] += 1;
+        }
+        let phases = (0..variable_count).map(|index| match default_phase {
+            DefaultPhase::Positive => true,
+            DefaultPhase::Negative => false,
+            DefaultPhase::Occurrence => occurrences[2 * index] >= occurrences[2 * index + 1],
+        }).collect();
+        let mut solver = SATSolver {
+            variables: variables.clone(),
+            clauses: Vec::new(),
+            watches: vec![Vec::new(); 2 * variable_count],
+            values: vec![0; variable_count],
+            levels: vec![0; variable_count],
+            reasons: vec![None; variable_count],
+            trail: Vec::new(),
+            trail_limits: Vec::new(),
+            propagation_head: 0,
+            activities: vec![0.0; variable_count],
+            activity_increment: 1.0,
+            phases,
+        };
+        for clause in clauses {
+            let mut literals: Vec<usize> = clause.iter().map(|literal| encode(*literal)).collect();
+            literals.sort();
+            literals.dedup();
+            // Tautologies are always satisfied.
+            if literals.windows(2).any(|pair| pair[0] ^ 1 == pair[1]) { continue };
+            match literals.len() {
+                0 => return None,
+                1 => match literal_value(&solver.values, literals[0]) {
+                    0 => solver.assign(literals[0], None),
+                    -1 => return None,
+                    _ => {},
+                },
+                _ => { solver.add_clause(literals); },
+            }
+        }
+        return Some(solver);
+    }
+
+    /*
+    A function to add a clause of at least two literals, watching its first two literals.
+
+    Returns the index of the clause.
+    */
+    fn add_clause(&mut self, literals: Vec<usize>) -> usize {
+        let index = self.clauses.len();
+        self.watches[literals[0]].push(index);
+        self.watches[literals[1]].push(index);
+        self.clauses.push(literals);
+        return index;
+    }
+
+    /*
+    A function to assign an encoded literal true at the current decision level.
+    */
+    fn assign(&mut self, literal: usize, reason: Option<usize>) {
+        let variable = literal >> 1;
+        self.values[variable] = if literal & 1 == 0 { 1 } else { -1 };
+        self.levels[variable] = self.trail_limits.len();
+        self.reasons[variable] = reason;
+        self.trail.push(literal);
+    }
+
+    /*
+    A function to propagate the assignments on the trail that haven't been propagated yet. Each clause watching a
+    literal that has been assigned false either finds another literal to watch, implies its other watched literal, or
+    is a conflict.
+
+    Returns the index of a conflicting clause, or None if propagation completes without conflict.
+    */
+    fn propagate(&mut self) -> Option<usize> {
+        while self.propagation_head < self.trail.len() {
+            let false_literal = self.trail[self.propagation_head] ^ 1;
+            self.propagation_head += 1;
+            let mut watching = std::mem::take(&mut self.watches[false_literal]);
+            let mut conflict = None;
+            let mut index = 0;
+            while index < watching.len() {
+                let clause_index = watching[index];
+                let clause = &mut self.clauses[clause_index];
+                if clause[0] == false_literal { clause.swap(0, 1) };
+                if literal_value(&self.values, clause[0]) == 1 {
+                    index += 1;
+                    continue;
+                }
+                let replacement = (2..clause.len()).find(|position| literal_value(&self.values, clause[*position]) != -1);
+                if !replacement.is_none() {
+                    clause.swap(1, replacement.unwrap());
+                    self.watches[clause[1]].push(clause_index);
+                    watching.swap_remove(index);
+                    continue;
+                }
+                if literal_value(&self.values, clause[0]) == -1 {
+                    conflict = Some(clause_index);
+                    break;
+                }
+                let implied_literal = clause[0];
+                self.assign(implied_literal, Some(clause_index));
+                index += 1;
+            }
+            watching.append(&mut self.watches[false_literal]);
+            self.watches[false_literal] = watching;
+            if !conflict.is_none() { return conflict };
+        }
+        return None;
+    }
+
+    /*
+    A function to analyse a conflict by resolving the conflicting clause with the clauses responsible for the
+    implications at the current decision level, stopping at the first unique implication point. The variables involved
+    have their activity bumped.
+
+    Returns (the learned clause with the asserting literal first, the decision level to backjump to).
+    */
+    fn analyse(&mut self, conflict: usize) -> (Vec<usize>, usize) {
+        let current_level = self.trail_limits.len();
+        let mut seen = vec![false; self.variables.len()];
+        let mut learned_clause = vec![0];
+        let mut current_level_count = 0;
+        let mut clause_index = conflict;
+        let mut implied_literal = None;
+        let mut trail_index = self.trail.len();
+        loop {
+            // The first literal of a clause responsible for an implication is the implied literal itself.
+            let skip = if implied_literal.is_none() { 0 } else { 1 };
+            for position in skip..self.clauses[clause_index].len() {
+                let literal = self.clauses[clause_index][position];
+                let variable = literal >> 1;
+                if seen[variable] || self.levels[variable] == 0 { continue };
+                seen[variable] = true;
+                self.bump_activity(variable);
+                if self.levels[variable] == current_level {
+                    current_level_count += 1;
+                } else {
+                    learned_clause.push(literal);
+                }
+            }
+            loop {
+                trail_index -= 1;
+                if seen[self.trail[trail_index] >> 1] { break };
+            }
+            let literal = self.trail[trail_index];
+            seen[literal >> 1] = false;
+            current_level_count -= 1;
+            if current_level_count == 0 {
+                learned_clause[0] = literal ^ 1;
+                break;
+            }
+            clause_index = self.reasons[literal >> 1].expect("Implied literal should have a responsible clause");
+            implied_literal = Some(literal);
+        }
+        let mut backjump_level = 0;
+        for position in 1..learned_clause.len() {
+            let level = self.levels[learned_clause[position] >> 1];
+            // Keep the literal at the backjump level second so it is watched.
+            if level > backjump_level {
+                backjump_level = level;
+                learned_clause.swap(1, position);
+            }
+        }
+        return (learned_clause, backjump_level);
+    }
+
+    /*
+    A function to bump the activity of a variable involved in a conflict, rescaling every activity if they grow too large.
+    */
+    fn bump_activity(&mut self, variable: usize) {
+        self.activities[variable] += self.activity_increment;
+        if self.activities[variable] > 1e100 {
+            for activity in self.activities.iter_mut() { *activity *= 1e-100 };
+            self.activity_increment *= 1e-100;
+        }
+    }
+
+    /*
+    A function to undo every assignment made above the given decision level, saving the polarity of each variable.
+    */
+    fn backjump(&mut self, level: usize) {
+        if self.trail_limits.len() <= level { return };
+        for literal in self.trail.drain(self.trail_limits[level]..) {
+            self.values[literal >> 1] = 0;
+            self.phases[literal >> 1] = literal & 1 == 0;
+        }
+        self.trail_limits.truncate(level);
+        self.propagation_head = self.trail.len();
+    }
+
+    /*
+    A function to select the unassigned variable with the highest activity, taking the lowest variable on ties, in the
+    polarity it was last assigned.
+
+    Returns the encoded literal, or None if every variable is assigned.
+    */
+    fn select_literal(&self) -> Option<usize> {
+        let mut choice: Option<usize> = None;
+        for variable in 0..self.variables.len() {
+            if self.values[variable] != 0 { continue };
+            if choice.is_none() || self.activities[variable] > self.activities[choice.unwrap()] {
+                choice = Some(variable);
+            }
+        }
+        return choice.map(|variable| 2 * variable + if self.phases[variable] { 0 } else { 1 });
+    }
+
+    /*
+    A function to run CDCL with first UIP clause learning, non-chronological backtracking and VSIDS literal selection
+    until every variable is assigned, the empty clause is learned, or the time budget has been spent.
+
+    Returns SAT with a model, UNSAT, or Timeout.
+    */
+    fn solve(&mut self, config: &Config, timer: Instant) -> SATResult {
+        loop {
+            if config.timed_out(timer) { return SATResult::Timeout };
+            let conflict = self.propagate();
+            if !conflict.is_none() {
+                if self.trail_limits.is_empty() { return SATResult::UNSAT };
+                let (learned_clause, backjump_level) = self.analyse(conflict.unwrap());
+                self.backjump(backjump_level);
+                let asserting_literal = learned_clause[0];
+                let reason = if learned_clause.len() == 1 { None } else { Some(self.add_clause(learned_clause)) };
+                self.assign(asserting_literal, reason);
+                self.activity_increment /= 0.95;
+                continue;
+            }
+            let selection = self.select_literal();
+            if selection.is_none() {
+                let model = self.variables.iter().zip(&self.values).map(|(variable, value)| *variable * *value as i32).collect();
+                return SATResult::SAT(model);
+            }
+            self.trail_limits.push(self.trail.len());
+            self.assign(selection.unwrap(), None);
+        }
+    }
+}
+
+/*
+A function to solve a set of clauses, given as lists of literals, as plain SAT.
+
+Returns SAT with a model, UNSAT, or Timeout once the time budget in the config has been spent.
+*/
+pub fn solve_sat(clauses: &Vec<Vec<i32>>, config: &Config, timer: Instant) -> SATResult {
+    let solver = SATSolver::new(clauses, &config.default_phase);
+    if solver.is_none() { return SATResult::UNSAT };
+    return solver.unwrap().solve(config, timer);
+}
+
+/*
+A function to get the clauses that remain to be satisfied once every universal variable has been assigned, i.e. the
+active clauses with the literals of assigned variables removed. Clauses satisfied by an assignment are skipped.
+
+Returns the remaining clauses, or None if one of them still contains an unassigned universal literal, in which case the
+remaining problem isn't plain SAT.
+*/
+pub fn existential_tail_clauses(matrix: &CDCLMatrix) -> Option<Vec<Vec<i32>>> {
+    let mut clauses = Vec::new();
+    for (_index, clause) in matrix.core_data.clause_set.active_clauses() {
+        let mut literals = Vec::new();
+        let mut is_satisfied = false;
+        for literal in clause.literals() {
+            let assignment = matrix.assignments.get(&literal.abs());
+            if assignment.is_none() {
+                if matrix.core_data.variable_of(literal).is_universal() { return None };
+                literals.push(literal);
+            } else if assignment.unwrap().value == literal {
+                is_satisfied = true;
+                break;
+            }
+        }
+        if !is_satisfied { clauses.push(literals) };
+    }
+    return Some(clauses);
+}
+
+/*
+A function to finish the current branch of the CDCL search with the SAT procedure, once only existential variables are
+left in the quantifier prefix. The SAT procedure works on its own copy of the remaining clauses, so nothing it learns is
+added to the clause database, and an unsatisfiable tail is backtracked from chronologically as no clause is learned.
+
+Returns the result in the form returned by cdcl, or None if the remaining problem isn't plain SAT.
+*/
+pub fn solve_existential_tail(matrix: &mut CDCLMatrix, statistics: &mut Statistics, timer: Instant) -> Option<(Clause, i32, Result)> {
+    let clauses = existential_tail_clauses(matrix);
+    if clauses.is_none() { return None };
+    statistics.increment_sat_tail_count();
+    log_debug!(matrix.core_data.config.verbosity, "Solving the existential tail as SAT at decision level {}", matrix.decision_level);
+    match solve_sat(&clauses.unwrap(), &matrix.core_data.config, timer) {
+        SATResult::SAT(model) => {
+            if matrix.core_data.config.verify_enabled() || matrix.core_data.config.certificate_enabled() {
+                matrix.sat_assignments = matrix.assignments.clone();
+                for literal in model {
+                    matrix.sat_assignments.insert(literal.abs(), Assignment { value: literal, decision_level: matrix.decision_level + 1, clause_responsible: None });
+                }
+            }
+            return Some(satisfiable());
+        },
+        // No conflict clause to learn from, so naively backtrack.
+        SATResult::UNSAT => return Some((Clause::new_empty_clause(), matrix.decision_level, Result::UNSAT)),
+        SATResult::Timeout => return Some(timeout()),
+    }
+}
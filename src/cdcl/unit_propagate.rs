@@ -1,6 +1,6 @@
 use std::collections::{HashMap, VecDeque};
 
-use crate::{data_structures::{CDCLMatrix, Assignment, QuantifierType, Statistics}, util::get_quantifier_type, universal_reduction::{detect_universal_literal, remove_universal_literal, readd_universal_literal}};
+use crate::{data_structures::{CDCLMatrix, Assignment, QuantifierType, Statistics, PropagationOrder}, util::get_quantifier_type, universal_reduction::{detect_universal_literal, remove_universal_literal, readd_universal_literal}};
 
 /*
 A function to perform unit propagation (Boolean Constraint Propagation) on a given CDCLMatrix data structure.
@@ -10,12 +10,31 @@ It will check for the empty set of clauses and the empty clause and return flags
 unsatisfiable assignments.
 */
 pub fn unit_propagate(matrix: &mut CDCLMatrix, unit_literal: Vec<i32>, decision: bool, statistics: &mut Statistics) {
+    propagate(matrix, unit_literal, decision, HashMap::new(), statistics);
+}
+
+/*
+A function to perform unit propagation from a literal implied by the clause at the given index, such as the asserting
+literal of a learned clause that is already unit after backjumping. The literal is put on the trail as an implication 
+of the clause, so conflict analysis can resolve on it.
+*/
+pub fn propagate_implied_literal(matrix: &mut CDCLMatrix, implied_literal: i32, clause_index: i32, statistics: &mut Statistics) {
+    propagate(matrix, vec![implied_literal], true, HashMap::from([(implied_literal, clause_index)]), statistics);
+}
+
+/*
+A function to perform unit propagation as in unit_propagate, where implied_clause_references maps each of the given 
+literals that is implied, rather than decided, to the clause responsible for it.
+*/
+fn propagate(matrix: &mut CDCLMatrix, unit_literal: Vec<i32>, decision: bool, mut implied_clause_references: HashMap<i32, i32>, statistics: &mut Statistics) {
     let mut new_unit_literals = VecDeque::new();
-    let mut implied_clause_references = HashMap::new();
     new_unit_literals.extend(&unit_literal);
     while !new_unit_literals.is_empty() {
         statistics.increment_propagation_count();
-        let temp_unit_literal = new_unit_literals.pop_front().unwrap();
+        let temp_unit_literal = match matrix.core_data.config.propagation_order {
+            PropagationOrder::FIFO => new_unit_literals.pop_front().unwrap(),
+            PropagationOrder::LIFO => new_unit_literals.pop_back().unwrap(),
+        };
         // Assign to trail and assignments.
         if decision {
             let clause_index = implied_clause_references.get(&temp_unit_literal).copied();
@@ -26,6 +45,8 @@ pub fn unit_propagate(matrix: &mut CDCLMatrix, unit_literal: Vec<i32>, decision:
             };
             matrix.trail.push(new_assignment.clone());
             matrix.assignments.insert(temp_unit_literal.abs(), new_assignment);
+        } else {
+            matrix.fixed_literals.push(temp_unit_literal);
         }
 
 
@@ -35,7 +56,7 @@ pub fn unit_propagate(matrix: &mut CDCLMatrix, unit_literal: Vec<i32>, decision:
             matrix.core_data.quantifier_list.remove(quantifier_position.unwrap());
         }
         if quantifier_type.eq(&QuantifierType::Universal) {
-            matrix.core_data.clause_set.clause_count = -1;
+            matrix.core_data.clause_set.set_contradiction();
             return;
         } else {
             let pos_clause_references = matrix.core_data.clause_references.get_vec(&temp_unit_literal);
@@ -43,6 +64,7 @@ pub fn unit_propagate(matrix: &mut CDCLMatrix, unit_literal: Vec<i32>, decision:
                 for clause_index in pos_clause_references.unwrap().clone() {
                     matrix.core_data.clause_set.clause_list[clause_index as usize].is_removed = true; // Mark clause as removed
                     matrix.core_data.clause_set.decrement_counter();
+                    statistics.add_unit_propagation_removed_clauses(1);
                     matrix.core_data.clause_references.retain(|&_key, &value| { value != clause_index});
                     // Check satisfiability
                     if matrix.core_data.clause_set.contains_empty_set() {
@@ -53,21 +75,15 @@ pub fn unit_propagate(matrix: &mut CDCLMatrix, unit_literal: Vec<i32>, decision:
             let complement_unit_literal = -temp_unit_literal;
             let neg_clause_references = matrix.core_data.clause_references.get_vec(&complement_unit_literal);
             if !neg_clause_references.is_none() {
-                let definitive_q_type = &matrix.core_data.variable_quantification.get(&temp_unit_literal.abs()).unwrap().q_type.clone();
                 for clause_index in neg_clause_references.unwrap().clone()  {
-                    if definitive_q_type.eq(&QuantifierType::Existential) {
-                        matrix.core_data.clause_set.clause_list[clause_index as usize].remove_e_literal(complement_unit_literal);
-                    } else {
-                        matrix.core_data.clause_set.clause_list[clause_index as usize].remove_a_literal(complement_unit_literal);
-                    }
+                    matrix.core_data.clause_set.clause_list[clause_index as usize].remove_literal(complement_unit_literal, &matrix.core_data.variable_quantification);
                     matrix.core_data.clause_references.remove(&complement_unit_literal);
 
                     if matrix.core_data.config.universal_reduction_enabled() {
                         let universal_literals = detect_universal_literal(&matrix.core_data.clause_set.clause_list[clause_index as usize], &matrix.core_data.variable_quantification);
                         if !universal_literals.is_empty() {
-                            remove_universal_literal(&mut matrix.core_data, universal_literals.clone(), clause_index);
-                            if matrix.core_data.clause_set.check_contradiction(None) {
-                                matrix.core_data.clause_set.clause_count = -1;
+                            // A clause emptied by universal reduction is a conflict, so stop propagating.
+                            if remove_universal_literal(&mut matrix.core_data, universal_literals.clone(), clause_index) {
                                 return;
                             } else {
                                 readd_universal_literal(&mut matrix.core_data, universal_literals, clause_index);
@@ -86,6 +102,15 @@ pub fn unit_propagate(matrix: &mut CDCLMatrix, unit_literal: Vec<i32>, decision:
                     let unit_clause_check = matrix.core_data.clause_set.clause_list[clause_index as usize].is_unit_clause();
                     if !unit_clause_check.is_none() {
                         let found_unit_clause = unit_clause_check.unwrap();
+                        // A unit literal whose variable is already assigned the opposite value is a conflict, even if
+                        // the clause has not been emptied.
+                        let existing_assignment = matrix.assignments.get(&found_unit_clause.abs());
+                        if !existing_assignment.is_none() && existing_assignment.unwrap().value == -found_unit_clause {
+                            matrix.core_data.clause_set.set_contradiction();
+                            let conflict = matrix.original_clause_list[clause_index as usize].clone();
+                            matrix.conflict_clause = Some(conflict);
+                            return;
+                        }
                         if !new_unit_literals.contains(&found_unit_clause) {
                             implied_clause_references.insert(found_unit_clause, clause_index);
                             new_unit_literals.push_back(found_unit_clause);
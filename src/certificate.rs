@@ -0,0 +1,43 @@
+use crate::{cdcl::Result, data_structures::CDCLMatrix};
+
+/*
+A function to format a certificate for an instance solved by CDCL. The first line gives the result, 1 for satisfiable 
+and 0 for unsatisfiable. For satisfiable instances, it is followed by a "V <literal> 0" line for each existential 
+variable in the outermost quantifier block that was assigned during the solve.
+
+Existential variables that were never assigned are don't-cares, as every clause was satisfied without them. These are
+omitted, or marked with a "V *<variable> 0" line when certificate_dont_cares is enabled.
+
+Returns the certificate.
+*/
+pub fn format_certificate(matrix: &CDCLMatrix, result: &Result) -> String {
+    if !result.eq(&Result::SAT) {
+        return String::from("s cnf 0");
+    }
+    let mut certificate = String::from("s cnf 1");
+    for literal in &matrix.core_data.quantification_order.existential_literal_order {
+        let variable = matrix.core_data.variable_quantification.get(literal).unwrap();
+        if variable.q_level != 1 { continue }; // Only existential variables in the outermost quantifier block.
+        let value = get_assigned_value(matrix, *literal);
+        if !value.is_none() {
+            certificate += &format!("\nV {} 0", value.unwrap());
+        } else if matrix.core_data.config.certificate_dont_cares {
+            certificate += &format!("\nV *{} 0", literal);
+        }
+    }
+    return certificate;
+}
+
+/*
+A function to get the value assigned to a variable, either at the most recent satisfiable assignment or permanently 
+during pre-processing.
+
+Returns the assigned literal, or None if the variable was never assigned.
+*/
+pub fn get_assigned_value(matrix: &CDCLMatrix, variable: i32) -> Option<i32> {
+    let assignment = matrix.sat_assignments.get(&variable.abs());
+    if !assignment.is_none() {
+        return Some(assignment.unwrap().value);
+    }
+    return matrix.fixed_literals.iter().find(|literal| literal.abs() == variable.abs()).copied();
+}
@@ -0,0 +1,218 @@
+use std::{fs, collections::BTreeMap, time::Instant};
+
+use crate::{log_normal, dpll, cdcl, data_structures::{Matrix, CDCLMatrix, Config, Statistics, Verbosity}, util::{read_instance_name, get_output_pathname, read_benchmark_paths}};
+
+/*
+A struct to store the result of a single instance in a benchmark results file.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchmarkRecord {
+    pub result: String,
+    pub runtime_ms: f64,
+}
+
+impl BenchmarkRecord {
+    /*
+    Checks whether the instance was solved, i.e. it didn't time out.
+    */
+    pub fn is_solved(&self) -> bool {
+        return self.result.eq("SAT") || self.result.eq("UNSAT");
+    }
+}
+
+/*
+A struct to store the number of instances in each category when comparing benchmark results against a baseline.
+
+improved => Solved with the same result in both, but faster than the baseline.
+regressed => Solved with the same result in both, but slower than the baseline.
+newly_solved => Timed out in the baseline but solved now.
+newly_timed_out => Solved in the baseline but timed out now.
+flipped => Solved in both but the result changed between SAT and UNSAT, which indicates a bug.
+missing => Only appears in one of the two results files.
+*/
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct ComparisonSummary {
+    pub improved: i32,
+    pub regressed: i32,
+    pub newly_solved: i32,
+    pub newly_timed_out: i32,
+    pub flipped: i32,
+    pub missing: i32,
+}
+
+/*
+A function to compare a benchmark results file against a baseline results file, printing the per-instance deltas and
+a summary.
+*/
+pub fn run_compare(baseline_path: &String, current_path: &String, verbosity: Verbosity) {
+    let baseline_contents = fs::read_to_string(baseline_path).expect("Unable to read baseline results file");
+    let current_contents = fs::read_to_string(current_path).expect("Unable to read results file");
+    let baseline = read_results_csv(&baseline_contents).expect("Baseline results should be a valid results CSV");
+    let current = read_results_csv(&current_contents).expect("Results should be a valid results CSV");
+    let (deltas, summary) = compare_results(&baseline, &current);
+    for delta in deltas {
+        log_normal!(verbosity, "{}", delta);
+    }
+    log_normal!(verbosity, "{}", format_comparison_summary(&summary));
+}
+
+/*
+A function to read benchmark results in CSV format. The header row must contain the columns instance, result (SAT, UNSAT
+or Timeout) and runtime_ms, in any order. Other columns are ignored.
+
+Returns the records keyed by instance name, or None if the contents are invalid.
+*/
+pub fn read_results_csv(contents: &str) -> Option<BTreeMap<String, BenchmarkRecord>> {
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+    let header: Vec<&str> = lines.next()?.split(',').map(|column| column.trim()).collect();
+    let instance_column = header.iter().position(|column| column.eq(&"instance"))?;
+    let result_column = header.iter().position(|column| column.eq(&"result"))?;
+    let runtime_column = header.iter().position(|column| column.eq(&"runtime_ms"))?;
+    let mut records = BTreeMap::new();
+    for line in lines {
+        let values: Vec<&str> = line.split(',').map(|value| value.trim()).collect();
+        if values.len() != header.len() {
+            return None;
+        }
+        let result = values[result_column].to_string();
+        if !result.eq("SAT") && !result.eq("UNSAT") && !result.eq("Timeout") {
+            return None;
+        }
+        let runtime_ms = values[runtime_column].parse::<f64>().ok()?;
+        records.insert(values[instance_column].to_string(), BenchmarkRecord { result, runtime_ms });
+    }
+    return Some(records);
+}
+
+/*
+A function to compare the results of each instance against the baseline.
+
+Returns (a line describing the delta of each instance, the summary of the comparison).
+*/
+pub fn compare_results(baseline: &BTreeMap<String, BenchmarkRecord>, current: &BTreeMap<String, BenchmarkRecord>) -> (Vec<String>, ComparisonSummary) {
+    let mut deltas = Vec::new();
+    let mut summary = ComparisonSummary::default();
+    for (instance, baseline_record) in baseline {
+        let current_record = current.get(instance);
+        if current_record.is_none() {
+            summary.missing += 1;
+            deltas.push(format!("Instance: {} -- Missing from results", instance));
+            continue;
+        }
+        let current_record = current_record.unwrap();
+        let delta = current_record.runtime_ms - baseline_record.runtime_ms;
+        let change = if !baseline_record.is_solved() && current_record.is_solved() {
+            summary.newly_solved += 1;
+            "Newly solved"
+        } else if baseline_record.is_solved() && !current_record.is_solved() {
+            summary.newly_timed_out += 1;
+            "Newly timed out"
+        } else if !baseline_record.result.eq(&current_record.result) {
+            summary.flipped += 1;
+            "Flipped"
+        } else if !current_record.is_solved() {
+            "Timed out"
+        } else if delta < 0.0 {
+            summary.improved += 1;
+            "Improved"
+        } else if delta > 0.0 {
+            summary.regressed += 1;
+            "Regressed"
+        } else {
+            "Unchanged"
+        };
+        deltas.push(format!("Instance: {} -- {} -- Result: {} -> {} -- Runtime: {}ms -> {}ms ({:+}ms)", instance, change, baseline_record.result,
+                            current_record.result, baseline_record.runtime_ms, current_record.runtime_ms, delta));
+    }
+    for instance in current.keys() {
+        if !baseline.contains_key(instance) {
+            summary.missing += 1;
+            deltas.push(format!("Instance: {} -- Missing from baseline", instance));
+        }
+    }
+    return (deltas, summary);
+}
+
+/*
+A function to format the summary of a comparison.
+
+Returns the formatted summary.
+*/
+pub fn format_comparison_summary(summary: &ComparisonSummary) -> String {
+    return format!("--------------------------------------------------------------\nImproved: {}, Regressed: {}, Newly solved: {}, Newly timed out: {}, Flipped: {}, Missing: {}",
+                    summary.improved, summary.regressed, summary.newly_solved, summary.newly_timed_out, summary.flipped, summary.missing);
+}
+
+/*
+A function to run a directory of files in QDIMACS format under both DPLL and CDCL, each with a fresh matrix and the
+configured timeout.
+
+Stores the results and runtimes of both solvers side by side in a file with the provided name "output-<filename_to_write>" 
+in the output directory, flagging instances the solvers disagree on. If stats_only is set, the summary is printed to stdout 
+instead and no file is written.
+*/
+pub fn run_bench_directory(path: String, config: Config, filename_to_write: &str, stats_only: bool) {
+    let bench_timer = Instant::now();
+    let records = match run_both_solvers(&path, &config) {
+        Ok(records) => records,
+        Err(error) => {
+            log_normal!(config.verbosity, "{}", error);
+            return;
+        }
+    };
+    let disagreements = records.values().filter(|(dpll_record, cdcl_record)| solvers_disagree(dpll_record, cdcl_record)).count();
+    let mut output_string = format!("--- DPLL vs CDCL --- \nCONFIG: [Literal Selection: {:?}, Pre-Resolution: {}, Pre-Process: {}, Universal Reduction: {}, Pure Literal Deletion: {}]",
+                                            config.literal_selection, config.pre_resolution.0, config.pre_process, config.universal_reduction, config.pure_literal_deletion);
+    output_string += &format!("\n--------------------------------------------------------------\nTotal: {}, DPLL Solved: {}, CDCL Solved: {}, Disagreements: {}\nComplete time: {}", 
+                                records.len(), records.values().filter(|(dpll_record, _)| dpll_record.is_solved()).count(),
+                                records.values().filter(|(_, cdcl_record)| cdcl_record.is_solved()).count(), disagreements, config.format_duration(bench_timer.elapsed()));
+    if stats_only {
+        log_normal!(config.verbosity, "{}", output_string);
+        return;
+    }
+    for (instance, (dpll_record, cdcl_record)) in &records {
+        output_string += &format!("\nInstance: {} -- DPLL: {} ({}ms) -- CDCL: {} ({}ms)", instance, dpll_record.result, dpll_record.runtime_ms, cdcl_record.result, cdcl_record.runtime_ms);
+        if solvers_disagree(dpll_record, cdcl_record) { output_string += " -- DISAGREEMENT" };
+    }
+    let pathname = get_output_pathname(&config.output_dir, filename_to_write);
+    fs::write(pathname, output_string).expect("Unable to write file");
+}
+
+/*
+A function to solve each instance in a directory with DPLL and then CDCL, each from a fresh matrix.
+
+Returns the (DPLL record, CDCL record) of each instance keyed by instance name, or an error if the directory can't be 
+read or contains no QDIMACS instances.
+*/
+pub fn run_both_solvers(group: &String, config: &Config) -> Result<BTreeMap<String, (BenchmarkRecord, BenchmarkRecord)>, String> {
+    let mut records = BTreeMap::new();
+    for file_path in read_benchmark_paths(group, config.verbosity)? {
+        let instance_name = read_instance_name(&file_path);
+        let instance_config = config.instance_config(&instance_name);
+        let instance_timer = Instant::now();
+        let dpll_result = match dpll::solve(&mut Matrix::new(file_path.clone(), instance_config.clone()), &mut Statistics::new(), instance_timer) {
+            dpll::Result::SAT => "SAT",
+            dpll::Result::UNSAT => "UNSAT",
+            dpll::Result::Timeout(_) | dpll::Result::DecisionLimit => "Timeout",
+        };
+        let dpll_record = BenchmarkRecord { result: dpll_result.to_string(), runtime_ms: instance_timer.elapsed().as_secs_f64() * 1000.0 };
+
+        let instance_timer = Instant::now();
+        let cdcl_result = match cdcl::solve(&mut CDCLMatrix::new(file_path.clone(), instance_config), &mut Statistics::new(), instance_timer) {
+            cdcl::Result::SAT => "SAT",
+            cdcl::Result::UNSAT => "UNSAT",
+            cdcl::Result::Timeout(_) | cdcl::Result::Restart | cdcl::Result::ConflictLimit | cdcl::Result::NoProgress => "Timeout",
+        };
+        let cdcl_record = BenchmarkRecord { result: cdcl_result.to_string(), runtime_ms: instance_timer.elapsed().as_secs_f64() * 1000.0 };
+
+        records.insert(instance_name, (dpll_record, cdcl_record));
+    }
+    return Ok(records);
+}
+
+/*
+Checks whether both solvers solved an instance but gave different results, which indicates a bug.
+*/
+pub fn solvers_disagree(dpll_record: &BenchmarkRecord, cdcl_record: &BenchmarkRecord) -> bool {
+    return dpll_record.is_solved() && cdcl_record.is_solved() && !dpll_record.result.eq(&cdcl_record.result);
+}
@@ -1,7 +1,8 @@
-use std::{fs::File, io::{self, BufRead}, path::Path, collections::HashMap};
+use std::{fs::{self, File}, io::{self, BufRead}, path::Path, collections::{HashMap, HashSet, VecDeque}, str::FromStr, time::{Duration, Instant}, sync::{Arc, atomic::{AtomicBool, Ordering as AtomicOrdering}}};
 use multimap::MultiMap;
+use serde::{Serialize, Deserialize};
 
-use crate::util::sort_literals_order;
+use crate::{qrat::QratStep, util::{format_duration_ms, sort_literals_order, merge_literals_order, format_trail, next_random, derive_instance_seed}, universal_reduction::detect_universal_literal};
 
 
 /*
@@ -11,6 +12,12 @@ An enum to store the type of solver algorithm to run.
 pub enum SolverType {
     DPLL,
     CDCL,
+    MaxQBF,
+    Parse,
+    Auto,
+    Compare,
+    Both,
+    Aggregate,
 }
 
 /*
@@ -19,12 +26,17 @@ A struct to store:
 - whether you are running a benchmark or instance,
 - the benchmark directory path or the instance file path
 - the filename you want the results stored in
+- whether a benchmark should only print its summary instead of writing the detailed results file
+- the baseline results file path to compare against when using Compare
 */
 pub struct Solver {
     pub solver_type: SolverType,
     pub run_bench: bool,
     pub path: String,
+    pub instance_paths: Vec<String>,
     pub output: String,
+    pub stats_only: bool,
+    pub baseline_path: String,
 }
 
 /*
@@ -33,16 +45,49 @@ A struct to store the hyperparameters governing how pre-resolution is ran.
 min_ratio: Min clause percentage of original clause database
 max_ratio: Max clause percentage of original clause database
 max_clause_length: Don't add resolved clause if the length is greater than this value
+length_per_level: Allow resolved clauses this many more literals for each quantification level below the outermost, 
+                  taken from the innermost literal of the clause
+min_level, max_level: Only resolve on existential literals whose quantification level is within this range
 repeat_below: Add another resolved clause for the current quantifier if clause length is greater than this value
 iterative: Defines whether to run pre-resolution iteratively on the resolved clauses, and how many iterations to run.
+max_added_clauses: Stop resolving once this many resolved clauses have been found, in total across all iterations
+max_duration_ms: Stop resolving on further literals once pre-resolution has run for longer than this many milliseconds
+subsumption: Mark clauses that are subsumed by a shorter resolved clause as removed after pre-resolution
 */
 #[derive(Clone)]
 pub struct ResolutionConfig {
     pub min_ratio: f32,
     pub max_ratio: f32,
     pub max_clause_length: usize,
+    pub length_per_level: usize,
+    pub min_level: usize,
+    pub max_level: usize,
     pub repeat_above: usize, 
     pub iterations: i32,
+    pub max_added_clauses: usize,
+    pub max_duration_ms: usize,
+    pub subsumption: bool,
+    pub universal_reduction_in_presolve: bool,
+}
+
+impl ResolutionConfig {
+    /*
+    A function to get the maximum length of a resolved clause whose innermost literal is at the given quantification 
+    level. With a length_per_level of 0 this is the flat max_clause_length cap.
+    */
+    pub fn max_clause_length_at_level(&self, q_level: i32) -> usize {
+        let levels_below_outermost = (q_level - 1).max(0) as usize;
+        return self.max_clause_length.saturating_add(self.length_per_level.saturating_mul(levels_below_outermost));
+    }
+
+    /*
+    A function to check if literals at the given quantification level may be resolved on, i.e. the level is within 
+    [min_level, max_level].
+    */
+    pub fn resolves_on_level(&self, q_level: i32) -> bool {
+        let q_level = q_level.max(0) as usize;
+        return self.min_level <= q_level && q_level <= self.max_level;
+    }
 }
 
 /*
@@ -52,6 +97,113 @@ An enum to store the type of literal selection.
 pub enum LiteralSelection {
     Ordered, // In-order selection
     VariableStateSum, // Variable State Sum selection
+    DLIS, // Dynamic Largest Individual Sum selection
+}
+
+/*
+An enum to store the polarity first tried for a literal chosen by Ordered literal selection.
+
+Positive => Try the positive literal first.
+Negative => Try the negative literal first.
+Occurrence => Try the polarity which appears in more clauses first, the same as variable state sum.
+*/
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DefaultPhase {
+    Positive,
+    Negative,
+    Occurrence,
+}
+
+/*
+An enum to store how much the solver writes to the output, ordered from least to most.
+
+Quiet => Nothing is written.
+Normal => Results, statistics and the messages of enabled options are written.
+Debug => The decisions, restarts and reductions made during the search are also written.
+*/
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Debug,
+}
+
+/*
+An enum to store the policy used to decide when to restart during CDCL.
+
+Luby => Restart after a number of conflicts following the luby series.
+Glucose => Restart when the average LBD of the last window learned clauses is greater than margin times the average LBD
+           of all learned clauses.
+*/
+#[derive(Clone, PartialEq, Debug)]
+pub enum RestartPolicy {
+    Luby,
+    Glucose { window: usize, margin: f32 },
+}
+
+/*
+An enum to store the scheme used to learn clauses during CDCL conflict analysis.
+
+FirstUIP => Stop resolving once the learned clause meets the first unique implication point stopping constraints.
+Decision => Keep resolving until only decision literals remain, learning a decision clause.
+*/
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum LearningScheme {
+    FirstUIP,
+    Decision,
+}
+
+/*
+An enum to store the order unit literals are taken from the propagation queue during unit propagation.
+
+FIFO => Propagate the unit literals in the order they were found, as a queue.
+LIFO => Propagate the most recently found unit literal first, as a stack.
+*/
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum PropagationOrder {
+    FIFO,
+    LIFO,
+}
+
+/*
+An enum to store the phase of solving in which an instance timed out.
+
+Preprocess => Timed out during pre-processing.
+PreResolution => Timed out during pre-resolution.
+Search => Timed out during the DPLL or CDCL search.
+*/
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TimeoutPhase {
+    Preprocess,
+    PreResolution,
+    Search,
+}
+
+/*
+A struct to store a flag for interrupting a solve from another thread. Clones share the flag, so interrupting any clone
+of a config interrupts every solve using it.
+*/
+#[derive(Clone, Debug, Default)]
+pub struct Interrupt(Arc<AtomicBool>);
+
+impl Interrupt {
+    /*
+    Creates a flag that hasn't been raised.
+    */
+    pub fn new() -> Self {
+        Interrupt(Arc::new(AtomicBool::new(false)))
+    }
+
+    /*
+    A function to raise the flag, so that solves using it stop at their next timeout check.
+    */
+    pub fn interrupt(&self) {
+        self.0.store(true, AtomicOrdering::SeqCst);
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        return self.0.load(AtomicOrdering::SeqCst);
+    }
 }
 
 /*
@@ -60,11 +212,120 @@ A struct to store the solver configuration.
 #[derive(Clone)]
 pub struct Config {
     pub literal_selection: LiteralSelection,
+    pub fallback_literal_selection: Option<LiteralSelection>,
+    pub default_phase: DefaultPhase,
+    pub propagation_order: PropagationOrder,
     pub pre_resolution: (bool, ResolutionConfig),
     pub pre_process: bool,
+    pub resolution_before_preprocess: bool,
     pub universal_reduction: bool,
     pub pure_literal_deletion: bool,
+    pub prune_void_quantifiers: bool,
     pub restarts: bool,
+    pub restart_policy: RestartPolicy,
+    pub restart_blocking: bool,
+    pub sat_tail: bool,
+    pub learning_scheme: LearningScheme,
+    pub repeated_conflict_limit: usize,
+    pub max_conflicts: usize,
+    pub max_preprocess_iterations: usize,
+    pub timeout: Duration,
+    pub interrupt: Interrupt,
+    pub iterative_deepening: bool,
+    pub max_decisions: usize,
+    pub initial_assignments: Vec<i32>,
+    pub remove_duplicate_clauses: bool,
+    pub reserve_hint: bool,
+    pub shuffle_clauses: bool,
+    pub seed: u64,
+    pub per_instance_seed: bool,
+    pub verbose_preprocess: bool,
+    pub verbose_benchmark: bool,
+    pub verbosity: Verbosity,
+    pub variable_elimination: (bool, usize),
+    pub deterministic: bool,
+    pub debug: bool,
+    pub dump_first_learned: bool,
+    pub dump_implication_graph: bool,
+    pub record_events: bool,
+    pub statistics_json: bool,
+    pub warm_restart: bool,
+    pub verify: bool,
+    pub certificate: bool,
+    pub certificate_dont_cares: bool,
+    pub qrat: bool,
+    pub output_dir: String,
+    pub times_in_milliseconds: bool,
+    pub auto_alternation_threshold: usize,
+}
+
+impl Default for Config {
+    /*
+    Creates the default solver configuration, matching the example config.json in the README.
+    */
+    fn default() -> Self {
+        Config {
+            literal_selection: LiteralSelection::VariableStateSum,
+            fallback_literal_selection: None,
+            default_phase: DefaultPhase::Positive,
+            propagation_order: PropagationOrder::FIFO,
+            pre_resolution: (false, ResolutionConfig {
+                min_ratio: 0.25,
+                max_ratio: 0.5,
+                max_clause_length: usize::MAX,
+                length_per_level: 0,
+                min_level: 0,
+                max_level: usize::MAX,
+                repeat_above: 3,
+                iterations: 1,
+                max_added_clauses: usize::MAX,
+                max_duration_ms: usize::MAX,
+                subsumption: false,
+                universal_reduction_in_presolve: false,
+            }),
+            pre_process: true,
+            resolution_before_preprocess: false,
+            universal_reduction: true,
+            pure_literal_deletion: true,
+            prune_void_quantifiers: false,
+            restarts: true,
+            restart_policy: RestartPolicy::Luby,
+            restart_blocking: false,
+            sat_tail: false,
+            learning_scheme: LearningScheme::FirstUIP,
+            repeated_conflict_limit: 100,
+            max_conflicts: usize::MAX,
+            max_preprocess_iterations: usize::MAX,
+            timeout: Duration::from_secs(30),
+            interrupt: Interrupt::new(),
+            iterative_deepening: false,
+            max_decisions: usize::MAX,
+            initial_assignments: Vec::new(),
+            remove_duplicate_clauses: true,
+            reserve_hint: false,
+            shuffle_clauses: false,
+            seed: 0,
+            per_instance_seed: false,
+            verbose_preprocess: false,
+            verbose_benchmark: false,
+            verbosity: Verbosity::Normal,
+            variable_elimination: (false, 0),
+            deterministic: false,
+            debug: false,
+            dump_first_learned: false,
+            dump_implication_graph: false,
+            record_events: false,
+            statistics_json: false,
+            warm_restart: false,
+            verify: false,
+            certificate: false,
+            certificate_dont_cares: false,
+            qrat: false,
+            output_dir: ".".to_string(),
+            times_in_milliseconds: false,
+            auto_alternation_threshold: 3,
+        }
+    }
 }
 
 impl Config {
@@ -72,6 +333,10 @@ impl Config {
         return self.pure_literal_deletion;
     }
 
+    pub fn prune_void_quantifiers_enabled(&self) -> bool {
+        return self.prune_void_quantifiers;
+    }
+
     pub fn universal_reduction_enabled(&self) -> bool {
         return self.universal_reduction;
     }
@@ -87,17 +352,202 @@ impl Config {
     pub fn restarts_enabled(&self) -> bool {
         return self.restarts;
     }
+
+    pub fn restart_blocking_enabled(&self) -> bool {
+        return self.restart_blocking;
+    }
+
+    /*
+    A function to check whether the solve started at the given time has run for longer than the timeout, or has been
+    interrupted.
+    */
+    pub fn timed_out(&self, timer: Instant) -> bool {
+        return timer.elapsed() > self.timeout || self.interrupted();
+    }
+
+    pub fn interrupted(&self) -> bool {
+        return self.interrupt.is_interrupted();
+    }
+
+    pub fn fallback_enabled(&self) -> bool {
+        return !self.fallback_literal_selection.is_none();
+    }
+
+    /*
+    A function to get the configuration of the first attempt at an instance. When a fallback literal selection is
+    configured, the first attempt only gets half of the timeout, and half of the decision and conflict limits when they
+    are set, so that budget remains for the retry.
+    */
+    pub fn first_attempt_config(&self) -> Config {
+        let mut config = self.clone();
+        if self.fallback_enabled() {
+            config.timeout = self.timeout / 2;
+            if self.max_decisions != usize::MAX { config.max_decisions = self.max_decisions / 2 };
+            if self.max_conflicts != usize::MAX { config.max_conflicts = self.max_conflicts / 2 };
+        }
+        return config;
+    }
+
+    /*
+    A function to get the configuration of the retry after the first attempt gave up, using the fallback literal
+    selection with the full timeout and limits.
+    */
+    pub fn fallback_config(&self) -> Config {
+        let mut config = self.clone();
+        config.literal_selection = self.fallback_literal_selection.clone().unwrap();
+        return config;
+    }
+
+    pub fn sat_tail_enabled(&self) -> bool {
+        return self.sat_tail;
+    }
+
+    pub fn resolution_before_preprocess_enabled(&self) -> bool {
+        return self.resolution_before_preprocess;
+    }
+
+    pub fn iterative_deepening_enabled(&self) -> bool {
+        return self.iterative_deepening;
+    }
+
+    /*
+    A function to check if duplicate clauses are removed when parsing an instance.
+    */
+    pub fn remove_duplicate_clauses_enabled(&self) -> bool {
+        return self.remove_duplicate_clauses;
+    }
+
+    pub fn reserve_hint_enabled(&self) -> bool {
+        return self.reserve_hint;
+    }
+
+    pub fn shuffle_clauses_enabled(&self) -> bool {
+        return self.shuffle_clauses;
+    }
+
+    pub fn per_instance_seed_enabled(&self) -> bool {
+        return self.per_instance_seed;
+    }
+
+    /*
+    A function to get the configuration to solve the named instance of a benchmark with. When per-instance seeds are 
+    enabled, the seed is derived from the instance name so each instance gets a distinct but reproducible seed.
+    */
+    pub fn instance_config(&self, instance_name: &str) -> Config {
+        let mut config = self.clone();
+        if self.per_instance_seed_enabled() {
+            config.seed = derive_instance_seed(self.seed, instance_name);
+        }
+        return config;
+    }
+
+    pub fn verbose_preprocess_enabled(&self) -> bool {
+        return self.verbose_preprocess;
+    }
+
+    pub fn verbose_benchmark_enabled(&self) -> bool {
+        return self.verbose_benchmark;
+    }
+
+    pub fn variable_elimination_enabled(&self) -> bool {
+        return self.variable_elimination.0;
+    }
+
+    pub fn deterministic_enabled(&self) -> bool {
+        return self.deterministic;
+    }
+
+    pub fn debug_enabled(&self) -> bool {
+        return self.debug;
+    }
+
+    pub fn dump_first_learned_enabled(&self) -> bool {
+        return self.dump_first_learned;
+    }
+
+    pub fn dump_implication_graph_enabled(&self) -> bool {
+        return self.dump_implication_graph;
+    }
+
+    pub fn record_events_enabled(&self) -> bool {
+        return self.record_events;
+    }
+
+    pub fn qrat_enabled(&self) -> bool {
+        return self.qrat;
+    }
+
+    /*
+    A function to format a duration for a report, as milliseconds when times in milliseconds are enabled, or in the 
+    Debug format of Duration otherwise.
+    */
+    pub fn format_duration(&self, duration: Duration) -> String {
+        if self.times_in_milliseconds {
+            return format!("{}ms", format_duration_ms(duration));
+        }
+        return format!("{:?}", duration);
+    }
+
+    pub fn statistics_json_enabled(&self) -> bool {
+        return self.statistics_json;
+    }
+
+    pub fn warm_restart_enabled(&self) -> bool {
+        return self.warm_restart;
+    }
+
+    pub fn verify_enabled(&self) -> bool {
+        return self.verify;
+    }
+
+    pub fn certificate_enabled(&self) -> bool {
+        return self.certificate;
+    }
 }
 
 /*
 A struct to store statistics relating to number of unit propagations,
 backtrack/backjump counts, and conflict counts where appropriate.
+
+It also stores the impact of each simplification technique during pre-processing - the clauses removed by unit 
+propagation, the literals removed by universal reduction, and the variables removed by pure literal deletion. These are
+only counted while is_preprocessing is set, so the same techniques applied during search are not counted. The literals 
+removed by the universal reduction applied once after parsing are counted separately, whether or not pre-processing is
+enabled.
+
+The peak clause count is the largest number of live clauses, original and learned, in the clause database during CDCL.
+The learned literal total is the combined length of the learned clauses added to the clause database during CDCL.
+
+The max decision level is the deepest decision level reached during CDCL. The conflict count is the number of conflicts
+reached during CDCL, including those discarded by a restart, and is what the conflict limit is checked against.
+
+The SAT tail count is the number of times CDCL handed the existential tail of the prefix to the SAT procedure.
+
+The decision count is the number of decisions made by DPLL, across every iterative deepening attempt. The attempt 
+decision count is the number made by the current attempt, which is what the decision limit is checked against, and the 
+decision bound is the bound of the iterative deepening attempt that solved the instance, if iterative deepening was used.
 */
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Statistics {
     pub propagation_count: i32,
     pub backtrack_count: i32,
     pub learned_clause_count: i32,
+    pub conflict_count: i32,
+    pub learned_literal_total: i32,
+    pub unit_propagation_removed_clauses: i32,
+    pub universal_reduction_removed_literals: i32,
+    #[serde(default)]
+    pub parse_reduction_removed_literals: i32,
+    pub pure_literal_removed_variables: i32,
+    pub peak_clause_count: i32,
+    pub max_decision_level: i32,
+    pub sat_tail_count: i32,
+    pub decision_count: usize,
+    #[serde(skip)]
+    pub attempt_decision_count: usize,
+    pub decision_bound: Option<usize>,
+    #[serde(skip)]
+    pub is_preprocessing: bool,
 }
 
 impl Statistics {
@@ -105,7 +555,28 @@ impl Statistics {
     Create an empty statistics struct.
     */
     pub fn new() -> Self {
-        Statistics { propagation_count: 0, backtrack_count: 0, learned_clause_count: 0 }
+        Statistics { propagation_count: 0, backtrack_count: 0, learned_clause_count: 0, conflict_count: 0, learned_literal_total: 0, 
+                     unit_propagation_removed_clauses: 0, universal_reduction_removed_literals: 0, parse_reduction_removed_literals: 0, pure_literal_removed_variables: 0, peak_clause_count: 0,
+                     max_decision_level: 0, sat_tail_count: 0, decision_count: 0, attempt_decision_count: 0, decision_bound: None, 
+                     is_preprocessing: false }
+    }
+
+    /*
+    A function to export the statistics as JSON, so the statistics of many runs can be aggregated offline.
+
+    Returns the statistics in JSON format.
+    */
+    pub fn to_json(&self) -> String {
+        return serde_json::to_string_pretty(self).unwrap();
+    }
+
+    /*
+    A function to import statistics exported by to_json.
+
+    Returns the statistics, or an error if the JSON isn't valid statistics.
+    */
+    pub fn from_json(json: &str) -> Result<Statistics, String> {
+        return serde_json::from_str(json).map_err(|error| format!("Invalid statistics: {}", error));
     }
 
     /*
@@ -115,6 +586,21 @@ impl Statistics {
         self.propagation_count += 1;
     }
 
+    /*
+    A function to update the max decision level with the current decision level.
+    */
+    pub fn update_max_decision_level(&mut self, decision_level: i32) {
+        self.max_decision_level = self.max_decision_level.max(decision_level);
+    }
+
+    /*
+    A function to increment decision count, and the decision count of the current attempt.
+    */
+    pub fn increment_decision_count(&mut self) {
+        self.decision_count += 1;
+        self.attempt_decision_count += 1;
+    }
+
     /*
     A function to increment backtrack/backjump count.
     */
@@ -122,12 +608,85 @@ impl Statistics {
         self.backtrack_count += 1;
     }
 
+    /*
+    A function to increment the number of times the existential tail was solved as SAT.
+    */
+    pub fn increment_sat_tail_count(&mut self) {
+        self.sat_tail_count += 1;
+    }
+
     /*
     A function to increment conflict count.
     */
     pub fn increment_learned_clause_count(&mut self) {
         self.learned_clause_count += 1;
     }
+
+    /*
+    A function to increment the number of conflicts reached during CDCL.
+    */
+    pub fn increment_conflict_count(&mut self) {
+        self.conflict_count += 1;
+    }
+
+    /*
+    A function to add the length of a learned clause to the learned literal total.
+    */
+    pub fn add_learned_literals(&mut self, count: i32) {
+        self.learned_literal_total += count;
+    }
+
+    /*
+    A function to get the average length of the learned clauses, or 0 if no clause has been learned.
+    */
+    pub fn average_learned_clause_length(&self) -> f32 {
+        if self.learned_clause_count == 0 {
+            return 0.0;
+        }
+        return self.learned_literal_total as f32 / self.learned_clause_count as f32;
+    }
+
+    /*
+    A function to add to the number of clauses removed by unit propagation during pre-processing.
+    */
+    pub fn add_unit_propagation_removed_clauses(&mut self, count: i32) {
+        if self.is_preprocessing {
+            self.unit_propagation_removed_clauses += count;
+        }
+    }
+
+    /*
+    A function to add to the number of literals removed by universal reduction during pre-processing.
+    */
+    pub fn add_universal_reduction_removed_literals(&mut self, count: i32) {
+        if self.is_preprocessing {
+            self.universal_reduction_removed_literals += count;
+        }
+    }
+
+    /*
+    A function to add to the number of variables removed by pure literal deletion during pre-processing.
+    */
+    pub fn add_pure_literal_removed_variables(&mut self, count: i32) {
+        if self.is_preprocessing {
+            self.pure_literal_removed_variables += count;
+        }
+    }
+
+    /*
+    A function to update the peak clause count with the current clause count.
+    */
+    pub fn update_peak_clause_count(&mut self, clause_count: i32) {
+        self.peak_clause_count = self.peak_clause_count.max(clause_count);
+    }
+
+    /*
+    A function to format the statistics for each simplification technique.
+    */
+    pub fn format_simplification_statistics(&self) -> String {
+        return format!("Unit Propagation Removed Clauses: {}, Universal Reduction Removed Literals: {}, Parse Reduction Removed Literals: {}, Pure Literal Removed Variables: {}", 
+                       self.unit_propagation_removed_clauses, self.universal_reduction_removed_literals, self.parse_reduction_removed_literals, self.pure_literal_removed_variables);
+    }
 }
 
 /*
@@ -142,7 +701,7 @@ pub struct UniversalReductionClause {
 /*
 An enum for storing the quantification type.
 */
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum QuantifierType {
     Universal,
     Existential,
@@ -151,19 +710,47 @@ pub enum QuantifierType {
 /*
 A struct to store a singular quantified literal that is contained in the quantifier prefix. 
 
-It stores the quantification type, the literal, and the quantification level.
+It stores the quantification type, the literal, the quantification level, and the position the literal appeared in the
+original quantifier prefix.
+
+Quantifiers are sorted by order_key, the quantification level then the position in the original quantifier prefix.
 */
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct Quantifier {
     pub q_type: QuantifierType,
     pub literal: i32,
     pub q_level: i32,
+    pub position: usize,
+}
+
+impl Quantifier {
+    /*
+    A function to check if the quantifier is universal.
+    */
+    pub fn is_universal(&self) -> bool {
+        return self.q_type.eq(&QuantifierType::Universal);
+    }
+
+    /*
+    A function to check if the quantifier is existential.
+    */
+    pub fn is_existential(&self) -> bool {
+        return self.q_type.eq(&QuantifierType::Existential);
+    }
+
+    /*
+    A function to get the key quantifiers are ordered by, the quantification level then the position in the original
+    quantifier prefix.
+    */
+    pub fn order_key(&self) -> (i32, usize) {
+        return (self.q_level, self.position);
+    }
 }
 
 /*
 A struct for storing the clause database and the number of non-removed clauses.
 */
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ClauseSet {
     pub clause_list: Vec<Clause>,
     pub clause_count: i32,
@@ -174,7 +761,20 @@ impl ClauseSet {
     A function to decrease the clause counter one.
     */
     pub fn decrement_counter(&mut self) {
-        self.clause_count -= 1;
+        self.decrease_counter(1);
+    }
+
+    /*
+    A function to decrease the clause counter by the number of removed clauses. The counter never crosses below 0, as
+    -1 is the empty clause sentinel which only check_contradiction and set_contradiction may set. Removing more clauses
+    than are counted means a clause was removed twice, which panics in every build, as clamping the count would instead
+    give a false verdict. Once the sentinel is set, removing clauses leaves it in place.
+    */
+    pub fn decrease_counter(&mut self, removed_clause_count: i32) {
+        if self.contains_empty_clause() { return };
+        assert!(self.clause_count >= removed_clause_count,
+                "Clause count {} decreased by {}, a clause was removed twice", self.clause_count, removed_clause_count);
+        self.clause_count -= removed_clause_count;
     }
 
     /*
@@ -200,27 +800,103 @@ impl ClauseSet {
             if self.clause_count.eq(&-1) {true} else {false}
         } else {
             if self.clause_list[clause_index.unwrap() as usize].is_empty() {
-                self.clause_count = -1;
+                self.set_contradiction();
                 return true;
             } else {
                 return false;
             }
         }
     }
+
+    /*
+    A function to set the empty clause sentinel, for contradictions found without an empty clause, such as propagating a
+    universal literal.
+    */
+    pub fn set_contradiction(&mut self) {
+        self.clause_count = -1;
+    }
+
+    /*
+    A function to iterate over the clauses that have not been removed, so callers don't have to check is_removed.
+
+    Returns an iterator of (clause index, clause) pairs.
+    */
+    pub fn active_clauses(&self) -> impl DoubleEndedIterator<Item = (usize, &Clause)> {
+        return self.clause_list.iter().enumerate().filter(|(_index, clause)| !clause.is_removed);
+    }
+}
+
+/*
+A struct for storing the raw list of literals in a clause, in the order they were written. It is parsed from a clause line
+in DIMACS format, e.g. "1 -2 3 0", where the trailing 0 is optional.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClauseLiterals(pub Vec<i32>);
+
+impl FromStr for ClauseLiterals {
+    type Err = String;
+
+    /*
+    A function to parse a clause line into its list of literals. Fails if any literal isn't an integer, or a 0 appears
+    before the end of the line.
+    */
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut tokens = s.split_whitespace().collect::<Vec<&str>>();
+        if tokens.last() == Some(&"0") {
+            tokens.pop();
+        }
+        let mut literals = Vec::new();
+        for token in tokens {
+            let literal = token.parse::<i32>().map_err(|_| format!("Invalid literal \"{}\" in clause \"{}\"", token, s))?;
+            if literal == 0 {
+                return Err(format!("Clause \"{}\" is terminated before the end of the line", s));
+            }
+            literals.push(literal);
+        }
+        return Ok(ClauseLiterals(literals));
+    }
 }
 
 /*
 A struct for storing a singular clause separated into existential and universal literals which are sorted in the
 order in which they appear in the quantifier prefix. The is_removed variable marks whether the clause is removed or not.
+The weight variable is set for soft clauses in MaxQBF instances, and is None for hard clauses.
 */
-#[derive(Debug, Clone, Eq, Hash, PartialEq)]
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct Clause {
     pub e_literals: Vec<i32>, // Sorted into the order the variables occur in the quantifier prefix
     pub a_literals: Vec<i32>, // Sorted into the order the variables occur in the quantifier prefix
     pub is_removed: bool,
+    pub weight: Option<u32>,
 }
 
 impl Clause {
+    /*
+    A function to create a new clause from a list of literals. The literals are deduplicated, split into existential and
+    universal literals, and sorted in their quantification ordering.
+
+    Returns the created clause.
+    */
+    pub fn new(literals: &[i32], variable_quantification: &MultiMap<i32, Variable>, quantification_order: &QuantificationOrder) -> Self {
+        let mut seen_literals = HashSet::new();
+        let mut e_literals = Vec::new();
+        let mut a_literals = Vec::new();
+        for literal in literals {
+            if !seen_literals.insert(*literal) { continue };
+            if variable_quantification.get(&literal.abs()).expect("Variable quantification missing literal").is_existential() {
+                e_literals.push(*literal);
+            } else {
+                a_literals.push(*literal);
+            }
+        }
+        return Clause {
+            e_literals: sort_literals_order(&quantification_order.existential_literal_order, e_literals),
+            a_literals: sort_literals_order(&quantification_order.universal_literal_order, a_literals),
+            is_removed: false,
+            weight: None,
+        };
+    }
+
     /*
     A function to create a new empty clause.
     */
@@ -229,15 +905,37 @@ impl Clause {
             e_literals: Vec::new(),
             a_literals: Vec::new(),
             is_removed: false,
+            weight: None,
         }
     }
 
     /*
-    A function that checks for a unit clause and returns the unit literal if there is one.
-    */
-    pub fn is_unit_clause(&self) -> Option<i32> {
+    A function to check whether the clause is satisfied by the given assignments.
 
-        if self.e_literals.len() + self.a_literals.len() == 1  && !self.is_removed {
+    Returns Some(true) if any literal is assigned true, Some(false) if every literal is assigned false, and None otherwise.
+    */
+    pub fn satisfied_by(&self, assignments: &HashMap<i32, Assignment>) -> Option<bool> {
+        let mut is_determined = true;
+        for literal in self.e_literals.iter().chain(self.a_literals.iter()) {
+            let assignment = assignments.get(&literal.abs());
+            if assignment.is_none() {
+                is_determined = false;
+            } else if assignment.unwrap().value == *literal {
+                return Some(true);
+            }
+        }
+        if is_determined {
+            return Some(false);
+        }
+        return None;
+    }
+
+    /*
+    A function that checks for a unit clause and returns the unit literal if there is one.
+    */
+    pub fn is_unit_clause(&self) -> Option<i32> {
+
+        if self.e_literals.len() + self.a_literals.len() == 1  && !self.is_removed {
             if self.a_literals.is_empty() {
                 return Some(self.e_literals[0]);
             } else {
@@ -258,6 +956,17 @@ impl Clause {
         return literals;
     }
 
+    /*
+    A function to return a list containing all the universal and existential literals within the clause, without 
+    consuming the clause.
+    */
+    pub fn literals(&self) -> Vec<i32> {
+        let mut literals: Vec<i32> = Vec::new();
+        literals.extend(&self.e_literals);
+        literals.extend(&self.a_literals);
+        return literals;
+    }
+
     /*
     A function to set the a_literals to a given list of literals. Used when reversing universal reduction in CDCL.
     */
@@ -286,6 +995,18 @@ impl Clause {
         self.e_literals.retain(|&x| x != literal);
     }
 
+    /*
+    A function that removes the given literal from the existential or universal literals of the clause, depending on how 
+    its variable is quantified.
+    */
+    pub fn remove_literal(&mut self, literal: i32, variable_quantification: &MultiMap<i32, Variable>) {
+        if variable_quantification.get(&literal.abs()).expect("Variable quantification missing literal").is_existential() {
+            self.remove_e_literal(literal);
+        } else {
+            self.remove_a_literal(literal);
+        }
+    }
+
     /*
     A function to check whether the given clause contains no literals.
     */
@@ -298,25 +1019,113 @@ impl Clause {
         }
     }
 
+    /*
+    A function to check whether the clause is a soft clause in a MaxQBF instance.
+    */
+    pub fn is_soft(&self) -> bool {
+        return !self.weight.is_none();
+    }
+
+    /*
+    A function to check whether the clause contains the given literal.
+    */
+    pub fn contains_literal(&self, literal: i32) -> bool {
+        return self.e_literals.contains(&literal) || self.a_literals.contains(&literal);
+    }
+
+    /*
+    A function to check whether every literal of the clause is also in the other clause, in which case the clause 
+    subsumes the other clause. Existential and universal literals are compared separately, as a variable's literals are 
+    always in the same list.
+    */
+    pub fn is_subset_of(&self, other: &Clause) -> bool {
+        return self.e_literals.iter().all(|literal| other.e_literals.contains(literal))
+            && self.a_literals.iter().all(|literal| other.a_literals.contains(literal));
+    }
+
+    /*
+    A function to find a variable that appears in the clause and, negated, in the other clause, as needed for resolution
+    and self-subsuming strengthening.
+
+    Returns the first such variable in prefix order, existential before universal, or None if the clauses don't clash.
+    */
+    pub fn shares_complement(&self, other: &Clause) -> Option<i32> {
+        let existential_clash = self.e_literals.iter().find(|&&literal| other.e_literals.contains(&-literal));
+        if !existential_clash.is_none() {
+            return Some(existential_clash.unwrap().abs());
+        }
+        return self.a_literals.iter().find(|&&literal| other.a_literals.contains(&-literal)).map(|literal| literal.abs());
+    }
+
     /*
     A function to get the number of literals in the clause.
     */
     pub fn get_clause_length(&self) -> usize {
         return self.a_literals.len() + self.e_literals.len();
     }
+
+    /*
+    A function to merge the literals of the clause with another clause. As the literals of both clauses are already sorted
+    by the quantifier prefix, the existential and universal literals are each merged in order rather than re-sorted, and 
+    duplicate literals are removed.
+
+    Returns the merged clause.
+    */
+    pub fn merge(&self, other: &Clause, quantification_order: &QuantificationOrder) -> Clause {
+        return Clause {
+            e_literals: merge_literals_order(&quantification_order.literal_positions, &self.e_literals, &other.e_literals),
+            a_literals: merge_literals_order(&quantification_order.literal_positions, &self.a_literals, &other.a_literals),
+            is_removed: false,
+            weight: None,
+        };
+    }
+
+    /*
+    A function to resolve the clause with another clause on the given pivot literal. If the resolvent contains a literal
+    and its complement, the resolution is invalid.
+
+    Returns the resolvent as a clause with its literals sorted by the quantifier prefix, or None if the resolution is 
+    invalid.
+    */
+    pub fn resolvent_with(&self, other: &Clause, pivot: i32, quantification_order: &QuantificationOrder) -> Option<Clause> {
+        let mut resolvent = self.merge(other, quantification_order);
+        resolvent.e_literals.retain(|&literal| literal != pivot && literal != -pivot);
+        resolvent.a_literals.retain(|&literal| literal != pivot && literal != -pivot);
+        // The complement of a literal is adjacent to it after merging, as they share a position in the quantifier prefix.
+        if resolvent.e_literals.windows(2).any(|pair| pair[0] == -pair[1]) || resolvent.a_literals.windows(2).any(|pair| pair[0] == -pair[1]) {
+            return None;
+        }
+        return Some(resolvent);
+    }
 }
 
 /*
 A struct for storing information about a given variable/literal. It stores the quantification type, quantification 
 level, and value of the literal.
 */
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Variable {
     pub q_type: QuantifierType,
     pub q_level: i32,
     pub value: i32,
 }
 
+impl Variable {
+    /*
+    A function to check if the variable is universally quantified.
+    */
+    pub fn is_universal(&self) -> bool {
+        return self.q_type.eq(&QuantifierType::Universal);
+    }
+
+    /*
+    A function to check if the variable is existentially quantified.
+    */
+    pub fn is_existential(&self) -> bool {
+        return self.q_type.eq(&QuantifierType::Existential);
+    }
+}
+
 /*
 A struct for storing a single assignment of a variable. It stores the value being assigned, the decision level it was 
 assigned at, and if applicable the index of the clause that was responsible for causing the given variable to be assigned.
@@ -337,19 +1146,141 @@ impl Assignment {
     pub fn is_decision(&self) -> bool {
         return self.clause_responsible.is_none();
     }
+
+    /*
+    A function to return whether the given assignment was the result of an implication.
+
+    Returns true for an implication, and false for a decision.
+    */
+    pub fn is_implication(&self) -> bool {
+        return !self.is_decision();
+    }
+
+    /*
+    A function to get the index of the clause responsible for an implication.
+
+    Returns the clause index, or None for a decision.
+    */
+    pub fn responsible_clause(&self) -> Option<i32> {
+        return self.clause_responsible;
+    }
 }
 
 /*
-A struct for storing the order in which the literals appeared in the quantifier prefix.
+A struct for storing the trail of assignments, decisions and implications, in chronological order.
 */
 #[derive(Clone)]
+pub struct Trail(pub Vec<Assignment>);
+
+impl Trail {
+    /*
+    A function to create a new empty trail.
+    */
+    pub fn new() -> Self {
+        return Trail(Vec::new());
+    }
+
+    /*
+    A function to add an assignment to the end of the trail.
+    */
+    pub fn push(&mut self, assignment: Assignment) {
+        self.0.push(assignment);
+    }
+
+    /*
+    A function to remove the most recent assignment from the trail.
+
+    Returns the assignment, or None if the trail is empty.
+    */
+    pub fn pop(&mut self) -> Option<Assignment> {
+        return self.0.pop();
+    }
+
+    /*
+    A function to get the number of assignments in the trail.
+    */
+    pub fn len(&self) -> usize {
+        return self.0.len();
+    }
+
+    /*
+    A function to iterate over the assignments in chronological order.
+    */
+    pub fn iter(&self) -> std::slice::Iter<'_, Assignment> {
+        return self.0.iter();
+    }
+
+    /*
+    A function to get the most recent decision made at the given decision level.
+
+    Returns the decision, or None if no decision was made at the level.
+    */
+    pub fn last_decision_at_level(&self, level: i32) -> Option<&Assignment> {
+        return self.0.iter().rev().find(|assignment| assignment.decision_level == level && assignment.is_decision());
+    }
+
+    /*
+    A function to get the literals assigned at the given decision level, in chronological order.
+    */
+    pub fn literals_at_level(&self, level: i32) -> Vec<i32> {
+        return self.0.iter().filter(|assignment| assignment.decision_level == level).map(|assignment| assignment.value).collect();
+    }
+}
+
+/*
+A struct for storing the order in which the literals appeared in the quantifier prefix. literal_positions maps each 
+variable to its position in the existential or universal literal order, so literals can be compared without searching 
+the orders.
+*/
+#[derive(Clone, Serialize, Deserialize)]
 pub struct QuantificationOrder {
     pub existential_literal_order: Vec<i32>,
     pub universal_literal_order: Vec<i32>,
+    pub literal_positions: HashMap<i32, usize>,
+}
+
+impl QuantificationOrder {
+    /*
+    Creates a new QuantificationOrder from the existential and universal literal orders.
+    */
+    pub fn new(existential_literal_order: Vec<i32>, universal_literal_order: Vec<i32>) -> Self {
+        let mut quantification_order = QuantificationOrder { existential_literal_order, universal_literal_order, literal_positions: HashMap::new() };
+        quantification_order.rebuild_literal_positions();
+        return quantification_order;
+    }
+
+    /*
+    A function to rebuild the literal positions after the existential or universal literal order has changed.
+    */
+    pub fn rebuild_literal_positions(&mut self) {
+        self.literal_positions = self.existential_literal_order.iter().enumerate().chain(self.universal_literal_order.iter().enumerate())
+            .map(|(position, literal)| (literal.abs(), position)).collect();
+    }
+}
+
+/*
+A struct for storing the parts of a Matrix that are written to a cache file. The clause references are rebuilt from the
+clause database on load, and the config is given on load so a cached matrix can be solved with any configuration.
+*/
+#[derive(Serialize, Deserialize)]
+struct MatrixCache {
+    quantifier_list: Vec<Quantifier>,
+    clause_set: ClauseSet,
+    variables: Vec<Variable>,
+    quantification_order: QuantificationOrder,
+    expected_result: Option<bool>,
 }
 
 /*
 A struct for storing data needed for facilitating a restart during CDCL.
+
+The literal block distance (LBD) of each learned clause is tracked for the Glucose restart policy. recent_lbds stores the 
+LBDs of the most recent learned clauses since the last restart, and lbd_sum and lbd_count store the totals over the run.
+
+interval_max_decision_level stores the max decision level reached in the current restart interval, and 
+previous_interval_max_decision_level the max decision level reached in the previous one. new_depth_record is set when the 
+search reaches a deeper decision level than in the previous restart interval, in which case restart blocking postpones 
+the next restart. blocked_restarts counts the restarts postponed this way.
 */
 #[derive(Clone)]
 pub struct RestartData {
@@ -357,13 +1288,21 @@ pub struct RestartData {
     pub conflicts_until_restart: i32,
     pub constant: i32,
     pub current_conflicts: i32,
+    pub policy: RestartPolicy,
+    pub recent_lbds: VecDeque<i32>,
+    pub lbd_sum: i64,
+    pub lbd_count: i64,
+    pub interval_max_decision_level: i32,
+    pub previous_interval_max_decision_level: i32,
+    pub new_depth_record: bool,
+    pub blocked_restarts: i32,
 }
 
 impl RestartData {
     /*
     A function to create a new RestartData data structure.
     */
-    pub fn new(constant: i32) -> Self {
+    pub fn new(constant: i32, policy: RestartPolicy) -> Self {
         let restart_counter = 1;
         let conflicts_until_restart = constant;
         return RestartData {
@@ -371,9 +1310,39 @@ impl RestartData {
             conflicts_until_restart,
             constant,
             current_conflicts: 0,
+            policy,
+            recent_lbds: VecDeque::new(),
+            lbd_sum: 0,
+            lbd_count: 0,
+            interval_max_decision_level: 0,
+            previous_interval_max_decision_level: 0,
+            new_depth_record: false,
+            blocked_restarts: 0,
         };
     }
 
+    /*
+    A function to record the LBD of a newly learned clause, keeping only the most recent window of LBDs for the Glucose
+    restart policy.
+    */
+    pub fn record_lbd(&mut self, lbd: i32) {
+        self.lbd_sum += lbd as i64;
+        self.lbd_count += 1;
+        if let RestartPolicy::Glucose { window, margin: _ } = self.policy {
+            self.recent_lbds.push_back(lbd);
+            if self.recent_lbds.len() > window {
+                self.recent_lbds.pop_front();
+            }
+        }
+    }
+
+    /*
+    A function to reset the window of recent LBDs after a restart.
+    */
+    pub fn reset_recent_lbds(&mut self) {
+        self.recent_lbds.clear();
+    }
+
     /*
     A function to update the number of conflicts that should be allowed before performing a restart. The algorithm
     implements a geometric progression to allow for longer restart intervals based on the luby series.
@@ -410,24 +1379,69 @@ impl RestartData {
         self.current_conflicts = 0;
     }
     
+    /*
+    A function to postpone a restart because the search has reached a new max decision level, starting a new restart 
+    interval without restarting.
+    */
+    pub fn block_restart(&mut self) {
+        self.blocked_restarts += 1;
+        self.start_restart_interval();
+        self.reset_current_conflicts();
+        self.reset_recent_lbds();
+    }
+
+    /*
+    A function to record a decision level reached in the current restart interval, noting a new depth record if it is
+    deeper than any decision level reached in the previous restart interval.
+    */
+    pub fn record_decision_level(&mut self, decision_level: i32) {
+        self.interval_max_decision_level = self.interval_max_decision_level.max(decision_level);
+        if decision_level > self.previous_interval_max_decision_level {
+            self.new_depth_record = true;
+        }
+    }
+
+    /*
+    A function to start a new restart interval, after a restart or a blocked restart.
+    */
+    pub fn start_restart_interval(&mut self) {
+        self.previous_interval_max_decision_level = self.interval_max_decision_level;
+        self.interval_max_decision_level = 0;
+        self.new_depth_record = false;
+    }
+
     /*
     A function to determine whether a restart should occur or not.
 
     Returns true if a restart should be performed, and false otherwise.
     */
     pub fn should_restart(&self) -> bool {
-        return self.current_conflicts == self.conflicts_until_restart;
+        match self.policy {
+            RestartPolicy::Luby => {
+                return self.current_conflicts == self.conflicts_until_restart;
+            },
+            RestartPolicy::Glucose { window, margin } => {
+                if self.recent_lbds.len() < window || self.lbd_count == 0 {
+                    return false;
+                }
+                let recent_average = self.recent_lbds.iter().sum::<i32>() as f32 / self.recent_lbds.len() as f32;
+                let global_average = self.lbd_sum as f32 / self.lbd_count as f32;
+                return recent_average > margin * global_average;
+            }
+        }
     }
 }
 
 /*
 A struct for storing the core data structures required for performing the DPLL and CDCL procedures.
 
-- quantifier_list stores the quantifier prefix.
+- quantifier_list stores the quantifier prefix. It is parsed in prefix order and quantifiers are only ever removed from
+  it, so it remains sorted by (quantification level, original position).
 - clause_set stores the clause database and clause count.
 - clause_references stores the all-watched literals data structure - in a multimap for O(1) access.
 - variable_quantification stores the quantification type of each literal - in a multimap for O(1) access.
 - quantification_order stores the order in which the literals appear in the quantifier prefix.
+- expected_result stores the result given by a "c result SAT" or "c result UNSAT" comment in the instance, if any.
 - config stores the configuration of the solver stores in config.json.
 */
 #[derive(Clone)]
@@ -437,6 +1451,7 @@ pub struct Matrix {
     pub clause_references: MultiMap<i32, i32>,
     pub variable_quantification: MultiMap<i32, Variable>,
     pub quantification_order: QuantificationOrder,
+    pub expected_result: Option<bool>,
     pub config: Config,
 }
 
@@ -445,21 +1460,128 @@ impl Matrix {
     Creates a new Matrix data structure.
     */
     pub fn new(filename: String, config: Config) -> Self {
-        let (quantifier_list, clause_set, clause_references, variable_quantification, quantification_order) = Matrix::create_structures(filename);
-        return Matrix {
+        let (quantifier_list, clause_set, clause_references, variable_quantification, quantification_order, expected_result) = Matrix::create_structures(filename, config.remove_duplicate_clauses_enabled(), config.reserve_hint_enabled());
+        let mut matrix = Matrix {
+            quantifier_list,
+            clause_set,
+            clause_references,
+            variable_quantification,
+            quantification_order,
+            expected_result,
+            config
+        };
+        if matrix.config.shuffle_clauses_enabled() { matrix.shuffle_clauses(matrix.config.seed) };
+        return matrix;
+    }
+
+    /*
+    Creates a new Matrix data structure for the conjunction of several QBF instances that share a variable namespace. 
+    A single instance is read as it is by Matrix::new.
+
+    Returns the Matrix, or an error if an instance can't be read or a variable is quantified inconsistently.
+    */
+    pub fn from_files(filenames: &[String], config: Config) -> Result<Self, String> {
+        if filenames.len() == 1 {
+            return Ok(Matrix::new(filenames[0].clone(), config));
+        }
+        let lines = Matrix::merge_instance_lines(filenames)?;
+        let (quantifier_list, clause_set, clause_references, variable_quantification, quantification_order, expected_result) = Matrix::create_structures_from_lines(lines, config.remove_duplicate_clauses_enabled(), config.reserve_hint_enabled());
+        let mut matrix = Matrix {
             quantifier_list,
             clause_set,
             clause_references,
             variable_quantification,
             quantification_order,
+            expected_result,
             config
         };
+        if matrix.config.shuffle_clauses_enabled() { matrix.shuffle_clauses(matrix.config.seed) };
+        return Ok(matrix);
+    }
+
+    /*
+    Creates a new Matrix data structure from a QBF instance in QDIMACS format held in a string.
+    */
+    pub fn from_string(instance: &str, config: Config) -> Self {
+        let lines = instance.lines().map(String::from).collect();
+        let (quantifier_list, clause_set, clause_references, variable_quantification, quantification_order, expected_result) = Matrix::create_structures_from_lines(lines, config.remove_duplicate_clauses_enabled(), config.reserve_hint_enabled());
+        let mut matrix = Matrix {
+            quantifier_list,
+            clause_set,
+            clause_references,
+            variable_quantification,
+            quantification_order,
+            expected_result,
+            config
+        };
+        if matrix.config.shuffle_clauses_enabled() { matrix.shuffle_clauses(matrix.config.seed) };
+        return matrix;
+    }
+
+    /*
+    A function to merge several QBF instances in QDIMACS format into the lines of a single instance, whose clauses are the
+    conjunction of the clauses of each instance. The quantifier blocks of later instances only declare the variables that
+    aren't already quantified, so their blocks are appended to the end of the prefix. Comment and problem lines are 
+    dropped as they describe the separate instances.
+
+    Returns the merged lines, or an error if an instance can't be read or a variable is quantified as existential in one
+    instance and universal in another.
+    */
+    pub fn merge_instance_lines(filenames: &[String]) -> Result<Vec<String>, String> {
+        let mut quantification: HashMap<i32, String> = HashMap::new();
+        let mut merged_lines = Vec::new();
+        for filename in filenames {
+            let lines = Matrix::read_lines(filename).map_err(|error| format!("Unable to read {}: {}", filename, error))?;
+            for line in lines.map_while(Result::ok) {
+                let vec = line.split_whitespace().collect::<Vec<&str>>();
+                if vec.is_empty() { continue };
+                if vec[0].eq("%") { break };
+                if vec[0].eq("c") || vec[0].eq("p") { continue };
+                if !vec[0].eq("e") && !vec[0].eq("a") {
+                    merged_lines.push(line);
+                    continue;
+                }
+                let mut new_variables = Vec::new();
+                for &variable in vec.iter().skip(1).filter(|&&variable| !variable.eq("0")) {
+                    let variable: i32 = variable.parse::<i32>().map_err(|_| format!("Invalid variable {} in {}", variable, filename))?.abs();
+                    let q_type = quantification.get(&variable);
+                    if q_type.is_none() {
+                        quantification.insert(variable, vec[0].to_string());
+                        new_variables.push(variable.to_string());
+                    } else if !q_type.unwrap().eq(vec[0]) {
+                        return Err(format!("Variable {} is quantified inconsistently in {}", variable, filename));
+                    }
+                }
+                if !new_variables.is_empty() {
+                    merged_lines.push(format!("{} {} 0", vec[0], new_variables.join(" ")));
+                }
+            }
+        }
+        return Ok(merged_lines);
     }
 
     /*
     Parses a QBF instance stored in QDIMACS format and generates the data structures required for creating a Matrix.
     */
-    pub fn create_structures(filename: String) -> (Vec<Quantifier>, ClauseSet, MultiMap<i32, i32>, MultiMap<i32, Variable>, QuantificationOrder) {
+    pub fn create_structures(filename: String, remove_duplicate_clauses: bool, reserve_hint: bool) -> (Vec<Quantifier>, ClauseSet, MultiMap<i32, i32>, MultiMap<i32, Variable>, QuantificationOrder, Option<bool>) {
+        let lines = match Matrix::read_lines(filename) {
+            Ok(lines) => lines.map_while(Result::ok).collect(),
+            Err(_) => Vec::new(),
+        };
+        return Matrix::create_structures_from_lines(lines, remove_duplicate_clauses, reserve_hint);
+    }
+
+    /*
+    Generates the data structures required for creating a Matrix from the lines of a QBF instance in QDIMACS format.
+
+    A "c result SAT" or "c result UNSAT" comment line is read as the expected result of the instance. Blank lines are 
+    skipped, and reading stops at a "%" line. A signed variable in a quantifier line is read as the variable itself. The 
+    quantifier prefix is read in a first pass, so clause literals are classified correctly wherever the clause lines 
+    appear. When removing duplicate clauses, a hard clause that exactly repeats an earlier clause is skipped. When 
+    reserve_hint is set, the clause database, clause references and variable quantification are created with the 
+    capacity for the number of variables and clauses given in the header.
+    */
+    pub fn create_structures_from_lines(lines: Vec<String>, remove_duplicate_clauses: bool, reserve_hint: bool) -> (Vec<Quantifier>, ClauseSet, MultiMap<i32, i32>, MultiMap<i32, Variable>, QuantificationOrder, Option<bool>) {
         let mut quantifier_list = Vec::new();
         let mut clause_list = Vec::new();
         let mut clause_references = MultiMap::new();
@@ -470,71 +1592,120 @@ impl Matrix {
         let mut previous_quantifier = String::from("");
         let mut quantification_level = 0;
         let mut clause_count = 0;
-        if let Ok(lines) = Matrix::read_lines(filename) {
-            for line in lines {
-                if let Ok(l) = line {
-                    let split = l.split_whitespace();
-                    let mut vec = split.clone().collect::<Vec<&str>>();
-                    if vec.is_empty() { break };
-                    if vec[0].eq("c") || vec[0].eq("p") {
-                        continue;
-                    } else if vec[0].eq("e") || vec[0].eq("a") {
-                        let quantifier_type = vec[0];
-                        let quantifier = if quantifier_type.eq("e") {QuantifierType::Existential} else {QuantifierType::Universal};
-                        vec.pop();
-                        if !quantifier_type.eq(previous_quantifier.as_str()) {
-                            previous_quantifier = String::from(quantifier_type);
-                            quantification_level += 1;
-                        }
-                        for &literal in vec.iter().skip(1) { // Skip the quantification element
-                            let literal = literal.parse().unwrap();
-                            quantifier_list.push(Quantifier {
-                                q_type: quantifier.clone(),
-                                q_level: quantification_level,
-                                literal,
-                            });
-                            if quantifier_type.eq("e") {
-                                existential_literal_order.push(literal);
-                            } else {
-                                universal_literal_order.push(literal);
-                            }
-                            variable_quantification.insert(literal, Variable {
-                                q_type: quantifier.clone(),
-                                q_level: quantification_level,
-                                value: literal,
-                            })
-                        }
+        let mut is_weighted = false;
+        let mut top_weight = None;
+        let mut expected_result = None;
+        let mut clause_lines = Vec::new();
+        let mut seen_clauses = HashSet::new();
+        for l in lines {
+            let split = l.split_whitespace();
+            let mut vec = split.clone().collect::<Vec<&str>>();
+            if vec.is_empty() { continue };
+            // A "%" line is the legacy SATLIB terminator, so anything after it is not part of the instance.
+            if vec[0].eq("%") { break };
+            if vec[0].eq("c") {
+                if vec.len() > 2 && vec[1].eq("result") {
+                    expected_result = match vec[2].to_uppercase().as_str() {
+                        "SAT" => Some(true),
+                        "UNSAT" => Some(false),
+                        _ => expected_result,
+                    };
+                }
+                continue;
+            } else if vec[0].eq("p") {
+                // Weighted instances use the "p wcnf <variables> <clauses> <top>" header.
+                if vec.len() > 1 && vec[1].eq("wcnf") {
+                    is_weighted = true;
+                    top_weight = vec.get(4).map(|weight| weight.parse::<u32>().unwrap());
+                }
+                // The header comes before the prefix and clauses, so the structures are still empty when they're replaced.
+                if reserve_hint {
+                    let variable_count = vec.get(2).and_then(|count| count.parse::<usize>().ok()).unwrap_or(0);
+                    let declared_clause_count = vec.get(3).and_then(|count| count.parse::<usize>().ok()).unwrap_or(0);
+                    quantifier_list.reserve(variable_count);
+                    clause_list.reserve(declared_clause_count);
+                    clause_lines.reserve(declared_clause_count);
+                    clause_references = MultiMap::with_capacity(2 * variable_count);
+                    variable_quantification = MultiMap::with_capacity(variable_count);
+                }
+                continue;
+            } else if vec[0].eq("e") || vec[0].eq("a") {
+                let quantifier_type = vec[0];
+                let quantifier = if quantifier_type.eq("e") {QuantifierType::Existential} else {QuantifierType::Universal};
+                vec.pop();
+                if !quantifier_type.eq(previous_quantifier.as_str()) {
+                    previous_quantifier = String::from(quantifier_type);
+                    quantification_level += 1;
+                }
+                for &literal in vec.iter().skip(1) { // Skip the quantification element
+                    // Quantifier lines list variables, so a sign some instances put on a variable is dropped.
+                    let literal = literal.parse::<i32>().unwrap().abs();
+                    quantifier_list.push(Quantifier {
+                        q_type: quantifier.clone(),
+                        q_level: quantification_level,
+                        literal,
+                        position: quantifier_list.len(),
+                    });
+                    if quantifier_type.eq("e") {
+                        existential_literal_order.push(literal);
                     } else {
-                        vec.pop();
-                        let mut a_literals = Vec::new();
-                        let mut e_literals = Vec::new();
-                        for literal in vec {
-                            let literal: i32 = literal.parse().unwrap();
-                            let negative_literal = -literal;
-                            if universal_literal_order.contains(&literal) || universal_literal_order.contains(&negative_literal) {
-                                a_literals.push(literal);
-                            } else {
-                                e_literals.push(literal);
-                            }
-                            clause_references.insert(literal, clause_count);
-                        }
-
-                        a_literals = sort_literals_order(&universal_literal_order, a_literals);
-                        e_literals = sort_literals_order(&existential_literal_order, e_literals);
-
-                        clause_list.push(Clause {
-                            e_literals,
-                            a_literals,
-                            is_removed: false,
-                        });
-                        clause_count += 1;
+                        universal_literal_order.push(literal);
                     }
+                    variable_quantification.insert(literal, Variable {
+                        q_type: quantifier.clone(),
+                        q_level: quantification_level,
+                        value: literal,
+                    })
+                }
+            } else {
+                clause_lines.push(l);
+            }
+        }
+        // Clauses are classified once the whole prefix has been read, so a clause line may appear before the prefix is complete.
+        for l in clause_lines {
+            let mut vec = l.split_whitespace().collect::<Vec<&str>>();
+            // Each clause in a weighted instance starts with its weight. Clauses weighted at least top are hard.
+            let mut weight = None;
+            if is_weighted {
+                let clause_weight: u32 = vec.remove(0).parse().unwrap();
+                if top_weight.is_none() || clause_weight < top_weight.unwrap() {
+                    weight = Some(clause_weight);
+                }
+            }
+            let ClauseLiterals(literals) = vec.join(" ").parse().unwrap();
+            let mut a_literals = Vec::new();
+            let mut e_literals = Vec::new();
+            for literal in &literals {
+                let negative_literal = -literal;
+                if universal_literal_order.contains(literal) || universal_literal_order.contains(&negative_literal) {
+                    a_literals.push(*literal);
+                } else {
+                    e_literals.push(*literal);
                 }
             }
+
+            a_literals = sort_literals_order(&universal_literal_order, a_literals);
+            e_literals = sort_literals_order(&existential_literal_order, e_literals);
+
+            let clause = Clause {
+                e_literals,
+                a_literals,
+                is_removed: false,
+                weight,
+            };
+            // Repeated soft clauses each contribute their weight, so only hard clauses are deduplicated.
+            if remove_duplicate_clauses && clause.weight.is_none() && !seen_clauses.insert(clause.clone()) {
+                continue;
+            }
+            for literal in literals {
+                clause_references.insert(literal, clause_count);
+            }
+            clause_list.push(clause);
+            clause_count += 1;
         }
         let clause_set = ClauseSet { clause_list, clause_count };
-        let quantification_order = QuantificationOrder { existential_literal_order, universal_literal_order };
-        return (quantifier_list, clause_set, clause_references, variable_quantification, quantification_order)
+        let quantification_order = QuantificationOrder::new(existential_literal_order, universal_literal_order);
+        return (quantifier_list, clause_set, clause_references, variable_quantification, quantification_order, expected_result)
     }
 
     /*
@@ -546,6 +1717,16 @@ impl Matrix {
         Ok(io::BufReader::new(file).lines())
     }
 
+    /*
+    A function to get a copy of the quantifier prefix in canonical order, sorted by quantification level and then by
+    the position each literal appeared in the original quantifier prefix.
+    */
+    pub fn quantifiers_sorted(&self) -> Vec<Quantifier> {
+        let mut quantifiers = self.quantifier_list.clone();
+        quantifiers.sort_by_key(Quantifier::order_key);
+        return quantifiers;
+    }
+
     /*
     A function that will return true if the current state is either satisfiable (true) or unsatisfiable (false).
     */
@@ -556,6 +1737,453 @@ impl Matrix {
             return false;
         }
     }
+
+    /*
+    A function to calculate the alternation depth of the quantifier prefix, the number of times the quantifier type 
+    changes going through the prefix in order. Quantification levels number the quantifier blocks from 1, so for a 
+    parsed prefix this is one less than the maximum q_level rather than equal to it.
+
+    Returns the alternation depth, or 0 for an empty prefix.
+    */
+    pub fn alternation_depth(&self) -> i32 {
+        let quantifiers = self.quantifiers_sorted();
+        let alternations = quantifiers.windows(2).filter(|pair| pair[0].q_type != pair[1].q_type).count();
+        return alternations as i32;
+    }
+
+    /*
+    A function to get the expected result of the instance given by its "c result" comment.
+
+    Returns Some(true) if the instance is expected to be satisfiable, Some(false) if unsatisfiable, or None if unknown.
+    */
+    pub fn expected_result(&self) -> Option<bool> {
+        return self.expected_result;
+    }
+
+    /*
+    A function to check whether the result found by a solver matches the expected result of the instance.
+
+    Returns false only if the instance has an expected result that differs from the given result.
+    */
+    pub fn matches_expected_result(&self, is_satisfiable: bool) -> bool {
+        return self.expected_result.map_or(true, |expected_result| expected_result == is_satisfiable);
+    }
+
+    /*
+    A function to prepare the initial assignments from the config to be propagated before the search. The assigned 
+    variables are removed from the quantifier prefix so that fixing a universal variable restricts the instance rather 
+    than being treated as a contradiction. Literals whose variable isn't quantified in the prefix are ignored.
+
+    Returns the literals to propagate.
+    */
+    pub fn take_initial_assignments(&mut self) -> Vec<i32> {
+        let initial_assignments: Vec<i32> = self.config.initial_assignments.iter()
+                                                .filter(|literal| self.variable_quantification.contains_key(&literal.abs()))
+                                                .copied()
+                                                .collect();
+        self.quantifier_list.retain(|quantifier| !initial_assignments.iter().any(|literal| literal.abs() == quantifier.literal));
+        return initial_assignments;
+    }
+
+    /*
+    A function to check whether every variable left in the quantifier prefix is existentially quantified, so that once 
+    the prefix is exhausted of universals the remaining problem is plain SAT.
+
+    Returns false if the prefix is empty or still contains a universal variable.
+    */
+    pub fn only_existentials_remain(&self) -> bool {
+        return !self.quantifier_list.is_empty() && self.quantifier_list.iter().all(|quantifier| quantifier.is_existential());
+    }
+
+    /*
+    A function to resolve the verdict of the remaining clauses when no variable left in the quantifier prefix appears in
+    them, so no further decisions can be made.
+
+    Returns false if a clause that hasn't been removed is empty (unsatisfiable), and true otherwise (satisfiable).
+    */
+    pub fn remaining_clauses_satisfiable(&self) -> bool {
+        return !self.clause_set.active_clauses().any(|(_index, clause)| clause.is_empty());
+    }
+
+    /*
+    A function to get the quantification level of the variable of a literal.
+
+    Returns the quantification level, or None if the variable isn't quantified in the prefix.
+    */
+    pub fn level_of(&self, literal: i32) -> Option<i32> {
+        return self.variable_quantification.get(&literal.abs()).map(|variable| variable.q_level);
+    }
+
+    /*
+    A function to get the variable of a literal.
+
+    Note: Panics if the variable isn't quantified in the prefix.
+    */
+    pub fn variable_of(&self, literal: i32) -> &Variable {
+        return self.variable_quantification.get(&literal.abs()).expect("Variable quantification missing literal");
+    }
+
+    /*
+    A function to get the quantifier type of the variable of a literal.
+
+    Note: Panics if the variable isn't quantified in the prefix.
+    */
+    pub fn type_of(&self, literal: i32) -> QuantifierType {
+        return self.variable_of(literal).q_type.clone();
+    }
+
+    /*
+    A function to get the number of variables remaining in the quantifier prefix.
+    */
+    pub fn num_variables(&self) -> usize {
+        return self.quantifier_list.len();
+    }
+
+    /*
+    A function to get the number of clauses that have not been removed.
+    */
+    pub fn num_clauses(&self) -> usize {
+        return self.clause_set.active_clauses().count();
+    }
+
+    /*
+    A function to format the size of the instance - the remaining variables, clauses, and alternations in the quantifier
+    prefix.
+    */
+    pub fn format_instance_size(&self) -> String {
+        return format!("Variables: {}, Clauses: {}, Alternations: {}", self.num_variables(), self.num_clauses(), self.alternation_depth());
+    }
+
+    /*
+    A function to write the matrix to a binary cache file, so a pre-processed matrix can be reloaded with load_cache 
+    instead of being parsed and pre-processed again. The config and clause references are not written.
+
+    Returns an error if the cache can't be serialised or written.
+    */
+    pub fn save_cache(&self, path: &str) -> Result<(), String> {
+        let mut variables: Vec<Variable> = self.variable_quantification.iter().map(|(_, variable)| variable.clone()).collect();
+        variables.sort_by_key(|variable| variable.value);
+        let cache = MatrixCache {
+            quantifier_list: self.quantifier_list.clone(),
+            clause_set: self.clause_set.clone(),
+            variables,
+            quantification_order: self.quantification_order.clone(),
+            expected_result: self.expected_result,
+        };
+        let bytes = bincode::serialize(&cache).map_err(|error| format!("Unable to serialise cache: {}", error))?;
+        return fs::write(path, bytes).map_err(|error| format!("Unable to write cache file {}: {}", path, error));
+    }
+
+    /*
+    A function to load a matrix written by save_cache, with the given config. The clause references are rebuilt from 
+    the clauses that have not been removed.
+
+    Returns the Matrix, or an error if the cache file can't be read or is invalid.
+    */
+    pub fn load_cache(path: &str, config: Config) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|error| format!("Unable to read cache file {}: {}", path, error))?;
+        let cache: MatrixCache = bincode::deserialize(&bytes).map_err(|error| format!("Invalid cache file {}: {}", path, error))?;
+        let mut clause_references = MultiMap::new();
+        for (index, clause) in cache.clause_set.clause_list.iter().enumerate() {
+            if clause.is_removed { continue; }
+            for literal in clause.literals() {
+                clause_references.insert(literal, index as i32);
+            }
+        }
+        let mut variable_quantification = MultiMap::new();
+        for variable in cache.variables {
+            variable_quantification.insert(variable.value, variable);
+        }
+        return Ok(Matrix {
+            quantifier_list: cache.quantifier_list,
+            clause_set: cache.clause_set,
+            clause_references,
+            variable_quantification,
+            quantification_order: cache.quantification_order,
+            expected_result: cache.expected_result,
+            config
+        });
+    }
+
+    /*
+    A function to serialise the matrix in QDIMACS format. Quantifiers are written in canonical prefix order, with one 
+    block per alternation, so levels of the same type left adjacent by removed quantifiers share a block. Only clauses 
+    that have not been removed are written. If the matrix contains the
+    empty clause, a single empty clause is written instead. Clause weights are not written.
+
+    Returns the QDIMACS string.
+    */
+    pub fn to_qdimacs(&self) -> String {
+        let variable_count = self.variable_quantification.keys().max().copied().unwrap_or(0);
+        let mut clause_lines = Vec::new();
+        if self.clause_set.contains_empty_clause() {
+            clause_lines.push(String::from("0"));
+        } else {
+            for clause in &self.clause_set.clause_list {
+                if clause.is_removed { continue };
+                let mut line = String::new();
+                for literal in clause.literals() {
+                    line += &format!("{} ", literal);
+                }
+                clause_lines.push(line + "0");
+            }
+        }
+
+        let mut qdimacs = format!("p cnf {} {}\n", variable_count, clause_lines.len());
+        let mut current_type = None;
+        for quantifier in self.quantifiers_sorted() {
+            if current_type != Some(quantifier.q_type.clone()) {
+                if !current_type.is_none() { qdimacs += "0\n" };
+                qdimacs += if quantifier.is_existential() { "e " } else { "a " };
+                current_type = Some(quantifier.q_type.clone());
+            }
+            qdimacs += &format!("{} ", quantifier.literal);
+        }
+        if !current_type.is_none() { qdimacs += "0\n" };
+        for line in clause_lines {
+            qdimacs += &format!("{}\n", line);
+        }
+        return qdimacs;
+    }
+
+    /*
+    A function to permanently remove the clauses at the given indices from the clause list. The clause references are 
+    updated incrementally rather than rebuilt from every clause.
+
+    Returns the index-remapping table from each old clause index to its new index, or None if the clause was removed.
+    */
+    pub fn remove_clauses(&mut self, removed_indices: &Vec<usize>) -> Vec<Option<i32>> {
+        let removed_indices: HashSet<usize> = HashSet::from_iter(removed_indices.clone());
+        let mut index_remapping = Vec::with_capacity(self.clause_set.clause_list.len());
+        let mut new_index = 0;
+        for index in 0..self.clause_set.clause_list.len() {
+            if removed_indices.contains(&index) {
+                index_remapping.push(None);
+            } else {
+                index_remapping.push(Some(new_index));
+                new_index += 1;
+            }
+        }
+        let mut index = 0;
+        self.clause_set.clause_list.retain(|_| {
+            index += 1;
+            return !index_remapping[index - 1].is_none();
+        });
+        self.remap_clause_references(&index_remapping);
+        return index_remapping;
+    }
+
+    /*
+    A function to update the clause references using an index-remapping table from old to new clause indices. A 
+    reference is dropped if its clause was removed from the clause list, has been removed under the current assignments,
+    or no longer contains the literal. All other references are moved to the new index of their clause.
+    */
+    pub fn remap_clause_references(&mut self, index_remapping: &Vec<Option<i32>>) {
+        let clause_list = &self.clause_set.clause_list;
+        for (literal, references) in self.clause_references.iter_all_mut() {
+            references.retain_mut(|reference| {
+                let new_index = index_remapping.get(*reference as usize).copied().flatten();
+                if new_index.is_none() { return false };
+                let clause = &clause_list[new_index.unwrap() as usize];
+                if clause.is_removed || !clause.contains_literal(*literal) { return false };
+                *reference = new_index.unwrap();
+                return true;
+            });
+        }
+        // Retaining every reference drops the literals left without any references.
+        self.clause_references.retain(|_, _| true);
+    }
+
+    /*
+    A function to check that the structures of the matrix are in sync with each other: every clause reference points to a
+    clause that hasn't been removed and contains the literal, the clause count matches the number of clauses that 
+    haven't been removed unless it is set to -1 for the empty clause, and every variable in the quantifier prefix has its
+    quantification stored.
+
+    Returns an error describing the first broken invariant found.
+    */
+    pub fn verify_structural_invariants(&self) -> Result<(), String> {
+        for (literal, references) in self.clause_references.iter_all() {
+            for reference in references {
+                let clause = self.clause_set.clause_list.get(*reference as usize);
+                if clause.is_none() {
+                    return Err(format!("Literal {} references clause {}, which is out of bounds", literal, reference));
+                }
+                if clause.unwrap().is_removed {
+                    return Err(format!("Literal {} references clause {}, which has been removed", literal, reference));
+                }
+                if !clause.unwrap().contains_literal(*literal) {
+                    return Err(format!("Literal {} references clause {}, which doesn't contain it", literal, reference));
+                }
+            }
+        }
+        let active_clause_count = self.clause_set.active_clauses().count() as i32;
+        if !self.clause_set.contains_empty_clause() && self.clause_set.clause_count != active_clause_count {
+            return Err(format!("Clause count is {}, but {} clauses haven't been removed", self.clause_set.clause_count, active_clause_count));
+        }
+        for quantifier in &self.quantifier_list {
+            if !self.variable_quantification.contains_key(&quantifier.literal) {
+                return Err(format!("Variable {} is in the quantifier prefix but its quantification isn't stored", quantifier.literal));
+            }
+        }
+        return Ok(());
+    }
+
+    /*
+    A function to panic with the broken invariant if the structures of the matrix are out of sync after the given 
+    operation. The check visits every clause reference, so it is only made in debug builds.
+    */
+    pub fn debug_verify_structural_invariants(&self, operation: &str) {
+        if cfg!(debug_assertions) {
+            match self.verify_structural_invariants() {
+                Ok(()) => {},
+                Err(error) => panic!("Structural invariant broken after {}: {}", operation, error),
+            }
+        }
+    }
+
+    /*
+    A function to randomly permute the clause list with a Fisher-Yates shuffle driven by the given seed. The clause 
+    references are rebuilt for the new clause indices.
+    */
+    pub fn shuffle_clauses(&mut self, seed: u64) {
+        let mut state = seed.wrapping_mul(0x9E3779B97F4A7C15) | 1;
+        for index in (1..self.clause_set.clause_list.len()).rev() {
+            let swap_index = next_random(&mut state, index as u64 + 1) as usize;
+            self.clause_set.clause_list.swap(index, swap_index);
+        }
+        let mut clause_references = MultiMap::new();
+        for (index, clause) in self.clause_set.clause_list.iter().enumerate() {
+            if clause.is_removed { continue; }
+            for literal in clause.literals() {
+                clause_references.insert(literal, index as i32);
+            }
+        }
+        self.clause_references = clause_references;
+    }
+
+    /*
+    A function to add a new clause made up of the given literals to the matrix after it has been constructed. The clause 
+    list, clause count and clause references are updated.
+
+    Returns the index of the new clause, or None if a literal's variable isn't quantified in the prefix.
+    */
+    pub fn add_clause(&mut self, literals: &[i32]) -> Option<usize> {
+        if literals.iter().any(|literal| !self.variable_quantification.contains_key(&literal.abs())) {
+            return None;
+        }
+        let clause = Clause::new(literals, &self.variable_quantification, &self.quantification_order);
+        self.clause_set.clause_list.push(clause.clone());
+        self.clause_set.clause_count += 1;
+        let clause_index = self.clause_set.clause_list.len() - 1;
+        for literal in clause.get_literal_list() {
+            self.clause_references.insert(literal, clause_index as i32);
+        }
+        return Some(clause_index);
+    }
+
+    /*
+    A function to renumber the remaining variables to the dense range 1..=n, keeping their relative order. A variable
+    remains if it is still in the quantifier prefix or appears in a clause that hasn't been removed. Removed clauses are 
+    permanently removed from the clause list, and the quantifier prefix, clause references, variable quantification and 
+    quantification order are rewritten with the new variables.
+
+    Returns the mapping from each remaining old variable to its new variable.
+    */
+    pub fn compact(&mut self) -> HashMap<i32, i32> {
+        let removed_indices = self.clause_set.clause_list.iter().enumerate()
+                                .filter(|(_, clause)| clause.is_removed)
+                                .map(|(index, _)| index)
+                                .collect();
+        self.remove_clauses(&removed_indices);
+        let mut remaining_variables: Vec<i32> = self.quantifier_list.iter().map(|quantifier| quantifier.literal).collect();
+        for clause in &self.clause_set.clause_list {
+            remaining_variables.extend(clause.literals().iter().map(|literal| literal.abs()));
+        }
+        remaining_variables.sort();
+        remaining_variables.dedup();
+        let mut variable_mapping = HashMap::new();
+        for (index, variable) in remaining_variables.iter().enumerate() {
+            variable_mapping.insert(*variable, index as i32 + 1);
+        }
+        let rename = |literal: &i32| variable_mapping[&literal.abs()] * literal.signum();
+
+        for quantifier in &mut self.quantifier_list {
+            quantifier.literal = rename(&quantifier.literal);
+        }
+        for clause in &mut self.clause_set.clause_list {
+            clause.e_literals = clause.e_literals.iter().map(rename).collect();
+            clause.a_literals = clause.a_literals.iter().map(rename).collect();
+        }
+        let mut clause_references = MultiMap::new();
+        for (literal, references) in self.clause_references.iter_all() {
+            clause_references.insert_many(rename(literal), references.clone());
+        }
+        self.clause_references = clause_references;
+        let mut variable_quantification = MultiMap::new();
+        for variable in &remaining_variables {
+            let mut quantification = self.variable_quantification.get(variable).expect("Variable quantification missing literal").clone();
+            quantification.value = variable_mapping[variable];
+            variable_quantification.insert(variable_mapping[variable], quantification);
+        }
+        self.variable_quantification = variable_quantification;
+        let order = &mut self.quantification_order;
+        order.existential_literal_order = order.existential_literal_order.iter().filter(|literal| variable_mapping.contains_key(literal)).map(rename).collect();
+        order.universal_literal_order = order.universal_literal_order.iter().filter(|literal| variable_mapping.contains_key(literal)).map(rename).collect();
+        order.rebuild_literal_positions();
+        return variable_mapping;
+    }
+
+    /*
+    A function to get the quantification type and level of each quantified variable, sorted by variable.
+    */
+    fn quantification_signature(&self) -> Vec<(i32, QuantifierType, i32)> {
+        let mut signature: Vec<(i32, QuantifierType, i32)> = self.variable_quantification.iter()
+                                                                .map(|(variable, quantification)| (*variable, quantification.q_type.clone(), quantification.q_level))
+                                                                .collect();
+        signature.sort_by_key(|(variable, _q_type, _q_level)| *variable);
+        return signature;
+    }
+}
+
+impl PartialEq for Matrix {
+    /*
+    Matrices are equal if they have the same quantifier prefix, the same clauses that have not been removed in the same 
+    order, and the same quantification of each variable. Positions in the original prefix aren't compared, and neither 
+    are the clause references as they are derived from the clauses. The expected result and configuration are ignored.
+    */
+    fn eq(&self, other: &Self) -> bool {
+        let prefix = |matrix: &Matrix| -> Vec<(QuantifierType, i32, i32)> {
+            return matrix.quantifiers_sorted().into_iter().map(|quantifier| (quantifier.q_type, quantifier.literal, quantifier.q_level)).collect();
+        };
+        let clauses = |matrix: &Matrix| -> Vec<Clause> {
+            return matrix.clause_set.clause_list.iter().filter(|clause| !clause.is_removed).cloned().collect();
+        };
+        return prefix(self) == prefix(other)
+                && self.clause_set.contains_empty_clause() == other.clause_set.contains_empty_clause()
+                && clauses(self) == clauses(other)
+                && self.quantification_signature() == other.quantification_signature();
+    }
+}
+
+/*
+An enum to store an event that occurred during the CDCL search, recorded when RecordEvents is enabled.
+
+Decision => A literal was decided at the given decision level.
+Conflict => A conflict occurred at the given decision level. The conflict clause is empty if the conflict was caused 
+            directly by a universal literal.
+Learned => A clause was learned by conflict analysis, with the level to backtrack to. The empty clause with backtrack 
+           level -1 means the instance is unsatisfiable.
+Backjump => The search backjumped to the given decision level.
+Restart => A restart was performed at the given decision level.
+*/
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub enum SearchEvent {
+    Decision { literal: i32, decision_level: i32 },
+    Conflict { decision_level: i32, conflict_clause: Vec<i32> },
+    Learned { learned_clause: Vec<i32>, backtrack_level: i32 },
+    Backjump { decision_level: i32 },
+    Restart { decision_level: i32 },
 }
 
 /*
@@ -565,10 +2193,32 @@ with additional ones unique for CDCL.
 - decision_level stores the current decision level the matrix is at in the CDCL procedure.
 - conflict_clause stores the clause which caused a given conflict. It's empty if it's not applicable.
 - original_clause_list contains the clause_list prior to any modifications.
+- parsed_clause_list contains the clauses as parsed, before universal reduction, pre-processing, or pre-resolution, 
+  which satisfying assignments are verified against.
 - trail stores a list of assignments, decisions and implications, in chronological order.
 - assignments stores a hashmap of assignments that have been made throughout the CDCL procedure.
 - learned_clause_refs stores a list of clause index's which point to which clauses have been learnt.
 - restart_data stores the RestartData structure for performing restarts.
+- timeout_trail stores a copy of the trail at the point the procedure timed out, when debugging is enabled.
+- sat_assignments stores a copy of the assignments at the most recent satisfiable assignment, when verification or 
+  certificate output is enabled.
+- eliminated_variables stores the variables removed by variable elimination during pre-processing with the clauses they 
+  were eliminated from, in the order they were eliminated.
+- fixed_literals stores the literals fixed permanently during pre-processing by unit propagation and pure literal deletion, 
+  which are not recorded in the trail.
+- repeated_conflicts stores the number of consecutive conflicts at repeated_conflict_level that learned 
+  last_learned_clause again.
+- last_learned_clause stores the clause most recently learned at repeated_conflict_level.
+- events stores the search events in the order they occurred, when recording events is enabled.
+- first_learned_clause stores the literals, backtrack level, and whether it is asserting of the first non-empty clause 
+  learned by conflict analysis, when dumping the first learned clause is enabled.
+- learned_clause_history stores the literals of every clause learned or loaded, including those since removed from the 
+  clause database, when warm restarts are enabled.
+- loaded_clause_count stores the number of clauses loaded from a learned clause file by a warm restart.
+- implication_graph stores the DOT description of the implication graph at the first conflict, when dumping the 
+  implication graph is enabled.
+- qrat_proof stores the clauses learned, reduced, and deleted in the order they occurred, when QRAT proof output is 
+  enabled.
 */
 #[derive(Clone)]
 pub struct CDCLMatrix {
@@ -576,10 +2226,24 @@ pub struct CDCLMatrix {
     pub decision_level: i32,
     pub conflict_clause: Option<Clause>,
     pub original_clause_list: Vec<Clause>,
-    pub trail: Vec<Assignment>,
+    pub parsed_clause_list: Vec<Clause>,
+    pub trail: Trail,
     pub assignments: HashMap<i32, Assignment>,
     pub learned_clause_refs: Vec<i32>,
     pub restart_data: RestartData,
+    pub timeout_trail: Trail,
+    pub sat_assignments: HashMap<i32, Assignment>,
+    pub fixed_literals: Vec<i32>,
+    pub eliminated_variables: Vec<(i32, Vec<Vec<i32>>)>,
+    pub repeated_conflicts: usize,
+    pub repeated_conflict_level: i32,
+    pub last_learned_clause: Option<Clause>,
+    pub events: Vec<SearchEvent>,
+    pub first_learned_clause: Option<(Vec<i32>, i32, bool)>,
+    pub learned_clause_history: Vec<Vec<i32>>,
+    pub loaded_clause_count: usize,
+    pub implication_graph: Option<String>,
+    pub qrat_proof: Vec<QratStep>,
 }
 
 impl CDCLMatrix {
@@ -588,19 +2252,72 @@ impl CDCLMatrix {
     */
     pub fn new(filename: String, config: Config) -> Self {
         let core_data = Matrix::new(filename, config);
+        return CDCLMatrix::from_matrix(core_data);
+    }
+
+    /*
+    Creates a new CDCLMatrix data structure from an existing Matrix.
+    */
+    pub fn from_matrix(core_data: Matrix) -> Self {
+        let restart_policy = core_data.config.restart_policy.clone();
         let original_clause_list = core_data.clause_set.clause_list.clone();
         return CDCLMatrix {
             core_data,
             decision_level: 0,
             conflict_clause: None,
+            parsed_clause_list: original_clause_list.clone(),
             original_clause_list,
-            trail: Vec::new(),
+            trail: Trail::new(),
             assignments: HashMap::new(),
             learned_clause_refs: Vec::new(),
-            restart_data: RestartData::new(100),
+            restart_data: RestartData::new(100, restart_policy),
+            timeout_trail: Trail::new(),
+            sat_assignments: HashMap::new(),
+            fixed_literals: Vec::new(),
+            eliminated_variables: Vec::new(),
+            repeated_conflicts: 0,
+            repeated_conflict_level: -1,
+            last_learned_clause: None,
+            events: Vec::new(),
+            first_learned_clause: None,
+            learned_clause_history: Vec::new(),
+            loaded_clause_count: 0,
+            implication_graph: None,
+            qrat_proof: Vec::new(),
         };
     }
 
+    /*
+    A function to record a search event, if recording events is enabled.
+    */
+    pub fn record_event(&mut self, event: SearchEvent) {
+        if self.core_data.config.record_events_enabled() {
+            self.events.push(event);
+        }
+    }
+
+    /*
+    A function to record a step of the QRAT proof, if QRAT proof output is enabled.
+    */
+    pub fn record_qrat_step(&mut self, step: QratStep) {
+        if self.core_data.config.qrat_enabled() {
+            self.qrat_proof.push(step);
+        }
+    }
+
+    /*
+    A function to record the universal reduction of a clause in the QRAT proof, if QRAT proof output is enabled. Each 
+    literal of the clause missing from its reduced literals is removed by its own step.
+    */
+    pub fn record_qrat_reduction(&mut self, literals: &Vec<i32>, reduced_literals: &Vec<i32>) {
+        if !self.core_data.config.qrat_enabled() { return };
+        let mut remaining_literals = literals.clone();
+        for literal in literals.iter().filter(|literal| !reduced_literals.contains(literal)) {
+            self.qrat_proof.push(QratStep::universal_reduction(&remaining_literals, *literal));
+            remaining_literals.retain(|remaining_literal| remaining_literal != literal);
+        }
+    }
+
     /*
     A function to increment the current decision level by one.
     */
@@ -610,9 +2327,12 @@ impl CDCLMatrix {
 
     /*
     A function to add a learned clause and apply the current assignments. It will update necessary structures for keeping
-    track of clause count and clause references.
+    track of clause count and clause references, and adds the length of the clause to the learned literal total. The 
+    clause is also kept in the learned clause history when warm restarts are enabled.
     */
-    pub fn add_clause(&mut self, clause: &Clause) {
+    pub fn add_clause(&mut self, clause: &Clause, statistics: &mut Statistics) {
+        statistics.add_learned_literals(clause.get_clause_length() as i32);
+        if self.core_data.config.warm_restart_enabled() { self.learned_clause_history.push(clause.literals()) };
         // Push original clause to the original clause store.
         self.original_clause_list.push(clause.clone());
 
@@ -628,6 +2348,81 @@ impl CDCLMatrix {
         self.core_data.clause_set.clause_count += 1;
     }
 
+    /*
+    A function to write every clause learned or loaded so far to a file without duplicates, one clause per line in DIMACS
+    format, so they can be reloaded with load_learned_clauses by a later solve of the same instance. The first line is a 
+    comment holding the content hash of the instance the clauses were learned on.
+
+    Returns an error if the file can't be written.
+    */
+    pub fn save_learned_clauses(&self, path: &str, instance_hash: u64) -> Result<(), String> {
+        let mut output_string = format!("c instance {:016x}\n", instance_hash);
+        let mut seen_clauses = HashSet::new();
+        for literals in &self.learned_clause_history {
+            if !seen_clauses.insert(literals) { continue };
+            for literal in literals {
+                output_string += &format!("{} ", literal);
+            }
+            output_string += "0\n";
+        }
+        return fs::write(path, output_string).map_err(|error| format!("Unable to write learned clause file {}: {}", path, error));
+    }
+
+    /*
+    A function to load the clauses written by save_learned_clauses into the clause database before solving. They are 
+    added as learned clauses, so they aren't checked when verifying an assignment and can be removed when the clause 
+    database is reduced. Clauses containing a variable that isn't quantified in the prefix are skipped. Nothing is loaded
+    unless the file was written for an instance with the given content hash, as the clauses don't hold for another one.
+
+    Returns the number of clauses loaded, or an error if the file can't be read, was written for a different instance, or
+    a clause is invalid.
+    */
+    pub fn load_learned_clauses(&mut self, path: &str, instance_hash: u64) -> Result<usize, String> {
+        let contents = fs::read_to_string(path).map_err(|error| format!("Unable to read learned clause file {}: {}", path, error))?;
+        let mut lines = contents.lines();
+        if lines.next() != Some(format!("c instance {:016x}", instance_hash).as_str()) {
+            return Err(format!("Learned clause file {} was written for a different instance, so it was not loaded", path));
+        }
+        let mut loaded_count = 0;
+        for line in lines {
+            if line.trim().is_empty() { continue };
+            let literals = line.parse::<ClauseLiterals>()?.0;
+            if literals.iter().any(|literal| !self.core_data.variable_quantification.contains_key(&literal.abs())) { continue };
+            let clause = Clause::new(&literals, &self.core_data.variable_quantification, &self.core_data.quantification_order);
+            self.original_clause_list.push(clause.clone());
+            self.core_data.clause_set.clause_list.push(clause.clone());
+            let clause_index = self.core_data.clause_set.clause_list.len() - 1;
+            self.learned_clause_refs.push(clause_index as i32);
+            for literal in clause.get_literal_list() {
+                self.core_data.clause_references.insert(literal, clause_index as i32);
+            }
+            self.core_data.clause_set.clause_count += 1;
+            self.learned_clause_history.push(literals);
+            loaded_count += 1;
+        }
+        self.loaded_clause_count += loaded_count;
+        return Ok(loaded_count);
+    }
+
+    /*
+    A function to track conflicts that make no progress, where the learned clause is the same clause last learned at the
+    current decision level. Consecutive conflicts without progress are counted until a new distinct clause is learned, or
+    a clause is learned at another decision level.
+
+    Returns the number of consecutive conflicts without progress at the current decision level.
+    */
+    pub fn record_learned_clause_progress(&mut self, clause: &Clause) -> usize {
+        let is_repeated = self.repeated_conflict_level == self.decision_level && self.last_learned_clause.as_ref() == Some(clause);
+        if is_repeated {
+            self.repeated_conflicts += 1;
+        } else {
+            self.repeated_conflicts = 0;
+            self.repeated_conflict_level = self.decision_level;
+            self.last_learned_clause = Some(clause.clone());
+        }
+        return self.repeated_conflicts;
+    }
+
     /*
     A function to apply the current assignments that have been made so far in the decision tree to a given clause.
     */
@@ -646,6 +2441,107 @@ impl CDCLMatrix {
         return new_clause;
     }
     
+    /*
+    A function to format the current state of the search for debugging. It lists the decision level, the remaining 
+    quantifier prefix in order with one line per quantifier block, the clauses that have not been removed with their 
+    existential and universal literals, the trail with the level of each assignment, and the assignment of each variable.
+
+    Returns the formatted state.
+    */
+    pub fn debug_state(&self) -> String {
+        let mut lines = vec![format!("Decision level: {}", self.decision_level), "Prefix:".to_string()];
+        let mut block_level = None;
+        for quantifier in &self.core_data.quantifier_list {
+            if block_level != Some(quantifier.q_level) {
+                let q_type = if quantifier.is_existential() { "e" } else { "a" };
+                lines.push(format!("  {}", q_type));
+                block_level = Some(quantifier.q_level);
+            }
+            lines.last_mut().unwrap().push_str(&format!(" {}", quantifier.literal));
+        }
+        lines.push("Clauses:".to_string());
+        for (index, clause) in self.core_data.clause_set.active_clauses() {
+            lines.push(format!("  {}: e {:?} a {:?}", index, clause.e_literals, clause.a_literals));
+        }
+        lines.push("Trail:".to_string());
+        lines.extend(format_trail(&self.trail).lines().map(|line| format!("  {}", line)));
+        lines.push("Assignments:".to_string());
+        let mut variables: Vec<&i32> = self.assignments.keys().collect();
+        variables.sort();
+        for variable in variables {
+            let assignment = &self.assignments[variable];
+            lines.push(format!("  {} = {} (level {})", variable, assignment.value, assignment.decision_level));
+        }
+        return lines.join("\n");
+    }
+
+    /*
+    A function to calculate the literal block distance (LBD) of a clause, the number of distinct decision levels among 
+    its assigned literals.
+
+    Returns the LBD.
+    */
+    pub fn literal_block_distance(&self, clause: &Clause) -> i32 {
+        let mut decision_levels = HashSet::new();
+        for literal in clause.literals() {
+            let assignment = self.assignments.get(&literal.abs());
+            if !assignment.is_none() {
+                decision_levels.insert(assignment.unwrap().decision_level);
+            }
+        }
+        return decision_levels.len() as i32;
+    }
+
+    /*
+    A function to find the variables that haven't been assigned in the search so far. Variables fixed during 
+    pre-processing are stored in fixed_literals rather than the assignments, so they are included.
+
+    Returns the unassigned variables in the order they appear in the quantifier prefix.
+    */
+    pub fn unassigned_variables(&self) -> Vec<i32> {
+        let quantification_order = &self.core_data.quantification_order;
+        let mut variables: Vec<i32> = quantification_order.existential_literal_order.iter().chain(quantification_order.universal_literal_order.iter())
+            .filter(|variable| !self.assignments.contains_key(variable))
+            .copied()
+            .collect();
+        // Each quantification level holds one quantifier type, so a stable sort by level keeps the prefix order.
+        variables.sort_by_key(|variable| self.core_data.variable_quantification.get(variable).unwrap().q_level);
+        return variables;
+    }
+
+    /*
+    A function to check whether a learned clause is asserting under the current assignments. None of its literals may be
+    assigned true, and exactly one literal must be left unassigned. Unassigned universal literals that can be removed by 
+    universal reduction against the unassigned existential literals are not counted.
+
+    Returns true if the clause is asserting, false otherwise.
+    */
+    pub fn is_asserting_clause(&self, clause: &Clause) -> bool {
+        return !self.asserting_literal(clause).is_none();
+    }
+
+    /*
+    A function to get the literal a clause asserts under the current assignments, i.e. its only literal that is left 
+    after removing the assigned literals and applying universal reduction, if no literal of the clause is satisfied.
+
+    Returns the asserted literal, or None if the clause is not asserting.
+    */
+    pub fn asserting_literal(&self, clause: &Clause) -> Option<i32> {
+        for literal in clause.literals() {
+            let assignment = self.assignments.get(&literal.abs());
+            if !assignment.is_none() && assignment.unwrap().value == literal {
+                return None;
+            }
+        }
+        let mut unassigned_clause = self.apply_current_assignments(clause);
+        if !unassigned_clause.e_literals.is_empty() {
+            let universal_literals = detect_universal_literal(&unassigned_clause, &self.core_data.variable_quantification);
+            unassigned_clause.remove_a_literals(universal_literals);
+        }
+        if unassigned_clause.get_clause_length() != 1 { return None };
+        return unassigned_clause.literals().first().copied();
+    }
+
     /*
     A functio that will re-add learned clauses to the clause database. This is needed when restoring cached data structures
     which don't hold newly learned clauses.
@@ -665,31 +2561,34 @@ impl CDCLMatrix {
     }
 
     /*
-    A function to reduce the clause database by 50% by applying age-based deletion.
+    A function to reduce the clause database by 50% by applying age-based deletion. A deleted clause that is satisfied 
+    under the current assignments has already been taken off the clause count, so only live clauses are counted off.
     */
     pub fn reduce_clause_database(&mut self) {
         let num_of_learned_clauses = &self.learned_clause_refs.len();
         let first_half = self.learned_clause_refs[0 .. (num_of_learned_clauses / 2)].to_vec();
         // Remove from clause_list  and remove from original clause_set
         for reference in first_half.iter().rev() {
-            self.original_clause_list.remove(*reference as usize);
-            self.core_data.clause_set.clause_list.remove(*reference as usize);
-            self.learned_clause_refs.remove(0);
-            self.core_data.clause_set.clause_count -= 1;
+            let deleted_clause = self.original_clause_list.remove(*reference as usize);
+            self.record_qrat_step(QratStep::Delete(deleted_clause.literals()));
+            if !self.core_data.clause_set.clause_list[*reference as usize].is_removed { self.core_data.clause_set.decrement_counter(); }
         }
-        self.refresh_clause_references();
+        self.learned_clause_refs.drain(0 .. first_half.len());
+        self.core_data.remove_clauses(&first_half.iter().map(|&reference| reference as usize).collect());
         for reference in self.learned_clause_refs.iter_mut() {
             *reference -= first_half.len() as i32;
         }
     }
     
     /*
-    A function to update the clause references in the clause database.
+    A function to rebuild the clause references from every clause in the clause database. Clauses that have been removed
+    under the current assignments are not referenced.
     */
     pub fn refresh_clause_references(&mut self) {
         let mut clause_references = MultiMap::new();
         for (index, clause) in self.core_data.clause_set.clause_list.iter().enumerate() {
-            for literal in clause.clone().get_literal_list() {
+            if clause.is_removed { continue; }
+            for literal in clause.literals() {
                 clause_references.insert(literal, index as i32);
             }
         }
@@ -1,19 +1,21 @@
 use std::time::Instant;
 
-use crate::{dpll::unit_propagate::unit_propagate, data_structures::{Matrix, QuantifierType, Statistics, LiteralSelection}, literal_selection::{select_literal_vss, select_literal}};
+use crate::{log_debug, dpll::unit_propagate::unit_propagate, data_structures::{Matrix, QuantifierType, Statistics, LiteralSelection, TimeoutPhase}, literal_selection::{select_literal_vss, select_literal, select_literal_dlis}, util::get_unit_literals};
 
 /*
 A struct to store the result of the DPLL procedure.
 
 SAT => Satisfiable at current decision branch.
 UNSAT => Unsatisfiable at current decision branch.
-Timeout => Instance timeout, stop running the current instance.
+Timeout => Instance timeout, stop running the current instance. Stores the phase of solving that timed out.
+DecisionLimit => The maximum number of decisions was exceeded, so the result is unknown.
 */
 #[derive(Clone, Debug, PartialEq)]
 pub enum Result {
     SAT,
     UNSAT,
-    Timeout,
+    Timeout(TimeoutPhase),
+    DecisionLimit,
 }
 
 /*
@@ -21,14 +23,22 @@ A function that will perform the David-Putnam-LogemannLoveland (DPLL) algorithm
 from the set {Pure Literal Deletion, Universal Reduction, Pre-Resolution (done prior), Pre-Process (done prior)}.
 Has one of the literal selection schemes {Ordered, Variable State Sum}.
 
-Returns SAT (satisfiable), UNSAT (unsatisfiable), or Timeout.
+Returns SAT (satisfiable), UNSAT (unsatisfiable), Timeout, or DecisionLimit.
 */
 pub fn dpll(matrix: &mut Matrix, decision_branch: Option<i32>, statistics: &mut Statistics, timer: Instant) -> Result {
-    if timer.elapsed().as_secs() > 30 { return Result::Timeout }
+    if matrix.config.timed_out(timer) { return Result::Timeout(TimeoutPhase::Search) }
+    if !decision_branch.is_none() {
+        statistics.increment_decision_count();
+        if statistics.attempt_decision_count > matrix.config.max_decisions { return Result::DecisionLimit }
+    }
 
     let new_matrix = &mut matrix.clone();
     if !decision_branch.is_none() {
         unit_propagate(new_matrix, vec![decision_branch.unwrap()], statistics);
+    } else {
+        // Unit clauses in the input are forced assignments, so propagate them before any decision even without pre-processing.
+        let unit_literals = get_unit_literals(&new_matrix.clause_set);
+        if !unit_literals.is_empty() { unit_propagate(new_matrix, unit_literals, statistics) };
     }
     if new_matrix.clause_set.contains_empty_set() {
         return Result::SAT;
@@ -36,23 +46,61 @@ pub fn dpll(matrix: &mut Matrix, decision_branch: Option<i32>, statistics: &mut
         return Result::UNSAT;
     }
 
-    let (literal, quantifier_type) = if new_matrix.config.literal_selection.eq(&LiteralSelection::Ordered) 
-                                                        {select_literal(new_matrix)} else {select_literal_vss(new_matrix)};
+    let selection = match new_matrix.config.literal_selection {
+        LiteralSelection::Ordered => select_literal(new_matrix),
+        LiteralSelection::VariableStateSum => select_literal_vss(new_matrix),
+        LiteralSelection::DLIS => select_literal_dlis(new_matrix),
+    };
+    if selection.is_none() {
+        return if new_matrix.remaining_clauses_satisfiable() { Result::SAT } else { Result::UNSAT };
+    }
+    let (literal, quantifier_type) = selection.unwrap();
 
-    let result = dpll(new_matrix, Some(literal), statistics, timer);
-    match (&result, quantifier_type) {
-        (Result::UNSAT, QuantifierType::Universal) => {
-            return result;
-        },
-        (Result::SAT, QuantifierType::Universal) | (Result::UNSAT, QuantifierType::Existential) => {
-            statistics.increment_backtrack_count();
-            return dpll(new_matrix, Some(-literal), statistics, timer);
-        },
-        (Result::SAT, QuantifierType::Existential) => {
-            return result;
-        },
-        (Result::Timeout, _) => {
+    // The sibling branch -literal is tried within this call rather than in a new stack frame.
+    let mut branch = literal;
+    loop {
+        log_debug!(new_matrix.config.verbosity, "Decision: {}", branch);
+        let result = dpll(new_matrix, Some(branch), statistics, timer);
+        match (&result, &quantifier_type) {
+            (Result::UNSAT, QuantifierType::Universal) => {
+                return result;
+            },
+            (Result::SAT, QuantifierType::Universal) | (Result::UNSAT, QuantifierType::Existential) => {
+                if branch == -literal { return result };
+                statistics.increment_backtrack_count();
+                branch = -literal;
+            },
+            (Result::SAT, QuantifierType::Existential) => {
+                return result;
+            },
+            (Result::Timeout(_), _) | (Result::DecisionLimit, _) => {
+                return result;
+            }
+        }
+    }
+}
+
+/*
+A function to perform DPLL with iterative deepening. Each attempt is stopped once it makes more decisions than the 
+current bound, and the bound starts at 1 and is doubled after every stopped attempt, up to the maximum number of 
+decisions. The decisions of every attempt are counted in the statistics, and the bound that the instance was solved at
+is stored in them.
+
+Returns SAT (satisfiable), UNSAT (unsatisfiable), Timeout, or DecisionLimit.
+*/
+pub fn iterative_deepening(matrix: &mut Matrix, statistics: &mut Statistics, timer: Instant) -> Result {
+    let max_decisions = matrix.config.max_decisions;
+    let mut bound: usize = 1;
+    loop {
+        let attempt_matrix = &mut matrix.clone();
+        attempt_matrix.config.max_decisions = bound.min(max_decisions);
+        statistics.attempt_decision_count = 0;
+        let result = dpll(attempt_matrix, None, statistics, timer);
+        if !result.eq(&Result::DecisionLimit) {
+            if !matches!(result, Result::Timeout(_)) { statistics.decision_bound = Some(bound.min(max_decisions)) };
             return result;
         }
+        if bound >= max_decisions { return result };
+        bound = bound.saturating_mul(2);
     }
-}
\ No newline at end of file
+}
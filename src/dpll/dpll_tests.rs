@@ -1,23 +1,14 @@
 #[cfg(test)]
 mod test {
-    use std::time::Instant;
+    use std::{collections::HashMap, fs, time::Instant};
 
-    use crate::{dpll::{preprocess::preprocess, dpll::{dpll, Result}}, data_structures::{Matrix, ResolutionConfig, LiteralSelection, Config, Statistics}, resolution::pre_resolution};
+    use crate::{parse::normalise_instance, dpll::{preprocess::preprocess, unit_propagate::unit_propagate, bench::run_bench_group, dpll::{dpll, iterative_deepening, Result}, solve, retry_with_fallback}, data_structures::{Matrix, LiteralSelection, Config, Statistics, QuantifierType}, resolution::pre_resolution, util::get_variable_state_sum, literal_selection::{select_literal_vss, select_literal_dlis}};
     
     fn config() -> Config {
         Config {
             literal_selection: LiteralSelection::Ordered,
-            pre_resolution: (false, ResolutionConfig {
-                min_ratio: 0.25,
-                max_ratio: 0.5,
-                max_clause_length: usize::MAX,
-                repeat_above: 3,
-                iterations: 1,
-            }),
-            pre_process: true,
-            universal_reduction: true,
-            pure_literal_deletion: true,
             restarts: false,
+            ..Config::default()
         }
     }
 
@@ -44,5 +35,278 @@ mod test {
         assert_eq!(Result::SAT, result);
     }
 
+    /*
+    Tests that a normalised instance written in QDIMACS format can be reparsed and solves to the same result as the 
+    original instance.
+    */
+    #[test]
+    fn normalised_instance_test() {
+        let filenames = vec!["./benchmarks/samples/example.qdimacs", "./test_files/asserting_learned_clause_test.qdimacs"];
+        for (index, filename) in filenames.iter().enumerate() {
+            let normalised_matrix = normalise_instance(filename.to_string(), config());
+            let normalised_filename = std::env::temp_dir().join(format!("normalised_instance_test_{}.qdimacs", index));
+            fs::write(&normalised_filename, normalised_matrix.to_qdimacs()).expect("Unable to write file");
+            let normalised_result = run_instance(normalised_filename.to_str().unwrap().to_string());
+            fs::remove_file(&normalised_filename).expect("Unable to remove file");
+            assert_eq!(run_instance(filename.to_string()), normalised_result);
+        }
+    }
+
+    /*
+    Tests that the clauses removed by unit propagation, the literals removed by universal reduction, and the variables 
+    removed by pure literal deletion are counted during pre-processing.
+    */
+    #[test]
+    fn simplification_statistics_test() {
+        let filename = "./test_files/preprocess_statistics_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let statistics = &mut Statistics::new();
+        preprocess(matrix, statistics, timer());
+        assert_eq!(true, matrix.clause_set.contains_empty_clause());
+        assert_eq!(3, statistics.unit_propagation_removed_clauses);
+        assert_eq!(2, statistics.universal_reduction_removed_literals);
+        assert_eq!(1, statistics.pure_literal_removed_variables);
+    }
+
+    /*
+    Tests that the simplification statistics are not counted by unit propagation outside of pre-processing, such as
+    during search, while the propagations themselves still are.
+    */
+    #[test]
+    fn simplification_statistics_search_test() {
+        let filename = "./test_files/preprocess_statistics_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let statistics = &mut Statistics::new();
+        unit_propagate(matrix, vec![3], statistics);
+        assert_eq!(true, matrix.clause_set.clause_count < 5);
+        assert_eq!(true, statistics.propagation_count > 0);
+        assert_eq!(0, statistics.unit_propagation_removed_clauses);
+        assert_eq!(0, statistics.universal_reduction_removed_literals);
+        assert_eq!(0, statistics.pure_literal_removed_variables);
+    }
+
+    /*
+    Tests that a clause emptied purely by universal reduction during unit propagation is detected as the empty clause,
+    and propagation stops without propagating further unit literals.
+    */
+    #[test]
+    fn universal_reduction_empty_clause_test() {
+        let filename = "./test_files/universal_reduction_propagation_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        unit_propagate(matrix, vec![-3], &mut Statistics::new());
+        assert_eq!(true, matrix.clause_set.contains_empty_clause());
+        assert_eq!(vec![1, 3], matrix.clause_set.clause_list[1].e_literals);
+    }
+
+    /*
+    Tests that the benchmark results file is written to the configured output directory, creating the directory.
+    */
+    #[test]
+    fn bench_output_directory_test() {
+        let output_dir = std::env::temp_dir().join("bench_output_directory_test");
+        let _ = fs::remove_dir_all(&output_dir);
+        let mut output_config = config();
+        output_config.output_dir = output_dir.to_str().unwrap().to_string();
+        run_bench_group("./benchmarks/samples".to_string(), output_config, "bench_output_directory_test", false);
+        let output = fs::read_to_string(output_dir.join("output-bench_output_directory_test")).expect("Results file should be written");
+        fs::remove_dir_all(&output_dir).expect("Unable to remove directory");
+        assert_eq!(true, output.starts_with("--- DPLL ---"));
+        assert_eq!(true, output.contains("Total: 1, Sat: 1, Unsat: 0, Timeout: 0"));
+        assert_eq!(false, output.contains("Clause Lengths"));
+    }
+
+    /*
+    Tests that running a benchmark on a missing directory stops without writing a results file.
+    */
+    #[test]
+    fn bench_missing_directory_test() {
+        let output_dir = std::env::temp_dir().join("bench_missing_directory_test");
+        let _ = fs::remove_dir_all(&output_dir);
+        let mut output_config = config();
+        output_config.output_dir = output_dir.to_str().unwrap().to_string();
+        run_bench_group("./benchmarks/missing".to_string(), output_config, "bench_missing_directory_test", false);
+        assert_eq!(false, output_dir.join("output-bench_missing_directory_test").exists());
+    }
+
+    /*
+    Tests that a benchmark directory containing a text file and a subdirectory alongside an instance only solves the 
+    instance.
+    */
+    #[test]
+    fn bench_mixed_directory_test() {
+        let benchmark_dir = std::env::temp_dir().join("bench_mixed_directory_test");
+        let _ = fs::remove_dir_all(&benchmark_dir);
+        fs::create_dir_all(benchmark_dir.join("nested")).expect("Unable to create directory");
+        fs::write(benchmark_dir.join("notes.txt"), "Not an instance").unwrap();
+        fs::copy("./benchmarks/samples/example.qdimacs", benchmark_dir.join("example.qdimacs")).unwrap();
+        fs::copy("./benchmarks/samples/example.qdimacs", benchmark_dir.join("nested").join("nested.qdimacs")).unwrap();
+        let mut output_config = config();
+        output_config.output_dir = benchmark_dir.join("output").to_str().unwrap().to_string();
+        run_bench_group(benchmark_dir.to_str().unwrap().to_string(), output_config, "bench_mixed_directory_test", false);
+        let output = fs::read_to_string(benchmark_dir.join("output").join("output-bench_mixed_directory_test")).expect("Results file should be written");
+        fs::remove_dir_all(&benchmark_dir).expect("Unable to remove directory");
+        assert_eq!(true, output.contains("Total: 1, Sat: 1, Unsat: 0, Timeout: 0"));
+        assert_eq!(true, output.contains("Instance: example.qdimacs"));
+    }
+
+    /*
+    Tests that the benchmark results file only contains the clause length histogram of each instance when the verbose 
+    benchmark option is enabled.
+    */
+    #[test]
+    fn verbose_benchmark_test() {
+        let output_dir = std::env::temp_dir().join("verbose_benchmark_test");
+        let _ = fs::remove_dir_all(&output_dir);
+        let mut output_config = config();
+        output_config.output_dir = output_dir.to_str().unwrap().to_string();
+        output_config.verbose_benchmark = true;
+        run_bench_group("./benchmarks/samples".to_string(), output_config, "verbose_benchmark_test", false);
+        let output = fs::read_to_string(output_dir.join("output-verbose_benchmark_test")).expect("Results file should be written");
+        fs::remove_dir_all(&output_dir).expect("Unable to remove directory");
+        assert_eq!(true, output.lines().last().unwrap().starts_with("Instance: example.qdimacs"));
+        assert_eq!(true, output.lines().last().unwrap().ends_with("-- Clause Lengths: [3: 4]"));
+    }
+
+    /*
+    Tests that the variable state sum of each variable matches the number of occurrences in the remaining clauses after 
+    each step of a propagation sequence.
+    */
+    #[test]
+    fn variable_state_sum_propagation_test() {
+        let filename = "./test_files/get_variable_state_sum_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let statistics = &mut Statistics::new();
+        for unit_literal in vec![None, Some(-2), Some(-5), Some(3)] {
+            if !unit_literal.is_none() { unit_propagate(matrix, vec![unit_literal.unwrap()], statistics) };
+            for variable in 1..=7 {
+                let (pos_count, neg_count) = matrix.clause_set.clause_list.iter()
+                                                .filter(|clause| !clause.is_removed)
+                                                .fold((0, 0), |(pos_count, neg_count), clause| {
+                                                    (pos_count + clause.contains_literal(variable) as i32, neg_count + clause.contains_literal(-variable) as i32)
+                                                });
+                assert_eq!((pos_count + neg_count, neg_count < pos_count), get_variable_state_sum(&matrix.clause_references, variable));
+            }
+        }
+    }
+
+    /*
+    Tests that DLIS selection scores each polarity separately on the clauses left after propagation. Once propagating 1
+    removes two clauses containing 2, the literal 3 appears most often, whereas VSS still prefers variable 2 as both of
+    its polarities are summed.
+    */
+    #[test]
+    fn dlis_selection_test() {
+        let filename = "./test_files/dlis_selection_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        assert_eq!(Some((2, QuantifierType::Existential)), select_literal_dlis(&mut matrix.clone()));
+        unit_propagate(matrix, vec![1], &mut Statistics::new());
+        assert_eq!(Some((3, QuantifierType::Existential)), select_literal_dlis(&mut matrix.clone()));
+        assert_eq!(Some((-2, QuantifierType::Existential)), select_literal_vss(matrix));
+    }
+
+    /*
+    Tests that pre-processing stops after the maximum number of iterations, leaving the unit clause found by universal 
+    reduction in the first round unpropagated, whilst unbounded pre-processing reaches the fixpoint and solves the instance.
+    */
+    #[test]
+    fn max_preprocess_iterations_test() {
+        let filename = "./test_files/max_preprocess_iterations_test.qdimacs".to_string();
+        for (max_preprocess_iterations, expected_clause_count) in [(0, 3), (1, 3), (usize::MAX, 0)] {
+            let mut bounded_config = config();
+            bounded_config.max_preprocess_iterations = max_preprocess_iterations;
+            let matrix = &mut Matrix::new(filename.clone(), bounded_config);
+            preprocess(matrix, &mut Statistics::new(), timer());
+            assert_eq!(expected_clause_count, matrix.clause_set.clause_count);
+        }
+    }
+
+    /*
+    Tests that unit clauses in the input are propagated before the first decision when pre-processing is disabled, so 
+    the first decision isn't wasted on the opposite literal.
+    */
+    #[test]
+    fn input_unit_clause_test() {
+        let filename = "./test_files/input_unit_clause_test.qdimacs".to_string();
+        let mut unit_config = config();
+        unit_config.pre_process = false;
+        let matrix = &mut Matrix::new(filename, unit_config);
+        let statistics = &mut Statistics::new();
+        assert_eq!(Result::SAT, dpll(matrix, None, statistics, timer()));
+        assert_eq!(0, statistics.backtrack_count);
+    }
+
+    /*
+    Tests that compacting the variables after pre-processing renumbers the remaining variables densely, and that the 
+    compacted instance has the same result.
+    */
+    #[test]
+    fn compact_test() {
+        let filename = "./test_files/compact_test.qdimacs".to_string();
+        let expected_result = run_instance(filename.clone());
+        let matrix = &mut Matrix::new(filename, config());
+        let statistics = &mut Statistics::new();
+        preprocess(matrix, statistics, timer());
+        let variable_mapping = matrix.compact();
+        assert_eq!(HashMap::from([(3, 1), (4, 2), (6, 3), (8, 4)]), variable_mapping);
+        assert_eq!(vec![1, 3, 4], matrix.quantification_order.existential_literal_order);
+        assert_eq!(vec![2], matrix.quantification_order.universal_literal_order);
+        assert_eq!(HashMap::from([(1, 0), (3, 1), (4, 2), (2, 0)]), matrix.quantification_order.literal_positions);
+        assert_eq!(vec![1, 2, 3, 4], matrix.quantifiers_sorted().iter().map(|quantifier| quantifier.literal).collect::<Vec<i32>>());
+        assert_eq!(Some(&vec![1]), matrix.clause_references.get_vec(&-4));
+        assert_eq!(vec![3, -4, 2], matrix.clause_set.clause_list[1].literals());
+        assert_eq!(expected_result, dpll(matrix, None, statistics, timer()));
+    }
+
+    /*
+    Tests that iterative deepening solves a shallow satisfiable instance at the smallest bound that is large enough, 
+    counting the decisions of every attempt, and that a single attempt stops once the maximum number of decisions is 
+    exceeded.
+    */
+    #[test]
+    fn iterative_deepening_test() {
+        let filename = "./test_files/iterative_deepening_test.qdimacs".to_string();
+        let mut config = config();
+        config.pre_process = false;
+        config.pure_literal_deletion = false;
+        config.iterative_deepening = true;
+        let matrix = &mut Matrix::new(filename.clone(), config.clone());
+        let statistics = &mut Statistics::new();
+        assert_eq!(Result::SAT, iterative_deepening(matrix, statistics, timer()));
+        assert_eq!(Some(2), statistics.decision_bound);
+        // The attempt at bound 1 is stopped at its second decision, and the attempt at bound 2 makes two decisions.
+        assert_eq!(2, statistics.attempt_decision_count);
+        assert_eq!(4, statistics.decision_count);
+
+        config.iterative_deepening = false;
+        config.max_decisions = 1;
+        let matrix = &mut Matrix::new(filename, config);
+        let statistics = &mut Statistics::new();
+        assert_eq!(Result::DecisionLimit, dpll(matrix, None, statistics, timer()));
+        assert_eq!(None, statistics.decision_bound);
+    }
+
+
+    /*
+    Tests that an instance the Ordered selection can't solve within half of the decision limit is retried and solved 
+    with the fallback VSS selection under the full limit.
+    */
+    #[test]
+    fn fallback_literal_selection_test() {
+        let filenames = vec!["./test_files/fallback_literal_selection_test.qdimacs".to_string()];
+        let mut config = config();
+        config.fallback_literal_selection = Some(LiteralSelection::VariableStateSum);
+        config.max_decisions = 1000;
+        let timer = timer();
+        let matrix = &mut Matrix::from_files(&filenames, config.first_attempt_config()).unwrap();
+        assert_eq!(500, matrix.config.max_decisions);
+        let statistics = &mut Statistics::new();
+        let first_result = solve(matrix, statistics, timer);
+        assert_eq!(Result::DecisionLimit, first_result);
+        let result = retry_with_fallback(&filenames, &config, matrix, statistics, first_result, timer);
+        assert_eq!(Result::UNSAT, result);
+        assert_eq!(LiteralSelection::VariableStateSum, matrix.config.literal_selection);
+        assert_eq!(1000, matrix.config.max_decisions);
+    }
+
     /* END OF GENERAL INSTANCE TESTS */
 }
\ No newline at end of file
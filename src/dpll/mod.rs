@@ -4,38 +4,140 @@ mod dpll;
 mod bench;
 mod dpll_tests;
 
-use crate::{dpll::{preprocess::preprocess, dpll::{dpll, Result}, bench::{run_clause_variable_ratio_instances, run_bench_group}}, data_structures::{Matrix, Statistics, Config}, resolution::pre_resolution};
+pub use crate::dpll::preprocess::preprocess;
+pub use crate::dpll::dpll::Result;
+use crate::{log_normal, log_debug, dpll::{dpll::{dpll, iterative_deepening}, unit_propagate::unit_propagate, bench::{run_clause_variable_ratio_instances, run_bench_group}}, data_structures::{Matrix, Statistics, Config, TimeoutPhase}, resolution::pre_resolution, universal_reduction::reduce_parsed_clauses, pure_literal_deletion::prune_void_quantifiers, statistics::write_statistics_json, util::build_fallback_matrix};
 use std::time::Instant;
 
 /*
-A function to run pre-processing, pre-resolution, and dpll, checking for satisfiability and unsatisfiability.
+A function to run pre-processing, pre-resolution, and dpll, checking for satisfiability and unsatisfiability. Several
+instances are solved as their conjunction.
 */
-pub fn run_instance(filename: String, config: Config) {
+pub fn run_instance(filenames: &[String], config: Config) {
     let timer = Instant::now();
-    let matrix = &mut Matrix::new(filename, config);
+    match Matrix::from_files(filenames, config.first_attempt_config()) {
+        Ok(matrix) => run_parsed_instance(filenames, config, matrix, timer),
+        Err(error) => log_normal!(config.verbosity, "{}", error),
+    }
+}
+
+/*
+A function to run an instance as above once it has been parsed into a matrix with the first attempt config, so the
+instance isn't parsed again after being inspected, such as when selecting a solver.
+*/
+pub fn run_parsed_instance(filenames: &[String], config: Config, mut matrix: Matrix, timer: Instant) {
+    let verbosity = config.verbosity;
+    let matrix = &mut matrix;
     let statistics = &mut Statistics::new();
-    if matrix.config.pre_process_enabled() { preprocess(matrix, statistics, timer) };
-    if matrix.config.pre_resolution_enabled() { pre_resolution(matrix, &mut Vec::new()) };
-    let result = dpll(matrix, None, statistics, timer);
+    let first_result = solve(matrix, statistics, timer);
+    let result = retry_with_fallback(filenames, &config, matrix, statistics, first_result, timer);
     match &result {
-        Result::UNSAT => println!("Unsatisfiable"),
-        Result::SAT => println!("Satisfiable"),
-        Result::Timeout => println!("Runtime has timed out - > 30 seconds.")
+        Result::UNSAT if !matrix.config.initial_assignments.is_empty() => log_normal!(verbosity, "Unsatisfiable under the initial assignments"),
+        Result::UNSAT => log_normal!(verbosity, "Unsatisfiable"),
+        Result::SAT => log_normal!(verbosity, "Satisfiable"),
+        Result::Timeout(phase) if matrix.config.interrupted() => log_normal!(verbosity, "Interrupted during {:?}.", phase),
+        Result::Timeout(phase) => log_normal!(verbosity, "Runtime has timed out during {:?} - > {} seconds.", phase, matrix.config.timeout.as_secs()),
+        Result::DecisionLimit => log_normal!(verbosity, "Decision limit of {} has been reached.", matrix.config.max_decisions),
+    }
+    log_normal!(verbosity, "Runtime: {}", config.format_duration(timer.elapsed()));
+    if config.fallback_enabled() && (result.eq(&Result::SAT) || result.eq(&Result::UNSAT)) {
+        log_normal!(verbosity, "Solved with {:?} literal selection.", matrix.config.literal_selection);
+    }
+    if !statistics.decision_bound.is_none() {
+        log_normal!(verbosity, "Solved with iterative deepening at a decision bound of {}.", statistics.decision_bound.unwrap());
+    }
+    if (result.eq(&Result::SAT) || result.eq(&Result::UNSAT)) && matrix.config.initial_assignments.is_empty() && !matrix.matches_expected_result(result.eq(&Result::SAT)) {
+        log_normal!(verbosity, "Result does not match the expected result of the instance.");
+    }
+    log_normal!(verbosity, "{}", statistics.format_simplification_statistics());
+    if matrix.config.statistics_json_enabled() {
+        let pathname = write_statistics_json(filenames, statistics, &matrix.config.output_dir);
+        log_normal!(verbosity, "Statistics written to {}", pathname);
+    }
+}
+
+/*
+A function to retry an instance whose first attempt timed out or hit its decision limit, solving it again with the 
+fallback literal selection if a retry is made. The statistics are reset so they describe the retry.
+
+Returns the result of the retry, or the given result if no retry is made or the instance can't be read again.
+*/
+pub fn retry_with_fallback(filenames: &[String], config: &Config, matrix: &mut Matrix, statistics: &mut Statistics, result: Result, timer: Instant) -> Result {
+    let fallback_matrix = match build_fallback_matrix(filenames, config, matches!(result, Result::Timeout(_) | Result::DecisionLimit), timer) {
+        Ok(fallback_matrix) => fallback_matrix,
+        Err(error) => {
+            log_normal!(config.verbosity, "{}", error);
+            return result;
+        },
+    };
+    if fallback_matrix.is_none() { return result };
+    *matrix = fallback_matrix.unwrap();
+    *statistics = Statistics::new();
+    return solve(matrix, statistics, timer);
+}
+
+/*
+A function to run pre-processing, pre-resolution, and dpll on an already constructed Matrix. Pre-resolution is run 
+before pre-processing if configured to.
+
+Returns the result of the DPLL procedure.
+*/
+pub fn solve(matrix: &mut Matrix, statistics: &mut Statistics, timer: Instant) -> Result {
+    if matrix.config.universal_reduction_enabled() {
+        let unit_literals = reduce_parsed_clauses(matrix, statistics);
+        if matrix.clause_set.contains_empty_clause() { return Result::UNSAT };
+        log_debug!(matrix.config.verbosity, "Unit literals after universal reduction at parse: {:?}", unit_literals);
+    }
+    if !matrix.config.initial_assignments.is_empty() {
+        let initial_assignments = matrix.take_initial_assignments();
+        unit_propagate(matrix, initial_assignments, statistics);
+        if matrix.clause_set.contains_empty_clause() { return Result::UNSAT };
+        if matrix.clause_set.contains_empty_set() { return Result::SAT };
+    }
+    let resolution_first = matrix.config.resolution_before_preprocess_enabled();
+    if resolution_first && matrix.config.pre_resolution_enabled() {
+        pre_resolution(matrix, &mut Vec::new());
+        matrix.debug_verify_structural_invariants("pre-resolution");
+        if matrix.config.timed_out(timer) { return Result::Timeout(TimeoutPhase::PreResolution) };
+    }
+    if matrix.config.pre_process_enabled() {
+        preprocess(matrix, statistics, timer);
+        matrix.debug_verify_structural_invariants("pre-processing");
+        if matrix.config.timed_out(timer) { return Result::Timeout(TimeoutPhase::Preprocess) };
+    }
+    if !resolution_first && matrix.config.pre_resolution_enabled() {
+        pre_resolution(matrix, &mut Vec::new());
+        matrix.debug_verify_structural_invariants("pre-resolution");
+        if matrix.config.timed_out(timer) { return Result::Timeout(TimeoutPhase::PreResolution) };
+    }
+    if matrix.config.prune_void_quantifiers_enabled() {
+        prune_void_quantifiers(matrix);
+    }
+    if matrix.config.verbose_preprocess_enabled() {
+        log_normal!(matrix.config.verbosity, "Pre-processed instance -- {}", matrix.format_instance_size());
+    }
+    // The prefix may no longer quantify the literals of the empty clause, so they can't be propagated again.
+    if matrix.clause_set.contains_empty_clause() { return Result::UNSAT };
+    if matrix.quantifier_list.is_empty() && !matrix.check_solved() {
+        return if matrix.remaining_clauses_satisfiable() { Result::SAT } else { Result::UNSAT };
+    }
+    if matrix.config.iterative_deepening_enabled() {
+        return iterative_deepening(matrix, statistics, timer);
     }
+    return dpll(matrix, None, statistics, timer);
 }
 
 /*
 A function to perform tests on a given set of benchmarks in QDIMACS format. 
 */
-pub fn run_bench_directory(path: String, config: Config, filename_to_write: &str) {
-    run_bench_group(path, config, filename_to_write);
+pub fn run_bench_directory(path: String, config: Config, filename_to_write: &str, stats_only: bool) {
+    run_bench_group(path, config, filename_to_write, stats_only);
 }
 
 /*
 A function to perform tests on a subset of the tacchella data set to measure CPU time
 as clause/variable ratio increases.
 */
-#[allow(dead_code)]
 pub fn run_clause_variable_ratio_bench_directory(config: Config, filename_to_write: &str) {
     run_clause_variable_ratio_instances(config, filename_to_write);
 }
\ No newline at end of file
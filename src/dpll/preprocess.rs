@@ -1,8 +1,6 @@
-use std::time::Instant;
+use std::{collections::HashSet, time::Instant};
 
-use multimap::MultiMap;
-
-use crate::{dpll::unit_propagate::unit_propagate, data_structures::{Matrix, Statistics}, universal_reduction::{remove_universal_literal, get_universal_literals_for_reduction}, pure_literal_deletion::{get_pure_literals, remove_pure_literals}, util::get_unit_literals};
+use crate::{dpll::unit_propagate::unit_propagate, data_structures::{Matrix, Statistics}, universal_reduction::{remove_universal_literal, get_universal_literals_for_reduction}, pure_literal_deletion::{get_pure_literals, remove_pure_literals}, util::get_unit_literals, variable_elimination::variable_elimination};
 
 /*
 A function to reduce the initial problem set by applying pre-processing techniques unit propagation, universal reduction,
@@ -13,12 +11,18 @@ pub fn preprocess(matrix: &mut Matrix, statistics: &mut Statistics, timer: Insta
     let mut pure_literals;
     let mut literals_for_universal_reduction;
     let mut unit_literals;
-    while !is_finished {
-        // Timeout the instance after 30 seconds 
-        if timer.elapsed().as_secs() > 30 { return; };
+    let mut iterations = 0;
+    statistics.is_preprocessing = true;
+    // Stop after the maximum number of iterations even if a fixpoint hasn't been reached.
+    while !is_finished && iterations < matrix.config.max_preprocess_iterations {
+        iterations += 1;
+        if matrix.config.timed_out(timer) {
+            statistics.is_preprocessing = false;
+            return;
+        }
 
         // Perform unit propagation on the set of clauses
-        unit_literals = get_unit_literals(&matrix.clause_set.clause_list);
+        unit_literals = get_unit_literals(&matrix.clause_set);
         if !unit_literals.is_empty() {
             unit_propagate(matrix, unit_literals, statistics);
         }
@@ -26,8 +30,9 @@ pub fn preprocess(matrix: &mut Matrix, statistics: &mut Statistics, timer: Insta
 
         // Perform pure literal deletion on the set of clauses
         if matrix.config.pure_literal_deletion_enabled() {
-            pure_literals = get_pure_literals(&matrix.clause_references);
+            pure_literals = get_pure_literals(&matrix.clause_references, matrix.config.deterministic_enabled());
             if !pure_literals.is_empty() {
+                statistics.add_pure_literal_removed_variables(pure_literals.len() as i32);
                 remove_pure_literals(matrix, pure_literals);
             }
             if matrix.check_solved() { break; }
@@ -35,22 +40,28 @@ pub fn preprocess(matrix: &mut Matrix, statistics: &mut Statistics, timer: Insta
 
         // Perform universal reduction on the set of clauses
         if matrix.config.universal_reduction_enabled() {
-            literals_for_universal_reduction = get_universal_literals_for_reduction(&matrix.clause_set.clause_list, &matrix.variable_quantification);
+            literals_for_universal_reduction = get_universal_literals_for_reduction(&matrix.clause_set, &matrix.variable_quantification);
             if !literals_for_universal_reduction.is_empty() {
                 for literal_to_remove in literals_for_universal_reduction {
+                    statistics.add_universal_reduction_removed_literals(literal_to_remove.values.len() as i32);
                     remove_universal_literal(matrix, literal_to_remove.values, literal_to_remove.clause_index);
                 }
             }
             if matrix.check_solved() { break; }
         }
-        pure_literals = if matrix.config.pure_literal_deletion_enabled() {get_pure_literals(&matrix.clause_references) } else { Vec::new() };
-        literals_for_universal_reduction = if matrix.config.universal_reduction_enabled() { get_universal_literals_for_reduction(&matrix.clause_set.clause_list, &matrix.variable_quantification) } else { Vec::new() };
-        unit_literals = get_unit_literals(&matrix.clause_set.clause_list);
+        pure_literals = if matrix.config.pure_literal_deletion_enabled() {get_pure_literals(&matrix.clause_references, matrix.config.deterministic_enabled()) } else { Vec::new() };
+        literals_for_universal_reduction = if matrix.config.universal_reduction_enabled() { get_universal_literals_for_reduction(&matrix.clause_set, &matrix.variable_quantification) } else { Vec::new() };
+        unit_literals = get_unit_literals(&matrix.clause_set);
         if pure_literals.is_empty() && literals_for_universal_reduction.is_empty() && unit_literals.is_empty() {
             is_finished = true;
         }
     }
+    if matrix.config.variable_elimination_enabled() {
+        let bound = matrix.config.variable_elimination.1;
+        variable_elimination(matrix, bound);
+    }
     simplify_constraints(matrix);
+    statistics.is_preprocessing = false;
 }
 
 /*
@@ -58,20 +69,7 @@ A function to simplify the problem set constraints. It will permanently remove a
 the problem, and it will update the clause references where appropriate.
 */
 pub fn simplify_constraints(matrix: &mut Matrix) {
-    let mut remove_clause_references = Vec::new();
-    for (index, clause) in matrix.clause_set.clause_list.iter().enumerate() {
-        if clause.is_removed {
-            remove_clause_references.push(index as usize);
-        }
-    }
-    for reference in remove_clause_references.iter().rev() {
-        matrix.clause_set.clause_list.remove(*reference);
-    }
-    let mut clause_references = MultiMap::new();
-    for (index, clause) in matrix.clause_set.clause_list.iter().enumerate() {
-        for literal in clause.clone().get_literal_list() {
-            clause_references.insert(literal, index as i32);
-        }
-    }
-    matrix.clause_references = clause_references;
-}
\ No newline at end of file
+    let active_clause_indices: HashSet<usize> = matrix.clause_set.active_clauses().map(|(index, _clause)| index).collect();
+    let remove_clause_references = (0..matrix.clause_set.clause_list.len()).filter(|index| !active_clause_indices.contains(index)).collect();
+    matrix.remove_clauses(&remove_clause_references);
+}
@@ -0,0 +1,65 @@
+use std::time::Instant;
+
+use crate::{cdcl::{solve, Result}, data_structures::{Matrix, CDCLMatrix, Config, Statistics}, certificate::get_assigned_value};
+
+/*
+A function to enumerate the distinct solutions of an instance, calling the callback with each one until it returns
+false. A solution is the assignment of the existential variables in the outermost quantifier block, as in a
+certificate, given as literals in prefix order. Existential variables left unassigned are don't-cares and are omitted,
+so a solution stands for every assignment of them. If the outermost quantifier block is universal, the only solution
+is the empty assignment.
+
+Each solution is found by solving the instance with CDCL under the given config, then blocked with a clause of its
+negated literals so the next solve finds a different one. Pure literal deletion, pre-resolution and variable
+elimination are disabled as they keep the instance satisfiable but can lose solutions. The timeout covers the whole
+enumeration.
+
+Returns UNSAT once every solution has been enumerated, SAT if the callback stopped the enumeration, or the result of
+the solve that timed out or reached the conflict limit.
+*/
+pub fn solve_all<F: FnMut(&[i32]) -> bool>(matrix: &Matrix, config: &Config, mut callback: F) -> Result {
+    let timer = Instant::now();
+    let mut enumeration_matrix = matrix.clone();
+    enumeration_matrix.config = config.clone();
+    enumeration_matrix.config.certificate = true;
+    enumeration_matrix.config.pure_literal_deletion = false;
+    enumeration_matrix.config.pre_resolution.0 = false;
+    enumeration_matrix.config.variable_elimination.0 = false;
+    loop {
+        let cdcl_matrix = &mut CDCLMatrix::from_matrix(enumeration_matrix.clone());
+        let statistics = &mut Statistics::new();
+        let result = solve(cdcl_matrix, statistics, timer);
+        if !result.eq(&Result::SAT) {
+            return result;
+        }
+        let solution = get_outermost_solution(cdcl_matrix);
+        if !callback(&solution) {
+            return Result::SAT;
+        }
+        // The empty solution stands for every assignment, so there are no others to find.
+        if solution.is_empty() {
+            return Result::UNSAT;
+        }
+        let blocking_clause: Vec<i32> = solution.iter().map(|literal| -literal).collect();
+        enumeration_matrix.add_clause(&blocking_clause);
+    }
+}
+
+/*
+A function to get the assignment of the existential variables in the outermost quantifier block after a satisfiable
+solve, skipping the don't-cares.
+
+Returns the assigned literals in prefix order.
+*/
+fn get_outermost_solution(matrix: &CDCLMatrix) -> Vec<i32> {
+    let mut solution = Vec::new();
+    for literal in &matrix.core_data.quantification_order.existential_literal_order {
+        let variable = matrix.core_data.variable_quantification.get(literal).unwrap();
+        if variable.q_level != 1 { continue }; // Only existential variables in the outermost quantifier block.
+        let value = get_assigned_value(matrix, *literal);
+        if !value.is_none() {
+            solution.push(value.unwrap());
+        }
+    }
+    return solution;
+}
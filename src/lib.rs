@@ -0,0 +1,171 @@
+extern crate multimap;
+
+use std::time::{Duration, Instant};
+
+use crate::{data_structures::{Config, Matrix, CDCLMatrix, SolverType, Statistics}, util::select_solver_type};
+
+pub mod dpll;
+pub mod cdcl;
+pub mod parse_config;
+pub mod data_structures;
+pub mod util;
+pub mod resolution;
+pub mod universal_reduction;
+pub mod pure_literal_deletion;
+pub mod variable_elimination;
+pub mod literal_selection;
+pub mod maxqbf;
+pub mod parse;
+pub mod verify;
+pub mod certificate;
+pub mod qrat;
+pub mod enumerate;
+pub mod compare;
+pub mod statistics;
+pub mod logging;
+mod tests;
+
+/*
+An enum to store the reason a solve finished without deciding the instance.
+
+Timeout => The timeout in the config was reached.
+ConflictLimit => CDCL learned more clauses than the conflict limit in the config.
+NoProgress => CDCL kept learning the same clause more times than the repeated conflict limit in the config.
+DecisionLimit => DPLL made more decisions than the decision limit in the config.
+Interrupted => The interrupt flag in the config was raised.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UnknownReason {
+    Timeout,
+    ConflictLimit,
+    NoProgress,
+    DecisionLimit,
+    Interrupted,
+}
+
+/*
+An enum to store the outcome of solving an instance, with the reason when it is unknown.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolveOutcome {
+    Sat,
+    Unsat,
+    Unknown { reason: UnknownReason },
+}
+
+impl SolveOutcome {
+    /*
+    Maps the result of a DPLL solve under the given config to an outcome. A timeout after the interrupt flag was raised
+    is reported as interrupted.
+    */
+    pub fn from_dpll_result(result: &dpll::Result, config: &Config) -> Self {
+        return match result {
+            dpll::Result::SAT => SolveOutcome::Sat,
+            dpll::Result::UNSAT => SolveOutcome::Unsat,
+            dpll::Result::Timeout(_) => SolveOutcome::timeout(config),
+            dpll::Result::DecisionLimit => SolveOutcome::Unknown { reason: UnknownReason::DecisionLimit },
+        };
+    }
+
+    /*
+    Maps the result of a CDCL solve under the given config to an outcome. A timeout after the interrupt flag was raised
+    is reported as interrupted.
+    */
+    pub fn from_cdcl_result(result: &cdcl::Result, config: &Config) -> Self {
+        return match result {
+            cdcl::Result::SAT => SolveOutcome::Sat,
+            cdcl::Result::UNSAT => SolveOutcome::Unsat,
+            cdcl::Result::Timeout(_) | cdcl::Result::Restart => SolveOutcome::timeout(config),
+            cdcl::Result::ConflictLimit => SolveOutcome::Unknown { reason: UnknownReason::ConflictLimit },
+            cdcl::Result::NoProgress => SolveOutcome::Unknown { reason: UnknownReason::NoProgress },
+        };
+    }
+
+    fn timeout(config: &Config) -> Self {
+        let reason = if config.interrupted() { UnknownReason::Interrupted } else { UnknownReason::Timeout };
+        return SolveOutcome::Unknown { reason };
+    }
+}
+
+/**
+Solves the QBF instance in QDIMACS format at the given path. The instance is pre-processed according to the config, and
+DPLL or CDCL is selected from the alternation depth of its quantifier prefix as with the Auto solver type. The solve can
+be stopped from another thread by raising the interrupt flag of the config.
+
+Returns Sat or Unsat, or Unknown with the reason the solve gave up.
+
+# Examples
+
+```
+use std::{env, fs};
+use qbf_evaluators::{solve, SolveOutcome, UnknownReason, data_structures::Config};
+
+// There exists x1 such that for all x2, (x1 or x2) and (x1 or not x2).
+let path = env::temp_dir().join("solve_sat.qdimacs");
+fs::write(&path, "p cnf 2 2\ne 1 0\na 2 0\n1 2 0\n1 -2 0\n").unwrap();
+assert_eq!(SolveOutcome::Sat, solve(path.to_str().unwrap(), Config::default()));
+
+// An interrupted solve gives up without deciding the instance.
+let config = Config::default();
+config.interrupt.interrupt();
+let reason = UnknownReason::Interrupted;
+assert_eq!(SolveOutcome::Unknown { reason }, solve(path.to_str().unwrap(), config));
+```
+*/
+pub fn solve(filename: &str, config: Config) -> SolveOutcome {
+    return solve_matrix(Matrix::new(filename.to_string(), config));
+}
+
+/*
+A function to solve a parsed instance under the config of its matrix, selecting DPLL or CDCL as with the Auto solver type.
+
+Returns the outcome of the solve.
+*/
+fn solve_matrix(mut matrix: Matrix) -> SolveOutcome {
+    let timer = Instant::now();
+    let statistics = &mut Statistics::new();
+    let config = matrix.config.clone();
+    match select_solver_type(&matrix) {
+        SolverType::DPLL => {
+            let result = dpll::solve(&mut matrix, statistics, timer);
+            return SolveOutcome::from_dpll_result(&result, &config);
+        },
+        _ => {
+            let result = cdcl::solve(&mut CDCLMatrix::from_matrix(matrix), statistics, timer);
+            return SolveOutcome::from_cdcl_result(&result, &config);
+        },
+    }
+}
+
+/**
+Solves a parsed QBF instance, giving up once the time budget has been spent. The instance is pre-processed according to
+the config, and DPLL or CDCL is selected from the alternation depth of its quantifier prefix as with the Auto solver 
+type. The config replaces the one the matrix was parsed with and its timeout is replaced by the given budget, so only 
+options applied while parsing, such as duplicate clause removal and clause shuffling, come from the matrix.
+
+Returns Some(true) if the instance is satisfiable, Some(false) if it is unsatisfiable, or None if the outcome is unknown.
+
+# Examples
+
+```
+use std::time::Duration;
+use qbf_evaluators::{solve_with_budget, data_structures::{Config, Matrix}};
+
+// There exists x1 such that for all x2, (x1 or x2) and (x1 or not x2).
+let matrix = Matrix::from_string("p cnf 2 2\ne 1 0\na 2 0\n1 2 0\n1 -2 0\n", Config::default());
+assert_eq!(Some(true), solve_with_budget(matrix, Config::default(), Duration::from_secs(10)));
+
+// For all x1 there exists x2 such that (x1 or x2) and (x1 or not x2).
+let matrix = Matrix::from_string("p cnf 2 2\na 1 0\ne 2 0\n1 2 0\n1 -2 0\n", Config::default());
+assert_eq!(Some(false), solve_with_budget(matrix, Config::default(), Duration::from_secs(10)));
+```
+*/
+pub fn solve_with_budget(mut matrix: Matrix, config: Config, timeout: Duration) -> Option<bool> {
+    matrix.config = config;
+    matrix.config.timeout = timeout;
+    return match solve_matrix(matrix) {
+        SolveOutcome::Sat => Some(true),
+        SolveOutcome::Unsat => Some(false),
+        SolveOutcome::Unknown { .. } => None,
+    };
+}
@@ -1,20 +1,32 @@
-use crate::{data_structures::{Matrix, QuantifierType}, util::get_variable_state_sum};
+use multimap::MultiMap;
+
+use crate::{data_structures::{Matrix, QuantifierType, DefaultPhase}, util::get_variable_state_sum};
 
 /*
 A function to select a literal from the outermost quantifier from the quantification prefix. It will not select literals 
-which don't appear in the set of clauses, removing them from the quantifier prefix.
+which don't appear in the set of clauses, removing them from the quantifier prefix. The polarity of the selected literal
+is chosen by the configured default phase.
 
-Returns (the selected literal, quantification type of the literal).
+Returns (the selected literal, quantification type of the literal), or None if no variable left in the quantifier prefix
+appears in the set of clauses.
 */
-pub fn select_literal(matrix: &mut Matrix) -> (i32, QuantifierType) {
+pub fn select_literal(matrix: &mut Matrix) -> Option<(i32, QuantifierType)> {
+    if matrix.quantifier_list.is_empty() { return None };
     let mut quantifier = matrix.quantifier_list.remove(0);
     let mut literal = quantifier.literal;
     while !matrix.clause_references.contains_key(&literal) && !matrix.clause_references.contains_key(&-literal) {
+        if matrix.quantifier_list.is_empty() { return None };
         quantifier = matrix.quantifier_list.remove(0);
         literal = quantifier.literal;
     }
     let quantifier_type = quantifier.q_type;
-    return (literal, quantifier_type);
+    let choose_positive = match matrix.config.default_phase {
+        DefaultPhase::Positive => true,
+        DefaultPhase::Negative => false,
+        DefaultPhase::Occurrence => get_variable_state_sum(&matrix.clause_references, literal).1,
+    };
+    if !choose_positive { literal = -literal };
+    return Some((literal, quantifier_type));
 }
 
 /*
@@ -24,13 +36,45 @@ It will not select literals which don't appear in the set of clauses, removing t
 Explanation: ∃123∀46∃5(C), I can select literals from the set {1, 2, 3} in any order. Only after propagating all
 these literals can I select from the next quantification set ∀46.
 
-Returns (the selected literal, quantification type of the literal).
+Returns (the selected literal, quantification type of the literal), or None if no variable left in the quantifier prefix
+appears in the set of clauses.
+*/
+pub fn select_literal_vss(matrix: &mut Matrix) -> Option<(i32, QuantifierType)> {
+    return select_literal_by_score(matrix, get_variable_state_sum);
+}
+
+/*
+A function to select a literal from the outer quantification set by Dynamic Largest Individual Sum (DLIS), choosing
+the single literal that appears in the most clauses not yet satisfied. Unlike variable state sum, the two polarities of
+a variable are scored separately rather than summed. It will not select literals which don't appear in the set of 
+clauses, removing them from the quantifier prefix.
+
+Returns (the selected literal, quantification type of the literal), or None if no variable left in the quantifier prefix
+appears in the set of clauses.
 */
-pub fn select_literal_vss(matrix: &mut Matrix) -> (i32, QuantifierType) {
+pub fn select_literal_dlis(matrix: &mut Matrix) -> Option<(i32, QuantifierType)> {
+    return select_literal_by_score(matrix, |clause_references, literal| {
+        let pos_appearances = clause_references.get_vec(&literal).map_or(0, |references| references.len() as i32);
+        let neg_appearances = clause_references.get_vec(&-literal).map_or(0, |references| references.len() as i32);
+        return if neg_appearances > pos_appearances { (neg_appearances, false) } else { (pos_appearances, true) };
+    });
+}
+
+/*
+A function to select the literal with the highest score from the outer quantification set, where the score function 
+gives the score of a variable and whether to choose it positively. The first variable with the highest score is chosen.
+Void quantifiers passed over are removed from the quantifier prefix once a literal is selected.
+
+Returns (the selected literal, quantification type of the literal), or None if no variable left in the quantifier prefix
+appears in the set of clauses, in which case the quantifier prefix is left unchanged.
+*/
+fn select_literal_by_score<F>(matrix: &mut Matrix, score: F) -> Option<(i32, QuantifierType)>
+where F: Fn(&MultiMap<i32, i32>, i32) -> (i32, bool) {
+    if matrix.quantifier_list.is_empty() { return None };
     let mut max_appearences = 0;
     let mut remove_indices = Vec::new();
     let mut choice = 0;
-    let mut top_level_quantification = &matrix.quantifier_list.get(0).unwrap().q_type;
+    let mut top_level_existential = matrix.quantifier_list.get(0).unwrap().is_existential();
     let mut choose_positive = true;
     for (index, q) in matrix.quantifier_list.iter().enumerate() {
         if !matrix.clause_references.contains_key(&q.literal) && !matrix.clause_references.contains_key(&-q.literal) {
@@ -38,14 +82,14 @@ pub fn select_literal_vss(matrix: &mut Matrix) -> (i32, QuantifierType) {
             continue;
         }
         // Move to next quantifier type if necessary.
-        if q.q_type.ne(top_level_quantification) {
+        if q.is_existential() != top_level_existential {
             if max_appearences > 0 {
                 break;
             } else { 
-                top_level_quantification = &q.q_type;
+                top_level_existential = q.is_existential();
             }
         }
-        let (current_literal_appearances, priority) = get_variable_state_sum(&matrix.clause_references, q.literal);
+        let (current_literal_appearances, priority) = score(&matrix.clause_references, q.literal);
 
         if current_literal_appearances > max_appearences {
             choose_positive = priority;
@@ -53,6 +97,8 @@ pub fn select_literal_vss(matrix: &mut Matrix) -> (i32, QuantifierType) {
             choice = index;
         }
     }
+    // Every quantifier left in the prefix is void, so there is nothing to select.
+    if max_appearences == 0 { return None };
     let quantifier = matrix.quantifier_list.remove(choice);
     let literal = if choose_positive {quantifier.literal} else {-quantifier.literal};
     let quantifier_type = quantifier.q_type;
@@ -64,5 +110,5 @@ pub fn select_literal_vss(matrix: &mut Matrix) -> (i32, QuantifierType) {
             matrix.quantifier_list.remove(*index);
         }
     }
-    return (literal, quantifier_type);
-}
\ No newline at end of file
+    return Some((literal, quantifier_type));
+}
@@ -0,0 +1,64 @@
+use std::{cell::RefCell, io::Write};
+
+use crate::data_structures::Verbosity;
+
+thread_local! {
+    /*
+    The writer that log messages are sent to on the current thread. None writes to standard output.
+    */
+    static WRITER: RefCell<Option<Box<dyn Write>>> = RefCell::new(None);
+}
+
+/*
+A function to redirect the log messages of the current thread to the given writer, e.g. to capture output in tests.
+*/
+pub fn set_writer(writer: Box<dyn Write>) {
+    WRITER.with(|current| *current.borrow_mut() = Some(writer));
+}
+
+/*
+A function to send the log messages of the current thread back to standard output.
+*/
+pub fn reset_writer() {
+    WRITER.with(|current| *current.borrow_mut() = None);
+}
+
+/*
+A function to write a log message followed by a new line, if the level of the message is within the configured
+verbosity. Messages at the Normal level are suppressed by Quiet, and messages at the Debug level are only written at
+the Debug verbosity.
+*/
+pub fn log_message(verbosity: Verbosity, level: Verbosity, message: std::fmt::Arguments) {
+    if level > verbosity {
+        return;
+    }
+    WRITER.with(|current| {
+        match current.borrow_mut().as_mut() {
+            Some(writer) => { let _ = writeln!(writer, "{}", message); },
+            None => println!("{}", message),
+        }
+    });
+}
+
+/*
+A macro to log a message at the Normal level, taking the verbosity followed by the format arguments of println!.
+*/
+#[macro_export]
+macro_rules! log_normal {
+    ($verbosity:expr, $($arg:tt)*) => {
+        $crate::logging::log_message($verbosity, $crate::data_structures::Verbosity::Normal, format_args!($($arg)*))
+    };
+}
+
+/*
+A macro to log a message at the Debug level, taking the verbosity followed by the format arguments of println!. The
+arguments are only formatted when the message is written.
+*/
+#[macro_export]
+macro_rules! log_debug {
+    ($verbosity:expr, $($arg:tt)*) => {
+        if $verbosity >= $crate::data_structures::Verbosity::Debug {
+            $crate::logging::log_message($verbosity, $crate::data_structures::Verbosity::Debug, format_args!($($arg)*))
+        }
+    };
+}
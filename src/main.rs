@@ -1,17 +1,6 @@
-extern crate multimap;
+use std::time::Instant;
 
-use crate::{parse_config::read_config_json, data_structures::SolverType};
-
-mod dpll;
-mod cdcl;
-mod parse_config;
-mod data_structures;
-mod util;
-mod resolution;
-mod universal_reduction;
-mod pure_literal_deletion;
-mod literal_selection;
-mod tests;
+use qbf_evaluators::{log_normal, parse_config::read_config_json, data_structures::{SolverType, Matrix}, util::select_solver_type, dpll, cdcl, maxqbf, parse, compare, statistics};
 
 /*
 The main function for running the different QBF solver implementations.
@@ -25,8 +14,35 @@ fn main() {
     let (solver, config) = read_config_json();
 
     if solver.run_bench {
-        if solver.solver_type.eq(&SolverType::DPLL) { dpll::run_bench_directory(solver.path, config, &solver.output) } else { cdcl::run_bench_directory(solver.path, config, &solver.output) }
+        match solver.solver_type {
+            SolverType::DPLL => dpll::run_bench_directory(solver.path, config, &solver.output, solver.stats_only),
+            SolverType::CDCL => cdcl::run_bench_directory(solver.path, config, &solver.output, solver.stats_only),
+            SolverType::MaxQBF => log_normal!(config.verbosity, "MaxQBF can only be run on a single instance."),
+            SolverType::Parse => log_normal!(config.verbosity, "Parse can only be run on a single instance."),
+            SolverType::Auto => log_normal!(config.verbosity, "Auto can only be run on a single instance."),
+            SolverType::Compare => log_normal!(config.verbosity, "Compare can only be run with RunBenchmark set to false."),
+            SolverType::Both => compare::run_bench_directory(solver.path, config, &solver.output, solver.stats_only),
+            SolverType::Aggregate => log_normal!(config.verbosity, "Aggregate can only be run with RunBenchmark set to false."),
+        }
+    } else if solver.instance_paths.len() > 1 && !matches!(solver.solver_type, SolverType::DPLL | SolverType::CDCL) {
+        log_normal!(config.verbosity, "Only DPLL and CDCL can be run on the conjunction of several instances.");
     } else {
-        if solver.solver_type.eq(&SolverType::DPLL) { dpll::run_instance(solver.path, config) } else { cdcl::run_instance(solver.path, config) }
+        match solver.solver_type {
+            SolverType::DPLL => dpll::run_instance(&solver.instance_paths, config),
+            SolverType::CDCL => cdcl::run_instance(&solver.instance_paths, config),
+            SolverType::MaxQBF => maxqbf::run_instance(solver.path, config),
+            SolverType::Parse => parse::run_instance(solver.path, config, &solver.output),
+            SolverType::Compare => compare::run_compare(&solver.baseline_path, &solver.path, config.verbosity),
+            SolverType::Both => log_normal!(config.verbosity, "Both can only be run with RunBenchmark set to true."),
+            SolverType::Aggregate => statistics::run_aggregate(&solver.path, config.verbosity),
+            SolverType::Auto => {
+                let timer = Instant::now();
+                match Matrix::from_files(&solver.instance_paths, config.first_attempt_config()) {
+                    Ok(matrix) if select_solver_type(&matrix).eq(&SolverType::DPLL) => dpll::run_parsed_instance(&solver.instance_paths, config, matrix, timer),
+                    Ok(matrix) => cdcl::run_parsed_instance(&solver.instance_paths, config, matrix, timer),
+                    Err(error) => log_normal!(config.verbosity, "{}", error),
+                }
+            },
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,201 @@
+use std::time::Instant;
+use multimap::MultiMap;
+
+use crate::{log_normal, log_debug, cdcl::{solve, Result}, data_structures::{Matrix, CDCLMatrix, Config, Statistics}};
+
+/*
+A struct to store the result of the MaxQBF procedure.
+
+Optimum => The hard clauses are satisfiable. Stores the maximum satisfiable soft clause weight and the indices of the 
+           soft clauses that were satisfied alongside the hard clauses.
+UNSAT => The hard clauses alone are unsatisfiable.
+Timeout => Instance timeout, stop running the current instance.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub enum MaxQBFResult {
+    Optimum(u32, Vec<usize>),
+    UNSAT,
+    Timeout,
+}
+
+/*
+A function to run MaxQBF solving on a weighted instance, printing the optimum soft clause weight.
+*/
+pub fn run_instance(filename: String, config: Config) {
+    let timer = Instant::now();
+    let timeout = config.timeout;
+    let verbosity = config.verbosity;
+    let matrix = Matrix::new(filename, config);
+    let result = maxqbf(&matrix, timer);
+    match &result {
+        MaxQBFResult::Optimum(weight, soft_clauses) => log_normal!(verbosity, "Optimum soft clause weight: {} - Satisfied soft clauses: {:?}", weight, soft_clauses),
+        MaxQBFResult::UNSAT => log_normal!(verbosity, "Unsatisfiable"),
+        MaxQBFResult::Timeout => log_normal!(verbosity, "Runtime has timed out: > {} seconds.", timeout.as_secs()),
+    }
+}
+
+/*
+A function to find the maximum total weight of soft clauses that can be satisfied alongside the hard clauses. 
+
+Each soft clause is relaxed with a new existential variable in an outermost block, which drops the clause when true, 
+and the total weight of the dropped soft clauses is bounded using a sequential weight counter. The bounded formula is 
+solved using CDCL, and the lower and upper bounds on the dropped weight are tightened each round by binary search until 
+they meet. The soft clauses kept at the optimum are then found by fixing each relaxation variable in turn.
+
+Note: The weight counter has a variable for each unit of weight up to the bound, so large weights make large formulas.
+
+Returns the Optimum (weight, satisfied soft clauses), UNSAT if the hard clauses are unsatisfiable, or Timeout.
+*/
+pub fn maxqbf(matrix: &Matrix, timer: Instant) -> MaxQBFResult {
+    let mut hard_clauses = Vec::new();
+    let mut soft_clauses = Vec::new();
+    for (index, clause) in matrix.clause_set.clause_list.iter().enumerate() {
+        if clause.is_soft() { soft_clauses.push(index) } else { hard_clauses.push(index) };
+    }
+    let total_weight: u32 = soft_clauses.iter().map(|index| matrix.clause_set.clause_list[*index].weight.unwrap()).sum();
+    let first_relaxation_variable = first_relaxation_variable(matrix);
+
+    // Dropping every soft clause leaves only the hard clauses.
+    match solve_relaxed(matrix, &hard_clauses, &soft_clauses, first_relaxation_variable, total_weight, &Vec::new(), timer) {
+        Result::SAT => {},
+        Result::UNSAT => return MaxQBFResult::UNSAT,
+        _ => return MaxQBFResult::Timeout,
+    }
+    let mut lower_bound = 0;
+    let mut upper_bound = total_weight;
+    while lower_bound < upper_bound {
+        let bound = lower_bound + (upper_bound - lower_bound) / 2;
+        match solve_relaxed(matrix, &hard_clauses, &soft_clauses, first_relaxation_variable, bound, &Vec::new(), timer) {
+            Result::SAT => upper_bound = bound,
+            Result::UNSAT => lower_bound = bound + 1,
+            _ => return MaxQBFResult::Timeout,
+        }
+    }
+
+    // Keep each soft clause in turn if the optimum can still be reached with it kept.
+    let mut kept_soft_clauses = Vec::new();
+    let mut fixed_relaxations = Vec::new();
+    for position in 0..soft_clauses.len() {
+        let relaxation_variable = first_relaxation_variable + position as i32;
+        fixed_relaxations.push(-relaxation_variable);
+        match solve_relaxed(matrix, &hard_clauses, &soft_clauses, first_relaxation_variable, upper_bound, &fixed_relaxations, timer) {
+            Result::SAT => kept_soft_clauses.push(soft_clauses[position]),
+            Result::UNSAT => *fixed_relaxations.last_mut().unwrap() = relaxation_variable,
+            _ => return MaxQBFResult::Timeout,
+        }
+    }
+    return MaxQBFResult::Optimum(total_weight - upper_bound, kept_soft_clauses);
+}
+
+/*
+A function to get the relaxation variable of the first soft clause, numbered after every variable of the matrix. The 
+relaxation variables of the other soft clauses follow it in order.
+*/
+fn first_relaxation_variable(matrix: &Matrix) -> i32 {
+    let variable_count = matrix.clause_set.clause_list.iter().flat_map(|clause| clause.literals()).map(|literal| literal.abs())
+                            .chain(matrix.variable_quantification.keys().copied()).max().unwrap_or(0);
+    return variable_count + 1;
+}
+
+/*
+A function to solve the hard clauses together with the relaxed soft clauses, where the total weight of the dropped soft
+clauses is at most the bound, and the given relaxation literals are fixed. The relaxation variables are numbered from
+first_relaxation_variable.
+
+Returns the result of CDCL on the relaxed formula.
+*/
+fn solve_relaxed(matrix: &Matrix, hard_clauses: &Vec<usize>, soft_clauses: &Vec<usize>, first_relaxation_variable: i32, bound: u32, fixed_relaxations: &Vec<i32>, timer: Instant) -> Result {
+    let relaxation_variables: Vec<i32> = (0..soft_clauses.len()).map(|position| first_relaxation_variable + position as i32).collect();
+    let weights: Vec<u32> = soft_clauses.iter().map(|index| matrix.clause_set.clause_list[*index].weight.unwrap()).collect();
+    let mut next_variable = first_relaxation_variable + soft_clauses.len() as i32;
+    let mut clauses: Vec<Vec<i32>> = Vec::new();
+    for (position, index) in soft_clauses.iter().enumerate() {
+        let mut relaxed_clause = matrix.clause_set.clause_list[*index].literals();
+        relaxed_clause.push(relaxation_variables[position]);
+        clauses.push(relaxed_clause);
+    }
+    let first_counter_variable = next_variable;
+    clauses.extend(encode_weight_bound(&relaxation_variables, &weights, bound, &mut next_variable));
+    clauses.extend(fixed_relaxations.iter().map(|literal| vec![*literal]));
+
+    // The relaxation and counter variables are chosen before every other variable, so they select the soft clauses kept.
+    let hard_qdimacs = select_clauses(matrix, hard_clauses).to_qdimacs();
+    let added_variables: Vec<String> = (first_relaxation_variable..next_variable).map(|variable| variable.to_string()).collect();
+    let mut instance = format!("p cnf {} {}\n", next_variable - 1, hard_clauses.len() + clauses.len());
+    if !added_variables.is_empty() { instance += &format!("e {} 0\n", added_variables.join(" ")) };
+    for line in hard_qdimacs.lines().skip(1) {
+        instance += &format!("{}\n", line);
+    }
+    for clause in clauses {
+        instance += &format!("{} 0\n", clause.iter().map(|literal| literal.to_string()).collect::<Vec<String>>().join(" "));
+    }
+    log_debug!(matrix.config.verbosity, "Solving relaxed formula with bound {} and {} counter variables.", bound, next_variable - first_counter_variable);
+    let cdcl_matrix = &mut CDCLMatrix::from_matrix(Matrix::from_string(&instance, matrix.config.clone()));
+    let statistics = &mut Statistics::new();
+    return solve(cdcl_matrix, statistics, timer);
+}
+
+/*
+A function to encode that the total weight of the true relaxation variables is at most the bound, as a sequential 
+weight counter. Counter variable (i, j) is true if the weight of the true relaxation variables up to i is at least j.
+
+Returns the clauses of the encoding, numbering the counter variables from next_variable.
+*/
+pub fn encode_weight_bound(relaxation_variables: &Vec<i32>, weights: &Vec<u32>, bound: u32, next_variable: &mut i32) -> Vec<Vec<i32>> {
+    let mut clauses = Vec::new();
+    // No counter is needed if dropping every soft clause is within the bound.
+    if weights.iter().sum::<u32>() <= bound { return clauses };
+    let bound = bound as usize;
+    let mut previous_counter: Vec<i32> = Vec::new();
+    for (position, &relaxation_variable) in relaxation_variables.iter().enumerate() {
+        let weight = weights[position] as usize;
+        // A soft clause weighing more than the bound can never be dropped.
+        if weight > bound {
+            clauses.push(vec![-relaxation_variable]);
+        }
+        let counter: Vec<i32> = (0..bound).map(|offset| *next_variable + offset as i32).collect();
+        *next_variable += bound as i32;
+        for j in 0..weight.min(bound) {
+            clauses.push(vec![-relaxation_variable, counter[j]]);
+        }
+        if !previous_counter.is_empty() {
+            for j in 0..bound {
+                clauses.push(vec![-previous_counter[j], counter[j]]);
+            }
+            if weight <= bound {
+                for j in 0..(bound - weight) {
+                    clauses.push(vec![-relaxation_variable, -previous_counter[j], counter[j + weight]]);
+                }
+                if weight > 0 {
+                    clauses.push(vec![-relaxation_variable, -previous_counter[bound - weight]]);
+                }
+            }
+        }
+        previous_counter = counter;
+    }
+    return clauses;
+}
+
+/*
+A function to create a copy of a matrix containing only the clauses at the given indices, treating them all as hard
+clauses. The clause references and clause count are rebuilt for the new clause database.
+
+Returns the new matrix.
+*/
+pub fn select_clauses(matrix: &Matrix, clause_indices: &Vec<usize>) -> Matrix {
+    let mut new_matrix = matrix.clone();
+    let mut clause_list = Vec::new();
+    let mut clause_references = MultiMap::new();
+    for (new_index, index) in clause_indices.iter().enumerate() {
+        let mut clause = matrix.clause_set.clause_list[*index].clone();
+        clause.weight = None;
+        for literal in clause.literals() {
+            clause_references.insert(literal, new_index as i32);
+        }
+        clause_list.push(clause);
+    }
+    new_matrix.clause_set.clause_count = clause_list.len() as i32;
+    new_matrix.clause_set.clause_list = clause_list;
+    new_matrix.clause_references = clause_references;
+    return new_matrix;
+}
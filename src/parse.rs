@@ -0,0 +1,25 @@
+use std::{fs, time::Instant};
+
+use crate::{log_normal, dpll::preprocess, data_structures::{Matrix, Config, Statistics}};
+
+/*
+A function to parse an instance, apply pre-processing if enabled, and write the normalised instance in QDIMACS format to
+the output file without solving it.
+*/
+pub fn run_instance(filename: String, config: Config, filename_to_write: &str) {
+    let verbosity = config.verbosity;
+    let matrix = normalise_instance(filename, config);
+    fs::write(filename_to_write, matrix.to_qdimacs()).expect("Unable to write file");
+    log_normal!(verbosity, "Normalised instance written to {}", filename_to_write);
+}
+
+/*
+A function to parse an instance and apply pre-processing if enabled.
+
+Returns the normalised matrix.
+*/
+pub fn normalise_instance(filename: String, config: Config) -> Matrix {
+    let matrix = &mut Matrix::new(filename, config);
+    if matrix.config.pre_process_enabled() { preprocess(matrix, &mut Statistics::new(), Instant::now()) };
+    return matrix.clone();
+}
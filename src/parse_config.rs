@@ -1,7 +1,7 @@
-use std::fs::File;
+use std::{fs::File, time::Duration};
 use serde_json::Value;
 
-use crate::data_structures::{SolverType, LiteralSelection, Config, ResolutionConfig, Solver};
+use crate::data_structures::{SolverType, LiteralSelection, Config, ResolutionConfig, Solver, RestartPolicy, LearningScheme, DefaultPhase, PropagationOrder, Verbosity, Interrupt};
 
 /*
 A function to read the configuration of the solver within config.json.
@@ -18,38 +18,74 @@ pub fn read_config_json() -> (Solver, Config) {
     let solver_options = json.get("SolverOptions").expect("file should have SolverOptions key");
 
     let solver_type_json = solver_options.get("SolverType").expect("file should have SolverType key");
-    let solver_type = read_solver_type_json(solver_type_json).expect("SolverType should be a valid solver: CDCL or DPLL");
+    let solver_type = read_solver_type_json(solver_type_json).expect("SolverType should be a valid solver: CDCL, DPLL, MaxQBF, Parse, Auto, Compare, Both, or Aggregate");
 
     let run_bench_json = json.get("RunBenchmark").expect("file should have RunBenchmark key");
     let run_bench = read_boolean_json(run_bench_json).expect("RunBenchmark should be a Boolean value");
     let path = read_path(run_bench, &json);
+    let instance_paths = if run_bench { vec![path.clone()] } else { read_instance_paths(&json) };
 
     let output_json = json.get("OutputFileName").expect("file should have OutputFileName key");
     let output = read_string_json(output_json).expect("OutputFileName must be a string");
 
+    let output_dir_json = json.get("OutputDirectory").expect("file should have OutputDirectory key");
+    let output_dir = read_string_json(output_dir_json).expect("OutputDirectory must be a string");
+
+    let stats_only_json = json.get("StatsOnly").expect("file should have StatsOnly key");
+    let stats_only = read_boolean_json(stats_only_json).expect("StatsOnly should be a Boolean value");
+
+    let times_in_milliseconds_json = json.get("TimesInMilliseconds").expect("file should have TimesInMilliseconds key");
+    let times_in_milliseconds = read_boolean_json(times_in_milliseconds_json).expect("TimesInMilliseconds should be a Boolean value");
+
+    let baseline_path_json = json.get("BaselinePath").expect("file should have BaselinePath key");
+    let baseline_path = read_string_json(baseline_path_json).expect("BaselinePath must be a string");
+
     let solver = Solver {
         solver_type,
         run_bench,
         path,
+        instance_paths,
         output,
+        stats_only,
+        baseline_path,
     };
 
     let pre_resolution_options = solver_options.get("PreResolutionConfig").expect("file should have PreResolutionConfig key");
     let min_ratio_json = pre_resolution_options.get("min_ratio").expect("file should have min_ratio key");
     let max_ratio_json = pre_resolution_options.get("max_ratio").expect("file should have max_ratio key");
     let max_clause_length_json = pre_resolution_options.get("max_clause_length").expect("file should have max_clause_length key");
+    let length_per_level_json = pre_resolution_options.get("length_per_level").expect("file should have length_per_level key");
+    let min_level_json = pre_resolution_options.get("min_level").expect("file should have min_level key");
+    let max_level_json = pre_resolution_options.get("max_level").expect("file should have max_level key");
     let repeat_above_json = pre_resolution_options.get("repeat_above").expect("file should have repeat_above key");
     let iterations_json = pre_resolution_options.get("iterations").expect("file should have iterations key");
+    let max_added_clauses_json = pre_resolution_options.get("max_added_clauses").expect("file should have max_added_clauses key");
+    let max_duration_ms_json = pre_resolution_options.get("max_duration_ms").expect("file should have max_duration_ms key");
+    let subsumption_json = pre_resolution_options.get("subsumption").expect("file should have subsumption key");
+    let universal_reduction_in_presolve_json = pre_resolution_options.get("universal_reduction_in_presolve").expect("file should have universal_reduction_in_presolve key");
     let resolution_config = ResolutionConfig {
         min_ratio: read_number_json_f32(min_ratio_json).expect("min_ratio value must be a valid number or 'infinity'"),
         max_ratio: read_number_json_f32(max_ratio_json).expect("min_ratio value must be a valid number or 'infinity'"),
         max_clause_length: read_number_json_usize(max_clause_length_json).expect("max_clause_length value must be a valid number or 'infinity'"),
+        length_per_level: read_number_json_usize(length_per_level_json).expect("length_per_level value must be a valid number or 'infinity'"),
+        min_level: read_number_json_usize(min_level_json).expect("min_level value must be a valid number or 'infinity'"),
+        max_level: read_number_json_usize(max_level_json).expect("max_level value must be a valid number or 'infinity'"),
         repeat_above: read_number_json_usize(repeat_above_json).expect("repeat_above value must be a valid number or 'infinity'"),
-        iterations: read_number_json_i32(iterations_json).expect("iterations value must be a valid number")
+        iterations: read_number_json_i32(iterations_json).expect("iterations value must be a valid number"),
+        max_added_clauses: read_number_json_usize(max_added_clauses_json).expect("max_added_clauses value must be a valid number or 'infinity'"),
+        max_duration_ms: read_number_json_usize(max_duration_ms_json).expect("max_duration_ms value must be a valid number or 'infinity'"),
+        subsumption: read_boolean_json(subsumption_json).expect("subsumption should be a Boolean value"),
+        universal_reduction_in_presolve: read_boolean_json(universal_reduction_in_presolve_json).expect("universal_reduction_in_presolve should be a Boolean value"),
     };
 
     let literal_selection_json = solver_options.get("LiteralSelection").expect("file should have LiteralSelection key");
-    let literal_selection = read_literal_selection_json(literal_selection_json).expect("LiteralSelection should be a valid type: VSS or Ordered");
+    let literal_selection = read_literal_selection_json(literal_selection_json).expect("LiteralSelection should be a valid type: VSS, Ordered or DLIS");
+    let fallback_literal_selection_json = solver_options.get("FallbackLiteralSelection").expect("file should have FallbackLiteralSelection key");
+    let fallback_literal_selection = read_fallback_literal_selection_json(fallback_literal_selection_json).expect("FallbackLiteralSelection should be None or a valid type: VSS, Ordered or DLIS");
+    let default_phase_json = solver_options.get("DefaultPhase").expect("file should have DefaultPhase key");
+    let default_phase = read_default_phase_json(default_phase_json).expect("DefaultPhase should be a valid phase: Positive, Negative, or Occurrence");
+    let propagation_order_json = solver_options.get("PropagationOrder").expect("file should have PropagationOrder key");
+    let propagation_order = read_propagation_order_json(propagation_order_json).expect("PropagationOrder should be a valid order: FIFO or LIFO");
 
     let pre_process_json = solver_options.get("Preprocess").expect("file should have Preprocess key");
     let pre_process = read_boolean_json(pre_process_json).expect("Preprocess should be a Boolean value");
@@ -59,21 +95,156 @@ pub fn read_config_json() -> (Solver, Config) {
 
     let pure_literal_deletion_json = solver_options.get("PureLiteralDeletion").expect("file should have PureLiteralDeletion key");
     let pure_literal_deletion = read_boolean_json(pure_literal_deletion_json).expect("PureLiteralDeletion should be a Boolean value");
+    let prune_void_quantifiers_json = solver_options.get("PruneVoidQuantifiers").expect("file should have PruneVoidQuantifiers key");
+    let prune_void_quantifiers = read_boolean_json(prune_void_quantifiers_json).expect("PruneVoidQuantifiers should be a Boolean value");
 
     let restarts_json = solver_options.get("Restarts").expect("file should have Restarts key");
     let restarts = read_boolean_json(restarts_json).expect("Restarts should be a Boolean value");
 
+    let glucose_options = solver_options.get("GlucoseConfig").expect("file should have GlucoseConfig key");
+    let window_json = glucose_options.get("window").expect("file should have window key");
+    let margin_json = glucose_options.get("margin").expect("file should have margin key");
+    let window = read_number_json_usize(window_json).expect("window value must be a valid number");
+    let margin = read_number_json_f32(margin_json).expect("margin value must be a valid number");
+    let restart_policy_json = solver_options.get("RestartPolicy").expect("file should have RestartPolicy key");
+    let restart_policy = read_restart_policy_json(restart_policy_json, window, margin).expect("RestartPolicy should be a valid policy: Luby or Glucose");
+    let restart_blocking_json = solver_options.get("RestartBlocking").expect("file should have RestartBlocking key");
+    let restart_blocking = read_boolean_json(restart_blocking_json).expect("RestartBlocking should be a Boolean value");
+
+    let learning_scheme_json = solver_options.get("LearningScheme").expect("file should have LearningScheme key");
+    let learning_scheme = read_learning_scheme_json(learning_scheme_json).expect("LearningScheme should be a valid scheme: FirstUIP or Decision");
+
+    let max_preprocess_iterations_json = solver_options.get("MaxPreprocessIterations").expect("file should have MaxPreprocessIterations key");
+    let max_preprocess_iterations = read_number_json_usize(max_preprocess_iterations_json).expect("MaxPreprocessIterations value must be a valid number or 'infinity'");
+
+    let repeated_conflict_limit_json = solver_options.get("RepeatedConflictLimit").expect("file should have RepeatedConflictLimit key");
+    let repeated_conflict_limit = read_number_json_usize(repeated_conflict_limit_json).expect("RepeatedConflictLimit value must be a valid number or 'infinity'");
+    let max_conflicts_json = solver_options.get("MaxConflicts").expect("file should have MaxConflicts key");
+    let max_conflicts = read_number_json_usize(max_conflicts_json).expect("MaxConflicts value must be a valid number or 'infinity'");
+    let sat_tail_json = solver_options.get("SATTail").expect("file should have SATTail key");
+    let sat_tail = read_boolean_json(sat_tail_json).expect("SATTail should be a Boolean value");
+
+    let auto_alternation_threshold_json = solver_options.get("AutoAlternationThreshold").expect("file should have AutoAlternationThreshold key");
+    let auto_alternation_threshold = read_number_json_usize(auto_alternation_threshold_json).expect("AutoAlternationThreshold value must be a valid number or 'infinity'");
+
+    let timeout_json = solver_options.get("Timeout").expect("file should have Timeout key");
+    let timeout = Duration::from_secs(read_number_json_usize(timeout_json).expect("Timeout value must be a valid number or 'infinity'") as u64);
+
+    let iterative_deepening_json = solver_options.get("IterativeDeepening").expect("file should have IterativeDeepening key");
+    let iterative_deepening = read_boolean_json(iterative_deepening_json).expect("IterativeDeepening should be a Boolean value");
+    let max_decisions_json = solver_options.get("MaxDecisions").expect("file should have MaxDecisions key");
+    let max_decisions = read_number_json_usize(max_decisions_json).expect("MaxDecisions value must be a valid number or 'infinity'");
+
+    let initial_assignments_json = solver_options.get("InitialAssignments").expect("file should have InitialAssignments key");
+    let initial_assignments = read_literal_list_json(initial_assignments_json).expect("InitialAssignments should be a list of non-zero integer literals");
+    let remove_duplicate_clauses_json = solver_options.get("RemoveDuplicateClauses").expect("file should have RemoveDuplicateClauses key");
+    let remove_duplicate_clauses = read_boolean_json(remove_duplicate_clauses_json).expect("RemoveDuplicateClauses should be a Boolean value");
+    let reserve_hint_json = solver_options.get("ReserveHint").expect("file should have ReserveHint key");
+    let reserve_hint = read_boolean_json(reserve_hint_json).expect("ReserveHint should be a Boolean value");
+
+    let shuffle_clauses_json = solver_options.get("ShuffleClauses").expect("file should have ShuffleClauses key");
+    let shuffle_clauses = read_boolean_json(shuffle_clauses_json).expect("ShuffleClauses should be a Boolean value");
+
+    let seed_json = solver_options.get("Seed").expect("file should have Seed key");
+    let seed = read_number_json_usize(seed_json).expect("Seed value must be a valid number") as u64;
+    let per_instance_seed_json = solver_options.get("PerInstanceSeed").expect("file should have PerInstanceSeed key");
+    let per_instance_seed = read_boolean_json(per_instance_seed_json).expect("PerInstanceSeed should be a Boolean value");
+
+    let verbose_preprocess_json = solver_options.get("VerbosePreprocess").expect("file should have VerbosePreprocess key");
+    let verbose_preprocess = read_boolean_json(verbose_preprocess_json).expect("VerbosePreprocess should be a Boolean value");
+    let verbose_benchmark_json = solver_options.get("VerboseBenchmark").expect("file should have VerboseBenchmark key");
+    let verbose_benchmark = read_boolean_json(verbose_benchmark_json).expect("VerboseBenchmark should be a Boolean value");
+
+    let variable_elimination_json = solver_options.get("VariableElimination").expect("file should have VariableElimination key");
+    let variable_elimination_bound_json = solver_options.get("VariableEliminationBound").expect("file should have VariableEliminationBound key");
+    let variable_elimination = (read_boolean_json(variable_elimination_json).expect("VariableElimination should be a Boolean value"), 
+                                read_number_json_usize(variable_elimination_bound_json).expect("VariableEliminationBound value must be a valid number or 'infinity'"));
+
+    let deterministic_json = solver_options.get("Deterministic").expect("file should have Deterministic key");
+    let deterministic = read_boolean_json(deterministic_json).expect("Deterministic should be a Boolean value");
+
+    let verbosity_json = solver_options.get("Verbosity").expect("file should have Verbosity key");
+    let verbosity = read_verbosity_json(verbosity_json).expect("Verbosity should be 0 (quiet), 1 (normal), or 2 (debug)");
+
+    let debug_json = solver_options.get("Debug").expect("file should have Debug key");
+    let debug = read_boolean_json(debug_json).expect("Debug should be a Boolean value");
+    let dump_first_learned_json = solver_options.get("DumpFirstLearned").expect("file should have DumpFirstLearned key");
+    let dump_first_learned = read_boolean_json(dump_first_learned_json).expect("DumpFirstLearned should be a Boolean value");
+
+    let dump_implication_graph_json = solver_options.get("DumpImplicationGraph").expect("file should have DumpImplicationGraph key");
+    let dump_implication_graph = read_boolean_json(dump_implication_graph_json).expect("DumpImplicationGraph should be a Boolean value");
+
+    let record_events_json = solver_options.get("RecordEvents").expect("file should have RecordEvents key");
+    let record_events = read_boolean_json(record_events_json).expect("RecordEvents should be a Boolean value");
+    let statistics_json_json = solver_options.get("StatisticsJson").expect("file should have StatisticsJson key");
+    let statistics_json = read_boolean_json(statistics_json_json).expect("StatisticsJson should be a Boolean value");
+
+    let warm_restart_json = solver_options.get("WarmRestart").expect("file should have WarmRestart key");
+    let warm_restart = read_boolean_json(warm_restart_json).expect("WarmRestart should be a Boolean value");
+
+    let verify_json = solver_options.get("Verify").expect("file should have Verify key");
+    let verify = read_boolean_json(verify_json).expect("Verify should be a Boolean value");
+
+    let certificate_json = solver_options.get("Certificate").expect("file should have Certificate key");
+    let certificate = read_boolean_json(certificate_json).expect("Certificate should be a Boolean value");
+    let certificate_dont_cares_json = solver_options.get("CertificateDontCares").expect("file should have CertificateDontCares key");
+    let certificate_dont_cares = read_boolean_json(certificate_dont_cares_json).expect("CertificateDontCares should be a Boolean value");
+    let qrat_json = solver_options.get("QRAT").expect("file should have QRAT key");
+    let qrat = read_boolean_json(qrat_json).expect("QRAT should be a Boolean value");
+
     let pre_resolution_json = solver_options.get("PreResolution").expect("file should have PreResolution key");
     let pre_resolution = (read_boolean_json(pre_resolution_json).expect("PreResolution should be a Boolean value"), resolution_config);
+    let resolution_before_preprocess_json = solver_options.get("ResolutionBeforePreprocess").expect("file should have ResolutionBeforePreprocess key");
+    let resolution_before_preprocess = read_boolean_json(resolution_before_preprocess_json).expect("ResolutionBeforePreprocess should be a Boolean value");
 
 
     let config = Config {
         literal_selection,
+        fallback_literal_selection,
+        default_phase,
+        propagation_order,
         pre_resolution,
         pre_process,
+        resolution_before_preprocess,
         universal_reduction,
         pure_literal_deletion,
+        prune_void_quantifiers,
         restarts,
+        restart_policy,
+        restart_blocking,
+        sat_tail,
+        learning_scheme,
+        repeated_conflict_limit,
+        max_conflicts,
+        max_preprocess_iterations,
+        timeout,
+        interrupt: Interrupt::new(),
+        iterative_deepening,
+        max_decisions,
+        initial_assignments,
+        remove_duplicate_clauses,
+        reserve_hint,
+        shuffle_clauses,
+        seed,
+        per_instance_seed,
+        verbose_preprocess,
+        verbose_benchmark,
+        verbosity,
+        variable_elimination,
+        deterministic,
+        debug,
+        dump_first_learned,
+        dump_implication_graph,
+        record_events,
+        statistics_json,
+        warm_restart,
+        verify,
+        certificate,
+        certificate_dont_cares,
+        qrat,
+        output_dir,
+        times_in_milliseconds,
+        auto_alternation_threshold,
     };
 
     return (solver, config);
@@ -117,6 +288,24 @@ pub fn read_number_json_i32(value: &Value) -> Option<i32> {
     return None;
 }
 
+/*
+A function to read a list of literals from json. Returns the list of literals or None if invalid.
+*/
+pub fn read_literal_list_json(value: &Value) -> Option<Vec<i32>> {
+    if !value.is_array() {
+        return None;
+    }
+    let mut literals = Vec::new();
+    for literal_json in value.as_array().unwrap() {
+        let literal = read_number_json_i32(literal_json);
+        if literal.is_none() || literal.unwrap() == 0 {
+            return None;
+        }
+        literals.push(literal.unwrap());
+    }
+    return Some(literals);
+}
+
 /*
 A function to read SolverType objects from json. Returns SolverType object or None if invalid.
 */
@@ -126,6 +315,18 @@ pub fn read_solver_type_json(value: &Value) -> Option<SolverType> {
             return Some(SolverType::CDCL);
         } else if value.as_str().unwrap().to_lowercase().eq("dpll") {
             return Some(SolverType::DPLL);
+        } else if value.as_str().unwrap().to_lowercase().eq("maxqbf") {
+            return Some(SolverType::MaxQBF);
+        } else if value.as_str().unwrap().to_lowercase().eq("parse") {
+            return Some(SolverType::Parse);
+        } else if value.as_str().unwrap().to_lowercase().eq("auto") {
+            return Some(SolverType::Auto);
+        } else if value.as_str().unwrap().to_lowercase().eq("compare") {
+            return Some(SolverType::Compare);
+        } else if value.as_str().unwrap().to_lowercase().eq("both") {
+            return Some(SolverType::Both);
+        } else if value.as_str().unwrap().to_lowercase().eq("aggregate") {
+            return Some(SolverType::Aggregate);
         }
     }
     return None;
@@ -140,6 +341,90 @@ pub fn read_literal_selection_json(value: &Value) -> Option<LiteralSelection> {
             return Some(LiteralSelection::VariableStateSum);
         } else if value.as_str().unwrap().to_lowercase().eq("ordered") {
             return Some(LiteralSelection::Ordered);
+        } else if value.as_str().unwrap().to_lowercase().eq("dlis") {
+            return Some(LiteralSelection::DLIS);
+        }
+    }
+    return None;
+}
+
+/*
+A function to read the fallback LiteralSelection from json, where 'none' disables the fallback. Returns the optional
+LiteralSelection or None if invalid.
+*/
+pub fn read_fallback_literal_selection_json(value: &Value) -> Option<Option<LiteralSelection>> {
+    if value.is_string() && value.as_str().unwrap().to_lowercase().eq("none") {
+        return Some(None);
+    }
+    return read_literal_selection_json(value).map(Some);
+}
+
+/*
+A function to read DefaultPhase objects from json. Returns DefaultPhase object or None if invalid.
+*/
+pub fn read_default_phase_json(value: &Value) -> Option<DefaultPhase> {
+    if value.is_string() {
+        if value.as_str().unwrap().to_lowercase().eq("positive") {
+            return Some(DefaultPhase::Positive);
+        } else if value.as_str().unwrap().to_lowercase().eq("negative") {
+            return Some(DefaultPhase::Negative);
+        } else if value.as_str().unwrap().to_lowercase().eq("occurrence") {
+            return Some(DefaultPhase::Occurrence);
+        }
+    }
+    return None;
+}
+
+/*
+A function to read Verbosity objects from json, given as a level of 0, 1 or 2. Returns Verbosity object or None if invalid.
+*/
+pub fn read_verbosity_json(value: &Value) -> Option<Verbosity> {
+    match value.as_u64() {
+        Some(0) => return Some(Verbosity::Quiet),
+        Some(1) => return Some(Verbosity::Normal),
+        Some(2) => return Some(Verbosity::Debug),
+        _ => return None,
+    }
+}
+
+/*
+A function to read RestartPolicy objects from json, using the given window and margin for the Glucose policy. Returns 
+RestartPolicy object or None if invalid.
+*/
+pub fn read_restart_policy_json(value: &Value, window: usize, margin: f32) -> Option<RestartPolicy> {
+    if value.is_string() {
+        if value.as_str().unwrap().to_lowercase().eq("luby") {
+            return Some(RestartPolicy::Luby);
+        } else if value.as_str().unwrap().to_lowercase().eq("glucose") {
+            return Some(RestartPolicy::Glucose { window, margin });
+        }
+    }
+    return None;
+}
+
+/*
+A function to read LearningScheme objects from json. Returns LearningScheme object or None if invalid.
+*/
+pub fn read_learning_scheme_json(value: &Value) -> Option<LearningScheme> {
+    if value.is_string() {
+        if value.as_str().unwrap().to_lowercase().eq("firstuip") {
+            return Some(LearningScheme::FirstUIP);
+        } else if value.as_str().unwrap().to_lowercase().eq("decision") {
+            return Some(LearningScheme::Decision);
+        }
+    }
+    return None;
+}
+
+/*
+A function to read PropagationOrder objects from json. Returns PropagationOrder object or None if invalid.
+*/
+pub fn read_propagation_order_json(value: &Value) -> Option<PropagationOrder> {
+    if value.is_string() {
+        if value.as_str().unwrap().to_lowercase().eq("fifo") {
+            return Some(PropagationOrder::FIFO);
+        } else if value.as_str().unwrap().to_lowercase().eq("lifo") {
+            return Some(PropagationOrder::LIFO);
         }
     }
     return None;
@@ -165,11 +450,31 @@ pub fn read_path(run_bench: bool, json: &Value) -> String {
         path_json = json.get("BenchmarkPath").expect("file should have BenchmarkPath key");
     } else {
         path_json = json.get("InstancePath").expect("file should have InstancePath key");
+        // With several instance paths, the first is used wherever only one instance can be run.
+        if path_json.is_array() {
+            return read_instance_paths(json).remove(0);
+        }
     }
     let path = read_string_json(path_json).expect("BenchmarkPath and InstancePath must be a string");
     return path;
 }
 
+/*
+A function to read the instance paths from json, where InstancePath is either a single path or a non-empty list of paths.
+Returns the list of paths.
+*/
+pub fn read_instance_paths(json: &Value) -> Vec<String> {
+    let path_json = json.get("InstancePath").expect("file should have InstancePath key");
+    if !path_json.is_array() {
+        return vec![read_string_json(path_json).expect("InstancePath must be a string or a list of strings")];
+    }
+    let paths: Vec<String> = path_json.as_array().unwrap().iter()
+                                .map(|path| read_string_json(path).expect("InstancePath must be a string or a list of strings"))
+                                .collect();
+    if paths.is_empty() { panic!("InstancePath must not be an empty list") };
+    return paths;
+}
+
 /*
 A function to read String values from json. Returns String value or None if invalid.
 */
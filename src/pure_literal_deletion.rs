@@ -1,12 +1,13 @@
 use multimap::MultiMap;
-use crate::{data_structures::{Matrix, QuantifierType}, universal_reduction::{remove_universal_literal, detect_universal_literal}, util::get_quantifier_type};
+use crate::{data_structures::Matrix, universal_reduction::{remove_universal_literal, detect_universal_literal}, util::get_quantifier_type};
 
 /*
-A function to get a list of pure literals from a given state.
+A function to get a list of pure literals from a given state. The clause references are iterated in hash order, which
+varies between runs, so when deterministic is set the pure literals are sorted to make their order reproducible.
 
 Returns the list of pure literals.
 */
-pub fn get_pure_literals(clause_references: &MultiMap<i32, i32>) -> Vec<i32> {
+pub fn get_pure_literals(clause_references: &MultiMap<i32, i32>, deterministic: bool) -> Vec<i32> {
     let mut pure_literals = Vec::new();
     for key in clause_references.keys() {
         let complement_key = -key;
@@ -14,25 +15,49 @@ pub fn get_pure_literals(clause_references: &MultiMap<i32, i32>) -> Vec<i32> {
             pure_literals.push(*key);
         }
     }
+    if deterministic {
+        pure_literals.sort();
+    }
     return pure_literals;
 }
 
+/*
+A function to remove every quantifier from the prefix whose variable appears in neither polarity in the clause
+references. Literal selection removes these void quantifiers lazily as it passes them, so pruning them upfront shrinks
+the prefix before the search starts.
+
+Returns the number of quantifiers pruned.
+*/
+pub fn prune_void_quantifiers(matrix: &mut Matrix) -> usize {
+    let prefix_length = matrix.quantifier_list.len();
+    let clause_references = &matrix.clause_references;
+    matrix.quantifier_list.retain(|quantifier| clause_references.contains_key(&quantifier.literal) || clause_references.contains_key(&-quantifier.literal));
+    return prefix_length - matrix.quantifier_list.len();
+}
+
 /*
 A function to will remove all pure literals from a given clause database, updating clause references where necessary.
 
+A pure existential literal is set true, satisfying and removing every clause it appears in. A pure universal literal is 
+set false, as this keeps the clauses hardest to satisfy, so it is only removed from the clauses it appears in. 
+
+Note: The quantifier type is taken from the variable quantification rather than the quantifier prefix, as a universal
+variable that is no longer in the prefix must never be treated as existential.
+
 Returns a list of unit literals detected during pure literal removal.
 */
 pub fn remove_pure_literals(matrix: &mut Matrix, pure_literals: Vec<i32>) -> Vec<i32> {
     let mut new_unit_literals = Vec::new();
     for literal in pure_literals {
-        let (quantifier_type, quantifier_position) = get_quantifier_type(&matrix.quantifier_list, literal);
+        let (_prefix_quantifier_type, quantifier_position) = get_quantifier_type(&matrix.quantifier_list, literal);
         if !quantifier_position.is_none() {
             matrix.quantifier_list.remove(quantifier_position.unwrap());
         }
+        let is_existential = matrix.variable_quantification.get(&literal.abs()).expect("Variable quantification missing literal").is_existential();
         let clause_references = matrix.clause_references.get_vec(&literal);
         if !clause_references.is_none() {
             for clause_index in clause_references.unwrap().clone() {
-                if quantifier_type.eq(&QuantifierType::Existential) {
+                if is_existential {
                     matrix.clause_set.clause_list[clause_index as usize].is_removed = true;
                     matrix.clause_set.decrement_counter();
                     matrix.clause_references.retain(|&_key, &value| { value != clause_index});
@@ -41,7 +66,7 @@ pub fn remove_pure_literals(matrix: &mut Matrix, pure_literals: Vec<i32>) -> Vec
                         return new_unit_literals;
                     }
                 } else {
-                    matrix.clause_set.clause_list[clause_index as usize].remove_a_literal(literal); // Only remove from a_literals as I know it is universally quantified.
+                    matrix.clause_set.clause_list[clause_index as usize].remove_literal(literal, &matrix.variable_quantification);
                     matrix.clause_references.remove(&literal);
 
                     // Detect literals for Universal Reduction and remove them
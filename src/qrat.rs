@@ -0,0 +1,225 @@
+use std::{fs, collections::{HashMap, HashSet}};
+use multimap::MultiMap;
+
+use crate::{data_structures::Variable, util::{get_output_pathname, read_instance_name}};
+
+/*
+An enum to store a step of a QRAT proof.
+
+Add => A clause was added, with its pivot literal first. The empty clause ends a proof of unsatisfiability.
+Delete => A clause was deleted from the clause database.
+UniversalReduction => A universal literal was removed from a clause by universal reduction, with the literal first.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub enum QratStep {
+    Add(Vec<i32>),
+    Delete(Vec<i32>),
+    UniversalReduction(Vec<i32>),
+}
+
+impl QratStep {
+    /*
+    Creates the step adding a clause, moving the pivot to the front as QRAT reads the first literal of an addition as
+    its pivot. The other literals keep their order.
+    */
+    pub fn addition(literals: &[i32], pivot: Option<i32>) -> Self {
+        let mut ordered_literals = Vec::with_capacity(literals.len());
+        if !pivot.is_none() && literals.contains(&pivot.unwrap()) {
+            ordered_literals.push(pivot.unwrap());
+        }
+        ordered_literals.extend(literals.iter().filter(|&&literal| Some(literal) != pivot));
+        return QratStep::Add(ordered_literals);
+    }
+
+    /*
+    Creates the step removing a universal literal from a clause, moving the literal to the front as QRAT reads the first
+    literal of a universal reduction as the literal removed.
+    */
+    pub fn universal_reduction(literals: &[i32], universal_literal: i32) -> Self {
+        let mut ordered_literals = vec![universal_literal];
+        ordered_literals.extend(literals.iter().filter(|&&literal| literal != universal_literal));
+        return QratStep::UniversalReduction(ordered_literals);
+    }
+
+    /*
+    A function to format the step as a line of a QRAT proof, the literals terminated by 0 with deletions prefixed by d
+    and universal reductions by u.
+    */
+    pub fn format(&self) -> String {
+        let (prefix, literals) = match self {
+            QratStep::Add(literals) => ("", literals),
+            QratStep::Delete(literals) => ("d ", literals),
+            QratStep::UniversalReduction(literals) => ("u ", literals),
+        };
+        let mut line = String::from(prefix);
+        for literal in literals {
+            line.push_str(&format!("{} ", literal));
+        }
+        line.push('0');
+        return line;
+    }
+}
+
+/*
+A function to format the steps of a proof of unsatisfiability as a QRAT proof. The empty clause is added at the end if
+the steps don't already end with it, as an unsatisfiable result may be reached without learning it, e.g. when
+pre-processing finds a contradiction.
+*/
+pub fn format_qrat_proof(steps: &[QratStep]) -> String {
+    let mut lines: Vec<String> = steps.iter().map(|step| step.format()).collect();
+    if steps.last() != Some(&QratStep::Add(Vec::new())) {
+        lines.push(QratStep::Add(Vec::new()).format());
+    }
+    return lines.join("\n") + "\n";
+}
+
+/*
+A function to write the QRAT proof of an unsatisfiable solve to "output-<instance>.qrat" in the output directory,
+where the instance names of a conjunction of several instances are joined with "+".
+
+Returns the path of the written file.
+*/
+pub fn write_qrat_proof(filenames: &[String], steps: &[QratStep], output_dir: &str) -> String {
+    let instance_name = filenames.iter().map(read_instance_name).collect::<Vec<String>>().join("+");
+    let pathname = get_output_pathname(output_dir, &format!("{}.qrat", instance_name));
+    fs::write(&pathname, format_qrat_proof(steps)).expect("Unable to write file");
+    return pathname;
+}
+
+/*
+A struct for storing the clauses of an instance while a QRAT proof of it is checked. Clauses are looked up by their
+literals in sorted order, as a step may list the literals of a clause in any order.
+
+- clauses stores every clause added so far, with none in place of those since deleted or reduced.
+- clause_indices stores the indices of the current clauses with the given sorted literals.
+- literal_references stores the indices of the clauses each literal has appeared in.
+*/
+struct ProofClauses {
+    clauses: Vec<Option<Vec<i32>>>,
+    clause_indices: HashMap<Vec<i32>, Vec<usize>>,
+    literal_references: HashMap<i32, Vec<usize>>,
+}
+
+impl ProofClauses {
+    /*
+    Creates a new ProofClauses data structure from the clauses of an instance.
+    */
+    fn new(clauses: &[Vec<i32>]) -> Self {
+        let mut proof_clauses = ProofClauses { clauses: Vec::new(), clause_indices: HashMap::new(), literal_references: HashMap::new() };
+        for literals in clauses {
+            proof_clauses.add(literals);
+        }
+        return proof_clauses;
+    }
+
+    /*
+    A function to add a clause.
+    */
+    fn add(&mut self, literals: &[i32]) {
+        let mut sorted_literals = literals.to_vec();
+        sorted_literals.sort();
+        sorted_literals.dedup();
+        let index = self.clauses.len();
+        for literal in &sorted_literals {
+            self.literal_references.entry(*literal).or_default().push(index);
+        }
+        self.clause_indices.entry(sorted_literals.clone()).or_default().push(index);
+        self.clauses.push(Some(sorted_literals));
+    }
+
+    /*
+    A function to delete one copy of a clause.
+
+    Returns false if there is no clause with the given literals.
+    */
+    fn delete(&mut self, literals: &[i32]) -> bool {
+        let mut sorted_literals = literals.to_vec();
+        sorted_literals.sort();
+        sorted_literals.dedup();
+        let indices = self.clause_indices.get_mut(&sorted_literals);
+        if indices.is_none() || indices.as_ref().unwrap().is_empty() { return false };
+        let index = indices.unwrap().pop().unwrap();
+        self.clauses[index] = None;
+        return true;
+    }
+
+    /*
+    A function to check if a clause is an asymmetric tautology, i.e. if unit propagation over the current clauses 
+    reaches a conflict once every literal of the clause is set false.
+    */
+    fn is_asymmetric_tautology(&self, literals: &[i32]) -> bool {
+        let mut true_literals: HashSet<i32> = HashSet::new();
+        let mut propagation_queue = Vec::new();
+        for literal in literals {
+            if true_literals.contains(literal) { return true };
+            if true_literals.insert(-literal) { propagation_queue.push(-literal) };
+        }
+        // Unit clauses propagate without any literal set false, and an empty clause is a conflict on its own.
+        for literals in self.clauses.iter().flatten().filter(|literals| literals.len() <= 1) {
+            if literals.is_empty() || true_literals.contains(&-literals[0]) { return true };
+            if true_literals.insert(literals[0]) { propagation_queue.push(literals[0]) };
+        }
+        while let Some(true_literal) = propagation_queue.pop() {
+            let references = self.literal_references.get(&-true_literal);
+            if references.is_none() { continue };
+            for index in references.unwrap() {
+                let clause = self.clauses[*index].as_ref();
+                if clause.is_none() || clause.unwrap().iter().any(|literal| true_literals.contains(literal)) { continue };
+                let mut unassigned_literals = clause.unwrap().iter().filter(|literal| !true_literals.contains(&-**literal));
+                let unit_literal = unassigned_literals.next();
+                if unit_literal.is_none() { return true };
+                if unassigned_literals.next().is_none() {
+                    true_literals.insert(*unit_literal.unwrap());
+                    propagation_queue.push(*unit_literal.unwrap());
+                }
+            }
+        }
+        return false;
+    }
+}
+
+/*
+A function to check a QRAT proof of unsatisfiability against the clauses of an instance. An added clause must be an 
+asymmetric tautology, a universal reduction must remove a universal literal quantified inside every existential literal
+of a current clause, and a deleted clause must be a current clause. The solver only adds asymmetric tautologies, so the
+resolution asymmetric tautology check of QRAT isn't made. Unit propagation over the clauses left at the end must reach a
+conflict, so that the empty clause can be added.
+
+Returns an error describing the first step that doesn't check, if any.
+*/
+pub fn check_qrat_proof(clauses: &[Vec<i32>], variable_quantification: &MultiMap<i32, Variable>, steps: &[QratStep]) -> Result<(), String> {
+    let mut proof_clauses = ProofClauses::new(clauses);
+    for (step_index, step) in steps.iter().enumerate() {
+        let is_valid = match step {
+            QratStep::Add(literals) => {
+                let is_asymmetric_tautology = proof_clauses.is_asymmetric_tautology(literals);
+                proof_clauses.add(literals);
+                is_asymmetric_tautology
+            },
+            QratStep::Delete(literals) => proof_clauses.delete(literals),
+            QratStep::UniversalReduction(literals) => {
+                let universal_literal = literals[0];
+                let universal_variable = variable_quantification.get(&universal_literal.abs());
+                let is_reducible = !universal_variable.is_none() && universal_variable.unwrap().is_universal()
+                    && literals[1..].iter().all(|literal| {
+                        let variable = variable_quantification.get(&literal.abs());
+                        return variable.is_none() || !variable.unwrap().is_existential() || variable.unwrap().q_level < universal_variable.unwrap().q_level;
+                    });
+                if is_reducible && proof_clauses.delete(literals) {
+                    proof_clauses.add(&literals[1..]);
+                    true
+                } else {
+                    false
+                }
+            },
+        };
+        if !is_valid {
+            return Err(format!("QRAT proof step {} does not check: {}", step_index + 1, step.format()));
+        }
+    }
+    // The empty clause is added when the proof is written if the steps don't already end with it.
+    if !proof_clauses.is_asymmetric_tautology(&[]) {
+        return Err(String::from("QRAT proof does not derive the empty clause"));
+    }
+    return Ok(());
+}
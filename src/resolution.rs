@@ -1,7 +1,7 @@
-use std::collections::HashSet;
+use std::{collections::{HashMap, HashSet}, sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, mpsc}, thread, time::{Duration, Instant}};
 use multimap::MultiMap;
 
-use crate::{data_structures::{Matrix, Clause, QuantifierType}, util::convert_literals_to_clause};
+use crate::{data_structures::{Matrix, Clause, ResolutionConfig, Variable}, universal_reduction::detect_universal_literal};
 
 /*
 A function to perform iterative pre-resolution on the clause database, adding resolved clauses to the original clause
@@ -10,9 +10,12 @@ database according to the pre-resolution hyperparameter configuration.
 Note: original_clause_list is passed in when the solver type is CDCL.
 */
 pub fn pre_resolution(matrix: &mut Matrix, original_clause_list: &mut Vec<Clause>) {
+    // Adding clauses to a solved clause database would overwrite its result, and there is nothing to resolve on without a prefix.
+    if matrix.check_solved() || matrix.quantifier_list.is_empty() { return };
     let mut clause_hashtable = HashSet::new();
     clause_hashtable.extend(matrix.clause_set.clause_list.clone());
     let resolution_config = matrix.config.pre_resolution.1.clone();
+    let resolution_timer = Instant::now();
 
     let clause_list = &mut matrix.clause_set.clause_list.clone();
     let clause_references = &mut matrix.clause_references.clone();
@@ -20,53 +23,210 @@ pub fn pre_resolution(matrix: &mut Matrix, original_clause_list: &mut Vec<Clause
 
     let resolved_clauses_cap = (matrix.clause_set.clause_list.len() as f32 * resolution_config.max_ratio) as usize;
     let resolutions_per_literal = (matrix.clause_set.clause_list.len() as f32 * resolution_config.min_ratio) as usize / matrix.quantifier_list.len();
+    let existential_literals: Vec<i32> = matrix.quantifier_list.iter()
+                                            .filter(|quantifier| quantifier.is_existential() && resolution_config.resolves_on_level(quantifier.q_level))
+                                            .map(|quantifier| quantifier.literal)
+                                            .collect();
     for iteration in 0..resolution_config.iterations {
         let mut resolved_clauses = Vec::new();
-        for quantifier in &matrix.quantifier_list {
-            let mut resolved_clauses_for_literal = 0;
-            if quantifier.q_type.eq(&QuantifierType::Existential) {
-                let literal = quantifier.literal;
-                if clause_references.contains_key(&literal) && clause_references.contains_key(&-literal) {
-                    let pos_references = clause_references.get_vec(&literal).unwrap();
-                    let neg_references = clause_references.get_vec(&-literal).unwrap();
-                    for p_ref in pos_references {
-                        let clause_1 = &clause_list[*p_ref as usize];
-                        for n_ref in neg_references {
-                            let clause_2 = &clause_list[*n_ref as usize];
-                            let resolution = resolve(clause_1.clone().get_literal_list(), clause_2.clone().get_literal_list(), literal);
-                            if resolution.is_none() {
-                                continue;
-                            } else {
-                                let resolved_literals = resolution.unwrap();
-                                let resolved_clause = convert_literals_to_clause(&matrix.variable_quantification, &matrix.quantification_order, &resolved_literals);
-                                if !clause_hashtable.contains(&resolved_clause) {
-                                    clause_hashtable.insert(resolved_clause.clone());
-                                    resolved_clauses.push(resolved_clause);
-                                    resolved_clauses_for_literal += 1;
-                                    if resolved_literals.len() > resolution_config.repeat_above {
-                                        continue;
-                                    }
-                                } else {
-                                    continue;
-                                }
-                            }
-                            if resolved_clauses_for_literal >= resolutions_per_literal { break; }
-                        }
-                        if resolved_clauses_for_literal >= resolutions_per_literal { break; }
-                    }
+        if resolution_time_exceeded(resolution_timer, &resolution_config) { break };
+        // The absolute cap holds across iterations, unlike the cap relative to the clause database.
+        let remaining_added_clauses = resolution_config.max_added_clauses - resolved_clause_database.len();
+        // Each literal is resolved on against a snapshot of the clause database taken at the start of the iteration.
+        let clause_snapshot = clause_hashtable.clone();
+        let snapshot = ResolutionSnapshot { clause_list, clause_references, clause_hashtable: &clause_snapshot, resolutions_per_literal, resolution_timer };
+        resolve_literals_parallel(matrix, &existential_literals, &snapshot, |index, candidates, is_capped| {
+            // A candidate already resolved for an earlier literal would use up part of a capped literal's quota, so the literal 
+            // is resolved on again against the up to date clause database to refill it. An uncapped literal found every 
+            // resolvent, so dropping the ones already resolved leaves the same candidates.
+            let candidates = if is_capped && candidates.iter().any(|resolved_clause| clause_hashtable.contains(resolved_clause)) {
+                let current_snapshot = ResolutionSnapshot { clause_hashtable: &clause_hashtable, ..snapshot };
+                resolve_on_literal(matrix, existential_literals[index], &current_snapshot)
+            } else {
+                candidates
+            };
+            for resolved_clause in candidates {
+                if resolved_clauses.len() >= remaining_added_clauses { break };
+                if !clause_hashtable.contains(&resolved_clause) {
+                    clause_hashtable.insert(resolved_clause.clone());
+                    resolved_clauses.push(resolved_clause);
                 }
             }
-            if resolved_clauses.len() > resolved_clauses_cap { break; }
-        }
+            // No more literals are merged, or resolved on, once a cap has been reached.
+            return resolved_clauses.len() <= resolved_clauses_cap && resolved_clauses.len() < remaining_added_clauses;
+        });
 
         // No need to continue resolution if we didnt produce any new resolved clauses
         if resolved_clauses.is_empty() { break };
         resolved_clause_database.extend(resolved_clauses.clone());
+        if resolved_clause_database.len() >= resolution_config.max_added_clauses { break };
         if iteration < resolution_config.iterations - 1 { // i.e it is not the last iteration.
             add_resolved_clauses_independently(clause_list, clause_references, resolved_clauses);
         }
     }
-    add_resolved_clauses(matrix, resolved_clause_database, resolution_config.max_clause_length, original_clause_list);
+    let first_resolved_index = matrix.clause_set.clause_list.len();
+    add_resolved_clauses(matrix, resolved_clause_database, &resolution_config, original_clause_list);
+    if resolution_config.subsumption {
+        remove_subsumed_clauses(matrix, first_resolved_index);
+    }
+}
+
+/*
+A function to resolve on each of the given literals in parallel. Each literal's resolutions are independent given a 
+snapshot of the clause list and clause references, so one thread per available core takes the next literal to resolve on
+until none are left.
+
+The candidate resolved clauses for each literal are passed to merge_candidates, along with the literal's index and
+whether its quota stopped the resolution early, in the same order as the given literals. No more literals are resolved
+on once merge_candidates returns false.
+*/
+pub fn resolve_literals_parallel(matrix: &Matrix, literals: &[i32], snapshot: &ResolutionSnapshot, mut merge_candidates: impl FnMut(usize, Vec<Clause>, bool) -> bool) {
+    if literals.is_empty() { return };
+    let next_literal = AtomicUsize::new(0);
+    let is_stopped = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..resolution_thread_count().min(literals.len()) {
+            let (sender, next_literal, is_stopped) = (sender.clone(), &next_literal, &is_stopped);
+            scope.spawn(move || {
+                while !is_stopped.load(Ordering::Relaxed) {
+                    let index = next_literal.fetch_add(1, Ordering::Relaxed);
+                    if index >= literals.len() { break };
+                    let (candidates, is_capped) = resolve_on_literal_detailed(matrix, literals[index], snapshot);
+                    if sender.send((index, (candidates, is_capped))).is_err() { break };
+                }
+            });
+        }
+        drop(sender);
+        // Candidates can arrive out of order, so they are held until every earlier literal has been merged.
+        let mut pending_candidates = HashMap::new();
+        let mut next_merged = 0;
+        for (index, candidates) in receiver {
+            pending_candidates.insert(index, candidates);
+            while let Some((candidates, is_capped)) = pending_candidates.remove(&next_merged) {
+                if !merge_candidates(next_merged, candidates, is_capped) {
+                    is_stopped.store(true, Ordering::Relaxed);
+                    return;
+                }
+                next_merged += 1;
+            }
+        }
+    });
+}
+
+/*
+A function to resolve every clause containing the given literal with every clause containing its complement, until
+resolutions_per_literal new resolved clauses have been found. Resolved clauses longer than repeat_above don't count 
+towards stopping. Clauses already in clause_hashtable, or already resolved for this literal, are skipped. No resolution
+is performed if the pre-resolution time budget has already been exceeded. Universal reduction is applied to each
+resolved clause when universal_reduction_in_presolve is enabled.
+
+Returns the list of new resolved clauses for the literal, ranked by their ResolutionInfo so that the shortest resolved
+clauses, with the fewest universal literals, are added first when the number of resolved clauses is capped.
+*/
+pub fn resolve_on_literal(matrix: &Matrix, literal: i32, snapshot: &ResolutionSnapshot) -> Vec<Clause> {
+    return resolve_on_literal_detailed(matrix, literal, snapshot).0;
+}
+
+/*
+A function to resolve on a literal as above, also reporting whether resolutions_per_literal stopped the resolution 
+before every pair of clauses had been resolved.
+
+Returns (the list of new resolved clauses for the literal, whether the quota was reached).
+*/
+pub fn resolve_on_literal_detailed(matrix: &Matrix, literal: i32, snapshot: &ResolutionSnapshot) -> (Vec<Clause>, bool) {
+    let (clause_list, clause_references, clause_hashtable) = (snapshot.clause_list, snapshot.clause_references, snapshot.clause_hashtable);
+    let resolutions_per_literal = snapshot.resolutions_per_literal;
+    let mut resolved_clauses = Vec::new();
+    let resolution_config = &matrix.config.pre_resolution.1;
+    if resolution_time_exceeded(snapshot.resolution_timer, resolution_config) { return (Vec::new(), false) };
+    let mut resolved_clauses_for_literal = 0;
+    let mut literal_hashtable = HashSet::new();
+    if clause_references.contains_key(&literal) && clause_references.contains_key(&-literal) {
+        let pos_references = clause_references.get_vec(&literal).unwrap();
+        let neg_references = clause_references.get_vec(&-literal).unwrap();
+        for p_ref in pos_references {
+            let clause_1 = &clause_list[*p_ref as usize];
+            for n_ref in neg_references {
+                let clause_2 = &clause_list[*n_ref as usize];
+                let resolution = resolve_detailed(clause_1.literals(), clause_2.literals(), literal, &matrix.variable_quantification);
+                if resolution.is_none() {
+                    continue;
+                } else {
+                    let (resolved_literals, mut info) = resolution.unwrap();
+                    let mut resolved_clause = Clause::new(&resolved_literals, &matrix.variable_quantification, &matrix.quantification_order);
+                    if resolution_config.universal_reduction_in_presolve {
+                        let universal_literals = detect_universal_literal(&resolved_clause, &matrix.variable_quantification);
+                        info.length -= universal_literals.len();
+                        info.universal_literal_count -= universal_literals.len();
+                        resolved_clause.remove_a_literals(universal_literals);
+                        // An empty resolved clause can't be added to the clause database, so it is dropped.
+                        if resolved_clause.is_empty() { continue };
+                    }
+                    if !clause_hashtable.contains(&resolved_clause) && !literal_hashtable.contains(&resolved_clause) {
+                        literal_hashtable.insert(resolved_clause.clone());
+                        let is_long = info.length > resolution_config.repeat_above;
+                        resolved_clauses.push((resolved_clause, info));
+                        resolved_clauses_for_literal += 1;
+                        if is_long {
+                            continue;
+                        }
+                    } else {
+                        continue;
+                    }
+                }
+                if resolved_clauses_for_literal >= resolutions_per_literal { break; }
+            }
+            if resolved_clauses_for_literal >= resolutions_per_literal { break; }
+        }
+    }
+    let is_capped = resolved_clauses_for_literal >= resolutions_per_literal;
+    resolved_clauses.sort_by_key(|(_resolved_clause, info)| (info.length, info.universal_literal_count));
+    return (resolved_clauses.into_iter().map(|(resolved_clause, _info)| resolved_clause).collect(), is_capped);
+}
+
+/*
+A function to get the number of threads to resolve on literals with, one for each available core.
+*/
+pub fn resolution_thread_count() -> usize {
+    return thread::available_parallelism().map(|count| count.get()).unwrap_or(1);
+}
+
+/*
+A function to check whether pre-resolution has run for longer than the max_duration_ms time budget.
+*/
+pub fn resolution_time_exceeded(resolution_timer: Instant, resolution_config: &ResolutionConfig) -> bool {
+    return resolution_timer.elapsed() > Duration::from_millis(resolution_config.max_duration_ms as u64);
+}
+
+/*
+A struct to store the state that each literal is resolved on against during an iteration of pre-resolution.
+
+- clause_list stores the clause database, including the resolved clauses of earlier iterations.
+- clause_references stores the indices of the clauses in clause_list that contain each literal.
+- clause_hashtable stores the clauses that are skipped when resolved again.
+- resolutions_per_literal stores the number of new resolved clauses after which resolving on a literal stops.
+- resolution_timer stores when pre-resolution started, to check the time budget against.
+*/
+#[derive(Clone, Copy)]
+pub struct ResolutionSnapshot<'a> {
+    pub clause_list: &'a Vec<Clause>,
+    pub clause_references: &'a MultiMap<i32, i32>,
+    pub clause_hashtable: &'a HashSet<Clause>,
+    pub resolutions_per_literal: usize,
+    pub resolution_timer: Instant,
+}
+
+/*
+A struct to store information about a resolvent, used to rank or filter resolvents during pre-resolution.
+
+- length stores the number of literals in the resolvent.
+- universal_literal_count stores the number of universally quantified literals in the resolvent.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolutionInfo {
+    pub length: usize,
+    pub universal_literal_count: usize,
 }
 
 /*
@@ -80,26 +240,45 @@ pub fn resolve(literals_list_1: Vec<i32>, literals_list_2: Vec<i32>, literal: i3
     resolved_literals.remove(&literal);
     resolved_literals.remove(&-literal);
     let mut literals_checked = HashSet::new();
-    let mut invalid = false;
     for x in resolved_literals.iter() {
         if literals_checked.contains(&-x) {
-            invalid = true;
-            break;
+            return None;
         } else {
             literals_checked.insert(*x);
         }
     }
-    return if invalid { None } else { Some(Vec::from_iter(resolved_literals)) };
+    return Some(Vec::from_iter(resolved_literals));
+}
+
+/*
+A function to perform Q-Resolution on a literal for two given clause literal lists, as in resolve.
+
+Returns the resolved literals along with the ResolutionInfo of the resolvent, or None if the resolution is invalid.
+*/
+pub fn resolve_detailed(literals_list_1: Vec<i32>, literals_list_2: Vec<i32>, literal: i32, variable_quantification: &MultiMap<i32, Variable>) -> Option<(Vec<i32>, ResolutionInfo)> {
+    let resolution = resolve(literals_list_1, literals_list_2, literal);
+    if resolution.is_none() { return None };
+    let resolved_literals = resolution.unwrap();
+    let universal_literal_count = resolved_literals.iter()
+                                    .filter(|x| variable_quantification.get(&x.abs()).map_or(false, |variable| variable.is_universal()))
+                                    .count();
+    let info = ResolutionInfo {
+        length: resolved_literals.len(),
+        universal_literal_count,
+    };
+    return Some((resolved_literals, info));
 }
 
 /*
 A function to add a list of clauses to the clause database of a given ParsedMatrix/Problem. It will update necessary 
-variable states such as clause references.
+variable states such as clause references. Clauses longer than the maximum clause length for the quantification level
+of their innermost literal are not added.
 */
-pub fn add_resolved_clauses(matrix: &mut Matrix, resolved_clauses: Vec<Clause>, max_clause_length: usize, original_clause_list: &mut Vec<Clause>) {
+pub fn add_resolved_clauses(matrix: &mut Matrix, resolved_clauses: Vec<Clause>, resolution_config: &ResolutionConfig, original_clause_list: &mut Vec<Clause>) {
     let mut clause_index = matrix.clause_set.clause_list.len() as i32 - 1;
     for clause in resolved_clauses {
-        if clause.get_clause_length() > max_clause_length { continue }
+        let innermost_level = clause.literals().iter().filter_map(|literal| matrix.level_of(*literal)).max().unwrap_or(1);
+        if clause.get_clause_length() > resolution_config.max_clause_length_at_level(innermost_level) { continue }
         matrix.clause_set.clause_list.push(clause.clone());
         matrix.clause_set.clause_count += 1;
         if !original_clause_list.is_empty() {
@@ -125,4 +304,38 @@ pub fn add_resolved_clauses_independently(clause_list: &mut Vec<Clause>, clause_
             clause_references.insert(literal, clause_index as i32);
         }
     }
-}
\ No newline at end of file
+}
+
+/*
+A function to mark clauses that are subsumed by a shorter resolved clause as removed, starting from the resolved clauses
+at first_resolved_index. Soft clauses are kept as removing them would change the weight of an assignment. The clause 
+references are refreshed so that they no longer reference removed clauses.
+*/
+pub fn remove_subsumed_clauses(matrix: &mut Matrix, first_resolved_index: usize) {
+    let clause_list = &mut matrix.clause_set.clause_list;
+    let mut removed_clause_count = 0;
+    for resolved_index in first_resolved_index..clause_list.len() {
+        if clause_list[resolved_index].is_removed { continue };
+        let resolved_clause_length = clause_list[resolved_index].get_clause_length();
+        for index in 0..clause_list.len() {
+            let clause = &clause_list[index];
+            if index == resolved_index || clause.is_removed || clause.is_soft() || clause.get_clause_length() <= resolved_clause_length {
+                continue;
+            }
+            if clause_list[resolved_index].is_subset_of(clause) {
+                clause_list[index].is_removed = true;
+                removed_clause_count += 1;
+            }
+        }
+    }
+    matrix.clause_set.decrease_counter(removed_clause_count);
+
+    let mut clause_references = MultiMap::new();
+    for (index, clause) in matrix.clause_set.clause_list.iter().enumerate() {
+        if clause.is_removed { continue };
+        for literal in clause.literals() {
+            clause_references.insert(literal, index as i32);
+        }
+    }
+    matrix.clause_references = clause_references;
+}
@@ -0,0 +1,129 @@
+use std::fs;
+
+use crate::{log_normal, data_structures::{Statistics, Verbosity}, util::{get_output_pathname, read_instance_name}};
+
+/*
+A struct to store the statistics of many runs summed together, so they can be averaged. The decision bound isn't
+summed, so it is always None in the total.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatisticsSummary {
+    pub run_count: usize,
+    pub total: Statistics,
+}
+
+impl StatisticsSummary {
+    /*
+    Creates an empty summary.
+    */
+    pub fn new() -> Self {
+        StatisticsSummary { run_count: 0, total: Statistics::new() }
+    }
+
+    /*
+    A function to add the statistics of a run to the summary.
+    */
+    pub fn add(&mut self, statistics: &Statistics) {
+        self.run_count += 1;
+        self.total.propagation_count += statistics.propagation_count;
+        self.total.backtrack_count += statistics.backtrack_count;
+        self.total.learned_clause_count += statistics.learned_clause_count;
+        self.total.conflict_count += statistics.conflict_count;
+        self.total.learned_literal_total += statistics.learned_literal_total;
+        self.total.unit_propagation_removed_clauses += statistics.unit_propagation_removed_clauses;
+        self.total.universal_reduction_removed_literals += statistics.universal_reduction_removed_literals;
+        self.total.parse_reduction_removed_literals += statistics.parse_reduction_removed_literals;
+        self.total.pure_literal_removed_variables += statistics.pure_literal_removed_variables;
+        self.total.peak_clause_count += statistics.peak_clause_count;
+        self.total.max_decision_level += statistics.max_decision_level;
+        self.total.sat_tail_count += statistics.sat_tail_count;
+        self.total.decision_count += statistics.decision_count;
+    }
+
+    /*
+    A function to average a total over the runs in the summary.
+
+    Returns the average, or 0 if the summary has no runs.
+    */
+    pub fn average(&self, total: f64) -> f64 {
+        if self.run_count == 0 {
+            return 0.0;
+        }
+        return total / self.run_count as f64;
+    }
+}
+
+/*
+A function to write the statistics of a solve as JSON to "output-<instance>.statistics.json" in the output directory,
+where the instance names of a conjunction of several instances are joined with "+".
+
+Returns the path of the written file.
+*/
+pub fn write_statistics_json(filenames: &[String], statistics: &Statistics, output_dir: &str) -> String {
+    let instance_name = filenames.iter().map(read_instance_name).collect::<Vec<String>>().join("+");
+    let pathname = get_output_pathname(output_dir, &format!("{}.statistics.json", instance_name));
+    fs::write(&pathname, statistics.to_json()).expect("Unable to write file");
+    return pathname;
+}
+
+/*
+A function to sum the statistics files in a directory, i.e. the files ending in ".statistics.json". Other files are
+skipped.
+
+Returns the summary, or an error if the directory can't be read or a statistics file is invalid.
+*/
+pub fn aggregate_statistics(directory: &str) -> Result<StatisticsSummary, String> {
+    let entries = fs::read_dir(directory).map_err(|error| format!("Unable to read statistics directory {}: {}", directory, error))?;
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|error| format!("Unable to read statistics directory {}: {}", directory, error))?.path();
+        if path.is_file() && path.display().to_string().ends_with(".statistics.json") {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    let mut summary = StatisticsSummary::new();
+    for path in paths {
+        let contents = fs::read_to_string(&path).map_err(|error| format!("Unable to read {}: {}", path.display(), error))?;
+        let statistics = Statistics::from_json(&contents).map_err(|error| format!("{} in {}", error, path.display()))?;
+        summary.add(&statistics);
+    }
+    return Ok(summary);
+}
+
+/*
+A function to format a statistics summary, giving the total and average over the runs of each statistic.
+*/
+pub fn format_statistics_summary(summary: &StatisticsSummary) -> String {
+    let total = &summary.total;
+    let statistics = [
+        ("Propagations", total.propagation_count as f64),
+        ("Backtracks", total.backtrack_count as f64),
+        ("Learned clauses", total.learned_clause_count as f64),
+        ("Conflicts", total.conflict_count as f64),
+        ("Learned literals", total.learned_literal_total as f64),
+        ("Unit propagation removed clauses", total.unit_propagation_removed_clauses as f64),
+        ("Universal reduction removed literals", total.universal_reduction_removed_literals as f64),
+        ("Parse reduction removed literals", total.parse_reduction_removed_literals as f64),
+        ("Pure literal removed variables", total.pure_literal_removed_variables as f64),
+        ("Peak clause count", total.peak_clause_count as f64),
+        ("Max decision level", total.max_decision_level as f64),
+        ("SAT tail count", total.sat_tail_count as f64),
+        ("Decisions", total.decision_count as f64),
+    ];
+    let mut lines = vec![format!("Runs: {}", summary.run_count)];
+    for (name, value) in statistics {
+        lines.push(format!("{} -- Total: {}, Average: {:.2}", name, value, summary.average(value)));
+    }
+    return lines.join("\n");
+}
+
+/*
+A function to aggregate the statistics files in a directory, printing the total and average of each statistic.
+*/
+pub fn run_aggregate(directory: &String, verbosity: Verbosity) {
+    match aggregate_statistics(directory) {
+        Ok(summary) => log_normal!(verbosity, "{}", format_statistics_summary(&summary)),
+        Err(error) => log_normal!(verbosity, "{}", error),
+    }
+}
@@ -1,25 +1,18 @@
 #[cfg(test)]
 mod test {
+    use std::{cell::RefCell, collections::{HashMap, HashSet, BTreeMap}, fs, io::Write, rc::Rc, time::{Duration, Instant}};
+
     use multimap::MultiMap;
     use serde_json::json;
 
-    use crate::{universal_reduction::{get_universal_literals_for_reduction, remove_universal_literal, detect_universal_literal}, data_structures::{Matrix, QuantifierType, Variable, Clause, ResolutionConfig, LiteralSelection, Config, SolverType, Quantifier}, pure_literal_deletion::{get_pure_literals, remove_pure_literals}, resolution::{resolve, add_resolved_clauses, pre_resolution}, util::{convert_literals_to_clause, read_instance_name, get_unit_literals, get_quantifier_type, get_variable_state_sum, sort_literals_order}, parse_config::{read_number_json_f32, read_number_json_usize, read_number_json_i32, read_boolean_json, read_string_json, read_solver_type_json, read_literal_selection_json}, literal_selection::{select_literal, select_literal_vss}};
+    use crate::{SolveOutcome, UnknownReason, universal_reduction::{get_universal_literals_for_reduction, remove_universal_literal, detect_universal_literal, reduce_parsed_clauses}, data_structures::{Matrix, CDCLMatrix, QuantifierType, Variable, Clause, LiteralSelection, DefaultPhase, PropagationOrder, Verbosity, Config, RestartPolicy, LearningScheme, SolverType, Quantifier, Assignment, Trail, Statistics, TimeoutPhase, ClauseLiterals}, pure_literal_deletion::{get_pure_literals, remove_pure_literals, prune_void_quantifiers}, resolution::{resolve, resolve_detailed, ResolutionInfo, add_resolved_clauses, pre_resolution, resolve_literals_parallel, resolve_on_literal, resolve_on_literal_detailed, resolution_thread_count, ResolutionSnapshot}, util::{convert_literals_to_clause, read_instance_name, get_unit_literals, get_quantifier_type, get_variable_state_sum, sort_literals_order, format_trail, select_solver_type, get_clause_length_histogram, format_clause_length_histogram, read_benchmark_paths, next_random, derive_instance_seed, format_duration_ms}, parse_config::{read_number_json_f32, read_number_json_usize, read_number_json_i32, read_boolean_json, read_string_json, read_solver_type_json, read_literal_selection_json, read_fallback_literal_selection_json, read_restart_policy_json, read_learning_scheme_json, read_default_phase_json, read_propagation_order_json, read_literal_list_json, read_verbosity_json}, logging::{set_writer, reset_writer}, literal_selection::{select_literal, select_literal_vss}, maxqbf::{maxqbf, MaxQBFResult}, cdcl::{solve, Result}, dpll, verify::verify_assignment, variable_elimination::variable_elimination, certificate::format_certificate, enumerate::solve_all, statistics::{write_statistics_json, aggregate_statistics, format_statistics_summary}, compare::{read_results_csv, compare_results, ComparisonSummary, BenchmarkRecord, run_both_solvers, solvers_disagree, run_bench_directory}};
 
 
     fn config() -> Config {
         Config {
             literal_selection: LiteralSelection::Ordered,
-            pre_resolution: (false, ResolutionConfig {
-                min_ratio: 0.25,
-                max_ratio: 0.5,
-                max_clause_length: usize::MAX,
-                repeat_above: 3,
-                iterations: 1,
-            }),
-            pre_process: true,
-            universal_reduction: true,
-            pure_literal_deletion: true,
             restarts: false,
+            ..Config::default()
         }
     }
     
@@ -32,7 +25,7 @@ mod test {
     fn unsatisfiable_by_universal_reduction_test() {
         let filename = "./test_files/universal_reduction_test.qdimacs".to_string();
         let matrix = &mut Matrix::new(filename, config());
-        let universal_reductions = get_universal_literals_for_reduction(&matrix.clause_set.clause_list, &matrix.variable_quantification);
+        let universal_reductions = get_universal_literals_for_reduction(&matrix.clause_set, &matrix.variable_quantification);
         for reduction in universal_reductions {
             remove_universal_literal(matrix, reduction.values, reduction.clause_index);
         }
@@ -44,7 +37,7 @@ mod test {
     */
     #[test]
     fn detect_universal_literal_test() {
-        let clause = Clause { e_literals: vec![1,2], a_literals: vec![3], is_removed: false };
+        let clause = Clause { e_literals: vec![1,2], a_literals: vec![3], is_removed: false, weight: None };
         let mut variable_quantification = MultiMap::new();
         variable_quantification.insert(1, Variable { q_type: QuantifierType::Existential, q_level: 1, value: 1 });
         variable_quantification.insert(2, Variable { q_type: QuantifierType::Existential, q_level: 1, value: 2 });
@@ -53,6 +46,39 @@ mod test {
         assert_eq!(3, detected_universal_literals_for_reduction[0]);
     }
 
+    /*
+    Tests the boundary of universal reduction - a universal literal at the same quantification level as the innermost 
+    existential literal is not reduced, as the level comparison is strict. Parsing never places existential and universal 
+    variables at the same level, so this case only arises from a hand-built prefix.
+    */
+    #[test]
+    fn detect_universal_literal_equal_level_test() {
+        let clause = Clause { e_literals: vec![1], a_literals: vec![2], is_removed: false, weight: None };
+        let mut variable_quantification = MultiMap::new();
+        variable_quantification.insert(1, Variable { q_type: QuantifierType::Existential, q_level: 1, value: 1 });
+        variable_quantification.insert(2, Variable { q_type: QuantifierType::Universal, q_level: 1, value: 2 });
+        assert_eq!(true, detect_universal_literal(&clause, &variable_quantification).is_empty());
+    }
+
+    /*
+    Tests that consecutive quantifier blocks of the same type are parsed at the same quantification level, while blocks of
+    different types are always at different levels.
+    */
+    #[test]
+    fn quantification_level_test() {
+        let filename = "./test_files/quantification_level_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let levels: Vec<Option<i32>> = (1..=6).map(|literal| matrix.level_of(literal)).collect();
+        assert_eq!(vec![Some(1), Some(1), Some(2), Some(2), Some(3), Some(4)], levels);
+        for existential in matrix.quantifier_list.iter().filter(|quantifier| quantifier.is_existential()) {
+            for universal in matrix.quantifier_list.iter().filter(|quantifier| quantifier.is_universal()) {
+                assert_ne!(existential.q_level, universal.q_level);
+            }
+        }
+        assert_eq!(vec![6], detect_universal_literal(&matrix.clause_set.clause_list[0], &matrix.variable_quantification));
+        assert_eq!(vec![-4], detect_universal_literal(&matrix.clause_set.clause_list[1], &matrix.variable_quantification));
+    }
+
     /*
     Tests that literals that can be reduced by universal reduction are removed correctly.
     */
@@ -60,7 +86,7 @@ mod test {
     pub fn remove_universal_literal_test() {
         let filename = "./test_files/universal_reduction_test2.qdimacs".to_string();
         let matrix = &mut Matrix::new(filename, config());
-        let universal_reductions = get_universal_literals_for_reduction(&matrix.clause_set.clause_list, &matrix.variable_quantification);
+        let universal_reductions = get_universal_literals_for_reduction(&matrix.clause_set, &matrix.variable_quantification);
         for reduction in universal_reductions {
             remove_universal_literal(matrix, reduction.values, reduction.clause_index);
         }
@@ -68,6 +94,33 @@ mod test {
         assert_ne!(None, clause_to_check.is_unit_clause());
         assert_eq!(vec![1], clause_to_check.e_literals);
     }
+
+    /*
+    Tests that a clause made unit by universal reduction straight after parsing is surfaced as a unit clause, with the
+    clause references of the removed literal dropped. The removed literal is counted as a parse reduction rather than a
+    pre-processing reduction, even with pre-processing disabled.
+    */
+    #[test]
+    pub fn reduce_parsed_clauses_test() {
+        let filename = "./test_files/parse_universal_reduction_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename.clone(), config());
+        let statistics = &mut Statistics::new();
+        assert_eq!(Vec::<i32>::new(), get_unit_literals(&matrix.clause_set));
+        assert_eq!(vec![1], reduce_parsed_clauses(matrix, statistics));
+        assert_eq!(1, statistics.parse_reduction_removed_literals);
+        assert_eq!(0, statistics.universal_reduction_removed_literals);
+        assert!(matrix.clause_set.clause_list[0].a_literals.is_empty());
+        assert!(!matrix.clause_references.get_vec(&2).unwrap_or(&Vec::new()).contains(&0));
+        assert_eq!(Ok(()), matrix.verify_structural_invariants());
+
+        let mut no_preprocess_config = config();
+        no_preprocess_config.pre_process = false;
+        assert_eq!(dpll::Result::SAT, dpll::solve(&mut Matrix::new(filename.clone(), no_preprocess_config.clone()), &mut Statistics::new(), Instant::now()));
+        let statistics = &mut Statistics::new();
+        assert_eq!(Result::SAT, solve(&mut CDCLMatrix::new(filename, no_preprocess_config), statistics, Instant::now()));
+        assert_eq!(1, statistics.parse_reduction_removed_literals);
+        assert_eq!(0, statistics.universal_reduction_removed_literals);
+    }
     /* END OF UNIVERSAL REDUCTION TESTS */
 
     /* START OF PURE LITERAL DELETION TESTS */
@@ -82,11 +135,41 @@ mod test {
         clause_references.insert(2, 0);
         clause_references.insert(-2, 1);
         clause_references.insert(-3, 2);
-        let pure_literals = get_pure_literals(&clause_references);
+        let pure_literals = get_pure_literals(&clause_references, false);
         assert!(pure_literals.contains(&-3));
         assert!(pure_literals.contains(&1));
     }
 
+    /*
+    Tests that pure literals are returned in sorted order when deterministic is set.
+    */
+    #[test]
+    pub fn get_pure_literals_deterministic_test() {
+        let mut clause_references = MultiMap::new();
+        for literal in [7, -5, 3, 2, -2, 9, -1] {
+            clause_references.insert(literal, 0);
+        }
+        assert_eq!(vec![-5, -1, 3, 7, 9], get_pure_literals(&clause_references, true));
+    }
+
+    /*
+    Tests that two deterministic runs on the same instance produce the same result and statistics.
+    */
+    #[test]
+    pub fn deterministic_statistics_test() {
+        let filename = "./test_files/repeated_conflict_test.qdimacs".to_string();
+        let mut deterministic_config = config();
+        deterministic_config.deterministic = true;
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            let matrix = &mut CDCLMatrix::new(filename.clone(), deterministic_config.clone());
+            let statistics = &mut Statistics::new();
+            let result = solve(matrix, statistics, Instant::now());
+            runs.push((result, statistics.clone()));
+        }
+        assert_eq!(runs[0], runs[1]);
+    }
+
     /*
     Tests that pure literals are removed correctly.
     */
@@ -94,221 +177,2027 @@ mod test {
     pub fn remove_pure_literals_test() {
         let filename = "./test_files/pure_literal_removal_test.qdimacs".to_string();
         let matrix = &mut Matrix::new(filename, config());
-        let pure_literals = get_pure_literals(&matrix.clause_references);
+        let pure_literals = get_pure_literals(&matrix.clause_references, false);
         let found_unit_literals = remove_pure_literals(matrix, pure_literals);
         assert_eq!(3, found_unit_literals[0]);
         assert_eq!(1, matrix.clause_set.clause_count);
     }
+
+    /*
+    Tests that a pure existential literal is set true, satisfying and removing every clause it appears in.
+    */
+    #[test]
+    pub fn remove_pure_existential_literal_test() {
+        let filename = "./test_files/pure_literal_existential_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let pure_literals = get_pure_literals(&matrix.clause_references, false);
+        assert_eq!(vec![2], pure_literals);
+        let found_unit_literals = remove_pure_literals(matrix, pure_literals);
+        assert_eq!(true, found_unit_literals.is_empty());
+        assert_eq!(true, matrix.clause_set.contains_empty_set());
+    }
+
+    /*
+    Tests that a pure universal literal is set false, so it is only removed from the clauses it appears in, even if the 
+    universal variable is no longer in the quantifier prefix.
+    */
+    #[test]
+    pub fn remove_pure_universal_literal_test() {
+        let filename = "./test_files/pure_literal_universal_test.qdimacs".to_string();
+        for in_prefix in [true, false] {
+            let matrix = &mut Matrix::new(filename.clone(), config());
+            if !in_prefix { matrix.quantifier_list.retain(|quantifier| quantifier.literal != 2) };
+            let pure_literals = get_pure_literals(&matrix.clause_references, false);
+            assert_eq!(vec![2], pure_literals);
+            let mut found_unit_literals = remove_pure_literals(matrix, pure_literals);
+            found_unit_literals.sort();
+            assert_eq!(vec![-1, 1], found_unit_literals);
+            assert_eq!(2, matrix.clause_set.clause_count);
+            assert_eq!(false, matrix.clause_references.contains_key(&2));
+        }
+    }
+    /*
+    Tests that pruning after pre-processing removes the quantifiers that never appeared in a clause and those emptied
+    by universal reduction, shrinking the prefix to the variables left in the clauses, and that both solvers give the 
+    same result with pruning enabled.
+    */
+    #[test]
+    pub fn prune_void_quantifiers_test() {
+        let filename = "./test_files/prune_void_quantifiers_test.qdimacs".to_string();
+        let mut prune_config = config();
+        prune_config.pure_literal_deletion = false;
+        let matrix = &mut Matrix::new(filename.clone(), prune_config.clone());
+        dpll::preprocess(matrix, &mut Statistics::new(), Instant::now());
+        assert_eq!(7, matrix.quantifier_list.len());
+        assert_eq!(4, prune_void_quantifiers(matrix));
+        assert_eq!(vec![1, 5, 6], matrix.quantifier_list.iter().map(|quantifier| quantifier.literal).collect::<Vec<i32>>());
+        assert_eq!(0, prune_void_quantifiers(matrix));
+
+        prune_config.prune_void_quantifiers = true;
+        assert_eq!(dpll::Result::SAT, dpll::solve(&mut Matrix::new(filename.clone(), prune_config.clone()), &mut Statistics::new(), Instant::now()));
+        assert_eq!(Result::SAT, solve(&mut CDCLMatrix::new(filename, prune_config), &mut Statistics::new(), Instant::now()));
+    }
+
     /* END OF PURE LITERAL DELETION TESTS */
 
     /* START OF RESOLUTION TESTS */
 
     /*
-    Tests that the resolve functionality can detect unsound resolutions.
+    Tests that the resolve functionality can detect unsound resolutions.
+    */
+    #[test]
+    pub fn invalid_resolve_test() {
+        let literals_1 = vec![1,2,3];
+        let literals_2 = vec![-1,-2,6];
+        let literal = 1;
+        let resolution = resolve(literals_1, literals_2, literal);
+        assert_eq!(true, resolution.is_none());
+    }
+
+    /*
+    Tests that the resolve functionality can perform Q-Resolution correctly.
+    */
+    #[test]
+    pub fn valid_resolve_test() {
+        let literals_1 = vec![1,2,3];
+        let literals_2 = vec![-1,4,6];
+        let literal = 1;
+        let mut resolution = resolve(literals_1, literals_2, literal).unwrap();
+        resolution.sort();
+        assert_eq!(vec![2,3,4,6], resolution);
+    }
+
+    /*
+    Tests that the detailed resolution records the length and number of universal literals of the resolvent.
+    */
+    #[test]
+    pub fn resolve_detailed_test() {
+        let filename = "./test_files/convert_literals_to_clause_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let literals_1 = vec![1,2,3];
+        let literals_2 = vec![-1,4,6,7];
+        let (mut resolution, info) = resolve_detailed(literals_1, literals_2, 1, &matrix.variable_quantification).unwrap();
+        resolution.sort();
+        assert_eq!(vec![2,3,4,6,7], resolution);
+        assert_eq!(ResolutionInfo { length: 5, universal_literal_count: 2 }, info);
+    }
+
+    /*
+    Tests that resolving two clauses returns the resolvent as a clause with the existential and universal literals split
+    and sorted by the quantifier prefix.
+    */
+    #[test]
+    pub fn clause_resolvent_with_test() {
+        let filename = "./test_files/convert_literals_to_clause_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let clause_1 = Clause::new(&[1, 3, 2], &matrix.variable_quantification, &matrix.quantification_order);
+        let clause_2 = Clause::new(&[-1, 6, 4], &matrix.variable_quantification, &matrix.quantification_order);
+        let resolution = clause_1.resolvent_with(&clause_2, 1, &matrix.quantification_order);
+        let expected_clause = Clause {
+            e_literals: vec![2, 3],
+            a_literals: vec![4, 6],
+            is_removed: false,
+            weight: None,
+        };
+        assert_eq!(false, resolution.is_none());
+        assert_eq!(expected_clause, resolution.unwrap());
+    }
+
+    /*
+    Tests that merging two clauses gives the same ordering of literals as converting their combined literals into a clause,
+    with duplicate literals removed.
+    */
+    #[test]
+    pub fn clause_merge_test() {
+        let filename = "./test_files/convert_literals_to_clause_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let clause_1 = Clause::new(&[3, 1, 6], &matrix.variable_quantification, &matrix.quantification_order);
+        let clause_2 = Clause::new(&[7, 4, 2, 3], &matrix.variable_quantification, &matrix.quantification_order);
+        let expected_clause = Clause::new(&[3, 2, 4, 1, 7, 6], &matrix.variable_quantification, &matrix.quantification_order);
+        let merged_clause = clause_1.merge(&clause_2, &matrix.quantification_order);
+        assert_eq!(vec![1, 2, 3, 7], merged_clause.e_literals);
+        assert_eq!(vec![4, 6], merged_clause.a_literals);
+        assert_eq!(expected_clause, merged_clause);
+    }
+
+    /*
+    Tests that resolving two clauses which would produce a resolvent containing a literal and its complement is invalid.
+    */
+    #[test]
+    pub fn clause_resolvent_with_invalid_test() {
+        let filename = "./test_files/convert_literals_to_clause_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let clause_1 = Clause::new(&[1, 2, 4], &matrix.variable_quantification, &matrix.quantification_order);
+        let clause_2 = Clause::new(&[-1, 3, -4], &matrix.variable_quantification, &matrix.quantification_order);
+        assert_eq!(true, clause_1.resolvent_with(&clause_2, 1, &matrix.quantification_order).is_none());
+    }
+
+    /*
+    Tests that a clause is a subset of a clause containing all of its existential and universal literals, including
+    itself, but not of a clause missing one of them or containing its complement, and that the empty clause is a subset
+    of every clause.
+    */
+    #[test]
+    pub fn clause_is_subset_of_test() {
+        let filename = "./test_files/convert_literals_to_clause_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let clause = Clause::new(&[1, -4, 3], &matrix.variable_quantification, &matrix.quantification_order);
+        let superset_clause = Clause::new(&[3, 2, -4, 1, 6], &matrix.variable_quantification, &matrix.quantification_order);
+        let missing_universal_clause = Clause::new(&[1, 2, 3, 6], &matrix.variable_quantification, &matrix.quantification_order);
+        let complement_clause = Clause::new(&[1, 3, 4], &matrix.variable_quantification, &matrix.quantification_order);
+        assert_eq!(true, clause.is_subset_of(&superset_clause));
+        assert_eq!(true, clause.is_subset_of(&clause));
+        assert_eq!(false, superset_clause.is_subset_of(&clause));
+        assert_eq!(false, clause.is_subset_of(&missing_universal_clause));
+        assert_eq!(false, clause.is_subset_of(&complement_clause));
+        assert_eq!(true, Clause::new_empty_clause().is_subset_of(&clause));
+    }
+
+    /*
+    Tests that a clashing variable is found for clauses sharing an existential or universal complement, preferring an
+    existential clash, and that clauses without a complement, or only sharing literals of the same polarity, don't clash.
+    */
+    #[test]
+    pub fn clause_shares_complement_test() {
+        let filename = "./test_files/convert_literals_to_clause_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let clause = Clause::new(&[1, -2, 4], &matrix.variable_quantification, &matrix.quantification_order);
+        let existential_clash = Clause::new(&[3, 2], &matrix.variable_quantification, &matrix.quantification_order);
+        let universal_clash = Clause::new(&[1, -4, 6], &matrix.variable_quantification, &matrix.quantification_order);
+        let both_clash = Clause::new(&[2, -4], &matrix.variable_quantification, &matrix.quantification_order);
+        let no_clash = Clause::new(&[1, 3, 4, -6], &matrix.variable_quantification, &matrix.quantification_order);
+        assert_eq!(Some(2), clause.shares_complement(&existential_clash));
+        assert_eq!(Some(2), existential_clash.shares_complement(&clause));
+        assert_eq!(Some(4), clause.shares_complement(&universal_clash));
+        assert_eq!(Some(2), clause.shares_complement(&both_clash));
+        assert_eq!(None, clause.shares_complement(&no_clash));
+        assert_eq!(None, clause.shares_complement(&clause));
+    }
+
+    /*
+    Tests that resolved clauses are added to the clause database correctly.
+    */
+    #[test]
+    pub fn add_resolved_clauses_test() {
+        let filename = "./test_files/preresolution_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let resolved_clause = Clause::new(&[2,3], &matrix.variable_quantification, &matrix.quantification_order);
+        add_resolved_clauses(matrix, vec![resolved_clause.clone()], &config().pre_resolution.1, &mut Vec::new());
+        assert_eq!(3, matrix.clause_set.clause_count);
+        assert_eq!(matrix.clause_set.clause_list[2], resolved_clause);
+    }
+
+    /*
+    Tests that a long resolved clause over deeply quantified variables is accepted when the maximum clause length grows 
+    with the quantification level, but rejected by the flat cap, whilst a shallow clause of the same length is rejected
+    by both.
+    */
+    #[test]
+    pub fn add_resolved_clauses_length_per_level_test() {
+        let filename = "./test_files/length_per_level_test.qdimacs".to_string();
+        for (length_per_level, expected_clause_count) in [(0, 2), (1, 3)] {
+            let matrix = &mut Matrix::new(filename.clone(), config());
+            let mut resolution_config = config().pre_resolution.1;
+            resolution_config.max_clause_length = 2;
+            resolution_config.length_per_level = length_per_level;
+            let shallow_clause = Clause::new(&[1, 2, 3], &matrix.variable_quantification, &matrix.quantification_order);
+            let deep_clause = Clause::new(&[1, 5, 6, 7], &matrix.variable_quantification, &matrix.quantification_order);
+            add_resolved_clauses(matrix, vec![shallow_clause, deep_clause.clone()], &resolution_config, &mut Vec::new());
+            assert_eq!(expected_clause_count, matrix.clause_set.clause_count);
+            if length_per_level > 0 {
+                assert_eq!(deep_clause, matrix.clause_set.clause_list[2]);
+            }
+        }
+    }
+
+    /*
+    Tests that pre-resolution is performed correctly.
+    */
+    #[test]
+    pub fn pre_resolution_test() {
+        let filename = "./test_files/preresolution_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        pre_resolution(matrix, &mut Vec::new());
+        assert_eq!(3, matrix.clause_set.clause_count);
+        assert_eq!(vec![2,3], matrix.clause_set.clause_list[2].literals());
+    }
+    /*
+    Tests that pre-resolution only resolves on existential literals whose quantification level is within the configured
+    level range.
+    */
+    #[test]
+    pub fn pre_resolution_level_range_test() {
+        let filename = "./test_files/resolution_level_range_test.qdimacs".to_string();
+        for ((min_level, max_level), expected_resolvents) in [((0, usize::MAX), vec![vec![4, 2], vec![4, -2]]), ((3, 3), vec![vec![4, -2]]), ((0, 1), vec![vec![4, 2]]), ((2, 2), vec![])] {
+            let mut level_config = config();
+            level_config.pre_resolution.1.min_level = min_level;
+            level_config.pre_resolution.1.max_level = max_level;
+            let matrix = &mut Matrix::new(filename.clone(), level_config);
+            pre_resolution(matrix, &mut Vec::new());
+            let resolvents: Vec<Vec<i32>> = matrix.clause_set.clause_list[4..].iter().map(|clause| clause.literals()).collect();
+            assert_eq!(expected_resolvents, resolvents);
+        }
+    }
+    /*
+    A function to collect the candidate resolved clauses for each of the given literals when resolving on them in parallel.
+    */
+    fn collect_candidates(matrix: &Matrix, literals: &[i32], clause_hashtable: &HashSet<Clause>, resolutions_per_literal: usize, resolution_timer: Instant) -> Vec<Vec<Clause>> {
+        let mut candidates_per_literal = Vec::new();
+        let snapshot = matrix_snapshot(matrix, clause_hashtable, resolutions_per_literal, resolution_timer);
+        resolve_literals_parallel(matrix, literals, &snapshot, |index, candidates, _is_capped| {
+            assert_eq!(candidates_per_literal.len(), index);
+            candidates_per_literal.push(candidates);
+            return true;
+        });
+        return candidates_per_literal;
+    }
+
+    /*
+    A function to take a snapshot of the clause database of a matrix to resolve literals on against.
+    */
+    fn matrix_snapshot<'a>(matrix: &'a Matrix, clause_hashtable: &'a HashSet<Clause>, resolutions_per_literal: usize, resolution_timer: Instant) -> ResolutionSnapshot<'a> {
+        return ResolutionSnapshot { clause_list: &matrix.clause_set.clause_list, clause_references: &matrix.clause_references, clause_hashtable, resolutions_per_literal, resolution_timer };
+    }
+
+    /*
+    Tests that resolving literals in parallel returns the resolved clauses for each literal in the given order.
+    */
+    #[test]
+    pub fn resolve_literals_parallel_test() {
+        let filename = "./test_files/preresolution_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let clause_hashtable = HashSet::from_iter(matrix.clause_set.clause_list.clone());
+        let candidates = collect_candidates(matrix, &[1, 2, 3], &clause_hashtable, 1, Instant::now());
+        assert_eq!(3, candidates.len());
+        assert_eq!(vec![2,3], candidates[0][0].literals());
+        assert_eq!(true, candidates[1].is_empty());
+        assert_eq!(true, candidates[2].is_empty());
+    }
+
+    /*
+    Measures the speedup of resolving on literals in parallel over resolving on them one after another, on a generated 
+    random instance with many clauses. It is ignored by default as it only reports timings - run it with
+    cargo test --release pre_resolution_speedup_test -- --ignored --nocapture
+    */
+    #[test]
+    #[ignore]
+    pub fn pre_resolution_speedup_test() {
+        let (variable_count, clause_count) = (400, 4000);
+        let mut state = 0x9e3779b97f4a7c15;
+        let mut instance = format!("p cnf {} {}\ne {} 0\n", variable_count, clause_count, (1..=variable_count).map(|variable| variable.to_string()).collect::<Vec<String>>().join(" "));
+        for _ in 0..clause_count {
+            let literals: Vec<String> = (0..3).map(|_| {
+                let variable = next_random(&mut state, variable_count) as i32 + 1;
+                if next_random(&mut state, 2) == 0 { variable.to_string() } else { (-variable).to_string() }
+            }).collect();
+            instance += &format!("{} 0\n", literals.join(" "));
+        }
+        let mut speedup_config = config();
+        speedup_config.pre_resolution.1.max_duration_ms = usize::MAX;
+        let matrix = &Matrix::from_string(&instance, speedup_config);
+        let literals: Vec<i32> = matrix.quantifier_list.iter().map(|quantifier| quantifier.literal).collect();
+        let clause_hashtable = HashSet::from_iter(matrix.clause_set.clause_list.clone());
+
+        let sequential_timer = Instant::now();
+        let sequential_candidates: Vec<Vec<Clause>> = literals.iter().map(|&literal| {
+            resolve_on_literal(matrix, literal, &matrix_snapshot(matrix, &clause_hashtable, usize::MAX, Instant::now()))
+        }).collect();
+        let sequential_time = sequential_timer.elapsed();
+        let parallel_timer = Instant::now();
+        let parallel_candidates = collect_candidates(matrix, &literals, &clause_hashtable, usize::MAX, Instant::now());
+        let parallel_time = parallel_timer.elapsed();
+
+        assert_eq!(sequential_candidates, parallel_candidates);
+        println!("Threads: {}, Resolved Clauses: {}, Sequential: {:?}, Parallel: {:?}, Speedup: {:.2}", resolution_thread_count(), 
+                 parallel_candidates.iter().map(|candidates| candidates.len()).sum::<usize>(), sequential_time, parallel_time, 
+                 sequential_time.as_secs_f64() / parallel_time.as_secs_f64());
+    }
+
+    /*
+    Tests that the resolved clauses for a literal are ranked by their length, and then by their number of universal 
+    literals, rather than in the order they were resolved.
+    */
+    #[test]
+    pub fn resolve_on_literal_ranking_test() {
+        let filename = "./test_files/resolution_ranking_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let clause_hashtable = HashSet::from_iter(matrix.clause_set.clause_list.clone());
+        let resolved_clauses = resolve_on_literal(matrix, 1, &matrix_snapshot(matrix, &clause_hashtable, usize::MAX, Instant::now()));
+        let resolved_literals: Vec<Vec<i32>> = resolved_clauses.iter().map(|clause| clause.literals()).collect();
+        assert_eq!(vec![vec![4, 6], vec![2, 3, 6], vec![6, 5, 7]], resolved_literals);
+    }
+
+    /*
+    Tests that a resolved clause already found for an earlier literal doesn't use up a later literal's quota - the first
+    resolvent on 2 is the resolvent on 1, so 2 only adds its own resolved clause once its quota is refilled. With a quota
+    large enough that 2 is never capped, dropping the resolvent on 1 leaves the same resolved clauses.
+    */
+    #[test]
+    pub fn pre_resolution_quota_test() {
+        let filename = "./test_files/resolution_quota_test.qdimacs".to_string();
+        for min_ratio in [1.0, 100.0] {
+            let mut quota_config = config();
+            quota_config.pre_resolution.1.min_ratio = min_ratio;
+            quota_config.pre_resolution.1.max_ratio = 2.0;
+            quota_config.pre_resolution.1.iterations = 1;
+            let matrix = &mut Matrix::new(filename.clone(), quota_config);
+            pre_resolution(matrix, &mut Vec::new());
+            assert_eq!(7, matrix.clause_set.clause_list.len());
+            let resolved_literals: Vec<Vec<i32>> = matrix.clause_set.clause_list[5..].iter().map(|clause| clause.literals()).collect();
+            assert_eq!(vec![vec![3, 4], vec![4, 5]], resolved_literals);
+        }
+    }
+
+    /*
+    Tests that resolving on a literal reports whether its quota stopped the resolution before every pair of clauses was 
+    resolved.
+    */
+    #[test]
+    pub fn resolve_on_literal_capped_test() {
+        let filename = "./test_files/resolution_quota_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let clause_hashtable = HashSet::from_iter(matrix.clause_set.clause_list.clone());
+        let (capped_clauses, is_capped) = resolve_on_literal_detailed(matrix, 2, &matrix_snapshot(matrix, &clause_hashtable, 1, Instant::now()));
+        assert_eq!((1, true), (capped_clauses.len(), is_capped));
+        let (resolved_clauses, is_capped) = resolve_on_literal_detailed(matrix, 2, &matrix_snapshot(matrix, &clause_hashtable, usize::MAX, Instant::now()));
+        assert_eq!((2, false), (resolved_clauses.len(), is_capped));
+    }
+
+    /*
+    Tests that a tiny pre-resolution time budget curtails resolution early, leaving a valid but smaller clause database.
+    */
+    #[test]
+    pub fn pre_resolution_time_budget_test() {
+        let filename = "./test_files/preresolution_test.qdimacs".to_string();
+        let mut budget_config = config();
+        budget_config.pre_resolution.1.max_duration_ms = 0;
+        let matrix = &mut Matrix::new(filename, budget_config);
+        pre_resolution(matrix, &mut Vec::new());
+        assert_eq!(2, matrix.clause_set.clause_count);
+        assert_eq!(2, matrix.clause_set.clause_list.len());
+    }
+    /*
+    Tests that a non-zero pre-resolution time budget stops resolution once it has been reached - a resolution timer started
+    within the budget still resolves, while one started longer ago than the budget returns no resolved clauses.
+    */
+    #[test]
+    pub fn pre_resolution_nonzero_time_budget_test() {
+        let filename = "./test_files/preresolution_test.qdimacs".to_string();
+        let mut budget_config = config();
+        budget_config.pre_resolution.1.max_duration_ms = 50;
+        let matrix = &mut Matrix::new(filename, budget_config);
+        let clause_hashtable = HashSet::from_iter(matrix.clause_set.clause_list.clone());
+        let within_budget = collect_candidates(matrix, &[1], &clause_hashtable, 1, Instant::now());
+        assert_eq!(vec![2,3], within_budget[0][0].literals());
+        let budget_reached = Instant::now() - Duration::from_millis(100);
+        let past_budget = collect_candidates(matrix, &[1], &clause_hashtable, 1, budget_reached);
+        assert_eq!(true, past_budget[0].is_empty());
+    }
+
+    /*
+    Tests that the absolute cap on resolved clauses holds across iterations - uncapped, each of three iterations adds six 
+    resolved clauses, while a cap of eight stops part way through the second iteration and a cap of 0 adds none.
+    */
+    #[test]
+    pub fn pre_resolution_max_added_clauses_test() {
+        let filename = "./test_files/max_added_clauses_test.qdimacs".to_string();
+        for (max_added_clauses, expected_clause_count) in [(usize::MAX, 26), (8, 16), (0, 8)] {
+            let mut cap_config = config();
+            cap_config.pre_resolution.1.min_ratio = 1.0;
+            cap_config.pre_resolution.1.max_ratio = 2.0;
+            cap_config.pre_resolution.1.iterations = 3;
+            cap_config.pre_resolution.1.max_added_clauses = max_added_clauses;
+            let matrix = &mut Matrix::new(filename.clone(), cap_config);
+            pre_resolution(matrix, &mut Vec::new());
+            assert_eq!(expected_clause_count, matrix.clause_set.clause_count);
+            assert_eq!(expected_clause_count as usize, matrix.clause_set.clause_list.len());
+        }
+    }
+
+    /*
+    Tests that original clauses subsumed by a shorter resolved clause are marked as removed after pre-resolution.
+    */
+    #[test]
+    pub fn pre_resolution_subsumption_test() {
+        let filename = "./test_files/preresolution_test.qdimacs".to_string();
+        let mut subsumption_config = config();
+        subsumption_config.pre_resolution.1.subsumption = true;
+        let matrix = &mut Matrix::new(filename, subsumption_config);
+        pre_resolution(matrix, &mut Vec::new());
+        assert_eq!(1, matrix.clause_set.clause_count);
+        assert_eq!(true, matrix.clause_set.clause_list[0].is_removed);
+        assert_eq!(true, matrix.clause_set.clause_list[1].is_removed);
+        assert_eq!(false, matrix.clause_set.clause_list[2].is_removed);
+        assert_eq!(false, matrix.clause_references.contains_key(&1));
+        assert_eq!(Some(&vec![2]), matrix.clause_references.get_vec(&3));
+    }
+
+    /*
+    Tests that resolved clauses are only universally reduced during pre-resolution when universal_reduction_in_presolve 
+    is enabled.
+    */
+    #[test]
+    pub fn pre_resolution_universal_reduction_test() {
+        let filename = "./test_files/preresolution_universal_reduction_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename.clone(), config());
+        pre_resolution(matrix, &mut Vec::new());
+        assert_eq!(3, matrix.clause_set.clause_list.len());
+        assert_eq!(vec![2], matrix.clause_set.clause_list[2].e_literals);
+        assert_eq!(vec![1], matrix.clause_set.clause_list[2].a_literals);
+
+        let mut reduction_config = config();
+        reduction_config.pre_resolution.1.universal_reduction_in_presolve = true;
+        let matrix = &mut Matrix::new(filename, reduction_config);
+        pre_resolution(matrix, &mut Vec::new());
+        assert_eq!(3, matrix.clause_set.clause_list.len());
+        assert_eq!(vec![2], matrix.clause_set.clause_list[2].e_literals);
+        assert_eq!(true, matrix.clause_set.clause_list[2].a_literals.is_empty());
+    }
+    /*
+    Tests that bounded variable elimination only eliminates the innermost existential variables, replacing their clauses
+    with the non-tautological resolvents, and that the result of the instance is unchanged.
+    */
+    #[test]
+    pub fn variable_elimination_test() {
+        let filename = "./test_files/variable_elimination_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename.clone(), config());
+        assert_eq!(vec![5, 4], variable_elimination(matrix, 0));
+        assert_eq!(vec![1, 2, 3], matrix.quantifier_list.iter().map(|quantifier| quantifier.literal).collect::<Vec<i32>>());
+        let mut clauses: Vec<Vec<i32>> = matrix.clause_set.active_clauses().map(|(_, clause)| clause.literals()).collect();
+        clauses.sort();
+        assert_eq!(vec![vec![-1, -2], vec![-1, 2, -3], vec![1, -2, 3]], clauses);
+        assert_eq!(3, matrix.clause_set.clause_count);
+
+        let mut elimination_config = config();
+        elimination_config.variable_elimination = (true, 0);
+        let expected_result = dpll::solve(&mut Matrix::new(filename.clone(), config()), &mut Statistics::new(), Instant::now());
+        let result = dpll::solve(&mut Matrix::new(filename, elimination_config), &mut Statistics::new(), Instant::now());
+        assert_eq!(expected_result, result);
+    }
+
+    /* END OF RESOLUTION TESTS */
+
+    /* START OF LITERAL SELECTION TESTS */
+
+    /*
+    Tests that the literals are selected in order and variable quantification is obtained.
+    */
+    #[test]
+    pub fn ordered_literal_selection_test_1() {
+        let filename = "./test_files/ordered_literal_selection_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let (literal, quantifier_type) = select_literal(matrix).unwrap();
+        assert_eq!(2, literal);
+        assert_eq!(QuantifierType::Existential, quantifier_type);
+    }
+
+    /*
+    Tests that the literals are selected in order and void quantifiers are ignored and removed.
+    */
+    #[test]
+    pub fn ordered_literal_selection_test_2() {
+        let filename = "./test_files/ordered_literal_selection_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        select_literal(matrix);
+        let void_quantifier = Quantifier {
+            q_type: QuantifierType::Existential,
+            literal: 1,
+            q_level: 1,
+            position: 0,
+        };
+        assert_eq!(false, matrix.quantifier_list.contains(&void_quantifier));
+    }
+
+    /*
+    Tests that ordered literal selection tries the polarity chosen by each default phase first.
+    */
+    #[test]
+    pub fn ordered_literal_selection_default_phase_test() {
+        let filename = "./test_files/default_phase_test.qdimacs".to_string();
+        for (default_phase, expected_literal) in [(DefaultPhase::Positive, 2), (DefaultPhase::Negative, -2), (DefaultPhase::Occurrence, -2)] {
+            let mut phase_config = config();
+            phase_config.default_phase = default_phase;
+            let matrix = &mut Matrix::new(filename.clone(), phase_config);
+            let (literal, quantifier_type) = select_literal(matrix).unwrap();
+            assert_eq!(expected_literal, literal);
+            assert_eq!(QuantifierType::Existential, quantifier_type);
+        }
+    }
+
+    /* 
+    Tests that the literals are selected using variable state sum and void quantifiers are ignored and removed.
+    */
+    #[test]
+    pub fn variable_state_sum_selection_test_1() {
+        let filename = "./test_files/ordered_literal_selection_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let (literal, quantifier_type) = select_literal_vss(matrix).unwrap();
+        assert_eq!(3, literal);
+        assert_eq!(QuantifierType::Existential, quantifier_type);
+
+        let void_quantifier = Quantifier {
+            q_type: QuantifierType::Existential,
+            literal: 1,
+            q_level: 1,
+            position: 0,
+        };
+        assert_eq!(false, matrix.quantifier_list.contains(&void_quantifier));
+    }
+
+    /*
+    Tests that both literal selection methods select nothing once no variable left in the quantifier prefix appears in 
+    the set of clauses, rather than panicking. Ordered selection removes the void quantifiers as it passes them, while 
+    variable state sum leaves the quantifier prefix unchanged.
+    */
+    #[test]
+    pub fn literal_selection_void_prefix_test() {
+        let filename = "./test_files/ordered_literal_selection_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        matrix.clause_references.clear();
+        let quantifier_list = matrix.quantifier_list.clone();
+        assert_eq!(None, select_literal_vss(matrix));
+        assert_eq!(quantifier_list, matrix.quantifier_list);
+        assert_eq!(None, select_literal(matrix));
+        assert_eq!(true, matrix.quantifier_list.is_empty());
+        assert_eq!(None, select_literal_vss(matrix));
+    }
+
+    /*
+    Tests that both solvers resolve the verdict from the remaining clauses, rather than panicking, when no variable left
+    in the quantifier prefix appears in them. The prefix is cut down to nothing, so the check after pre-processing 
+    decides the verdict, and to a single void quantifier, so literal selection finds nothing to select.
+    */
+    #[test]
+    pub fn void_prefix_verdict_test() {
+        let filename = "./test_files/void_prefix_test.qdimacs".to_string();
+        let mut void_config = config();
+        void_config.pre_process = false;
+        void_config.universal_reduction = false;
+        for literal_selection in [LiteralSelection::Ordered, LiteralSelection::VariableStateSum] {
+            void_config.literal_selection = literal_selection;
+            for void_quantifier in [None, Some(3)] {
+                let matrix = &mut Matrix::new(filename.clone(), void_config.clone());
+                matrix.quantifier_list.retain(|quantifier| Some(quantifier.literal) == void_quantifier);
+                let dpll_result = dpll::solve(&mut matrix.clone(), &mut Statistics::new(), Instant::now());
+                assert_eq!(dpll::Result::SAT, dpll_result);
+                let cdcl_result = solve(&mut CDCLMatrix::from_matrix(matrix.clone()), &mut Statistics::new(), Instant::now());
+                assert_eq!(Result::SAT, cdcl_result);
+            }
+        }
+    }
+
+    /*
+    Tests that both solvers find an instance unsatisfiable when pre-processing reduces it to a clause of a single 
+    universal literal, whichever order the clauses are in.
+    */
+    #[test]
+    pub fn universal_unit_clause_verdict_test() {
+        let filename = "./test_files/universal_unit_clause_test.qdimacs".to_string();
+        for seed in 0..4 {
+            let mut shuffle_config = config();
+            shuffle_config.shuffle_clauses = seed > 0;
+            shuffle_config.seed = seed;
+            let dpll_result = dpll::solve(&mut Matrix::new(filename.clone(), shuffle_config.clone()), &mut Statistics::new(), Instant::now());
+            assert_eq!(dpll::Result::UNSAT, dpll_result);
+            let cdcl_result = solve(&mut CDCLMatrix::new(filename.clone(), shuffle_config), &mut Statistics::new(), Instant::now());
+            assert_eq!(Result::UNSAT, cdcl_result);
+        }
+    }
+
+    /* END OF LITERAL SELECTION TESTS */
+
+    /* START OF UTIL TESTS */
+    /*
+    Tests that literals are sorted in the correct order according to the order they appear in the quantifier prefix.
+    */
+    #[test]
+    pub fn sort_literals_order_test() {
+        let filename = "./test_files/sort_literals_order_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let literals = vec![7,2,3,1];
+        let sorted_literals = sort_literals_order(&matrix.quantification_order.existential_literal_order, literals);
+        assert_eq!(vec![1,2,3,7], sorted_literals);
+    }
+
+    /*
+    Tests that the variable state sum value is correct.
+    */
+    #[test]
+    pub fn get_variable_state_sum_test() {
+        let filename = "./test_files/get_variable_state_sum_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let (vss, positive_sign) = get_variable_state_sum(&matrix.clause_references, 1);
+        assert_eq!(3, vss);
+        assert_eq!(true, positive_sign);
+    }
+
+    /*
+    Tests that literals are converted to a properly formatted clause within covert_literals_to_clause.
+    */
+    #[test]
+    pub fn convert_literals_to_clause_test() {
+        let filename = "./test_files/convert_literals_to_clause_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let converted_clause = convert_literals_to_clause(&matrix.variable_quantification, &matrix.quantification_order, &vec![3, 2, 4, 1]);
+        let expected_clause = Clause {
+            e_literals: vec![1, 2, 3],
+            a_literals: vec![4],
+            is_removed: false,
+            weight: None,
+        };
+        assert_eq!(expected_clause, converted_clause);
+    }
+
+    /*
+    Tests that Clause::new deduplicates the literals, splits them into existential and universal literals, and sorts 
+    them in the order they appear in the quantifier prefix.
+    */
+    #[test]
+    pub fn clause_new_test() {
+        let filename = "./test_files/convert_literals_to_clause_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let clause = Clause::new(&[3, 2, 4, 3, 1, 4], &matrix.variable_quantification, &matrix.quantification_order);
+        let expected_clause = Clause {
+            e_literals: vec![1, 2, 3],
+            a_literals: vec![4],
+            is_removed: false,
+            weight: None,
+        };
+        assert_eq!(expected_clause, clause);
+    }
+
+    /*
+    Tests that clause literals are classified as existential or universal from the whole quantifier prefix, even when a
+    clause line appears before the universal block is declared.
+    */
+    #[test]
+    pub fn interleaved_prefix_test() {
+        let filename = "./test_files/interleaved_prefix_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        assert_eq!(vec![1, 4], matrix.clause_set.clause_list[0].e_literals);
+        assert_eq!(vec![2, -3], matrix.clause_set.clause_list[0].a_literals);
+        assert_eq!(vec![-1], matrix.clause_set.clause_list[1].e_literals);
+        assert_eq!(vec![-2], matrix.clause_set.clause_list[1].a_literals);
+        assert_eq!(vec![-4], matrix.clause_set.clause_list[2].e_literals);
+        assert_eq!(vec![2, 3], matrix.clause_set.clause_list[2].a_literals);
+        assert_eq!(Some(3), matrix.level_of(4));
+        assert_eq!(3, matrix.clause_set.clause_count);
+    }
+
+    /*
+    Tests that a signed variable in a quantifier line is registered as the variable itself, in the prefix and the 
+    variable quantification, both when parsing a single instance and when merging instances.
+    */
+    #[test]
+    pub fn signed_quantifier_test() {
+        let filename = "./test_files/signed_quantifier_test.qdimacs".to_string();
+        let matrix = Matrix::new(filename.clone(), config());
+        assert_eq!(vec![1, 2, 3], matrix.quantifier_list.iter().map(|quantifier| quantifier.literal).collect::<Vec<i32>>());
+        assert_eq!(QuantifierType::Existential, matrix.variable_quantification.get(&1).unwrap().q_type);
+        assert_eq!(false, matrix.variable_quantification.contains_key(&-1));
+        assert_eq!(Some(2), matrix.level_of(3));
+        assert_eq!(vec![-1, 2], matrix.clause_set.clause_list[0].e_literals);
+        assert_eq!(vec![3], matrix.clause_set.clause_list[0].a_literals);
+        let merged_matrix = Matrix::from_files(&vec![filename.clone(), filename], config()).unwrap();
+        assert_eq!(true, matrix == merged_matrix);
+    }
+
+    /*
+    Tests that blank lines in an instance are skipped rather than ending the instance, and that the lines after a "%" 
+    terminator line are not parsed as clauses.
+    */
+    #[test]
+    pub fn terminator_test() {
+        let filename = "./test_files/terminator_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename.clone(), config());
+        assert_eq!(Some(3), matrix.level_of(3));
+        assert_eq!(3, matrix.clause_set.clause_count);
+        assert_eq!(vec![1, -3, -2], matrix.clause_set.clause_list[2].literals());
+        assert_eq!(false, matrix.clause_set.contains_empty_clause());
+        let merged_matrix = Matrix::from_files(&vec![filename.clone(), filename], config()).unwrap();
+        assert_eq!(3, merged_matrix.clause_set.clause_count);
+    }
+
+    /*
+    Tests that FIFO and LIFO propagation orders reach the same verdict for both solvers on an instance where one unit 
+    literal makes several clauses unit at once, while taking a different number of propagations to find the conflict.
+    */
+    #[test]
+    pub fn propagation_order_test() {
+        let filename = "./test_files/propagation_order_test.qdimacs".to_string();
+        let mut lifo_config = config();
+        lifo_config.propagation_order = PropagationOrder::LIFO;
+        let (fifo_statistics, lifo_statistics) = (&mut Statistics::new(), &mut Statistics::new());
+        let fifo_result = dpll::solve(&mut Matrix::new(filename.clone(), config()), fifo_statistics, Instant::now());
+        let lifo_result = dpll::solve(&mut Matrix::new(filename.clone(), lifo_config.clone()), lifo_statistics, Instant::now());
+        assert_eq!(dpll::Result::UNSAT, fifo_result);
+        assert_eq!(fifo_result, lifo_result);
+        assert_ne!(fifo_statistics.propagation_count, lifo_statistics.propagation_count);
+
+        let (fifo_statistics, lifo_statistics) = (&mut Statistics::new(), &mut Statistics::new());
+        let fifo_result = solve(&mut CDCLMatrix::new(filename.clone(), config()), fifo_statistics, Instant::now());
+        let lifo_result = solve(&mut CDCLMatrix::new(filename, lifo_config), lifo_statistics, Instant::now());
+        assert_eq!(Result::UNSAT, fifo_result);
+        assert_eq!(fifo_result, lifo_result);
+        assert_ne!(fifo_statistics.propagation_count, lifo_statistics.propagation_count);
+    }
+
+    /*
+    Tests that shuffling the clauses with a fixed seed reorders the clause list reproducibly and rebuilds the clause 
+    references, and that the shuffled instance has the same result as the unshuffled instance for both solvers, while
+    the order clauses are visited in changes the CDCL statistics.
+    */
+    #[test]
+    pub fn shuffle_clauses_test() {
+        let filename = "./test_files/shuffle_clauses_test.qdimacs".to_string();
+        let mut deterministic_config = config();
+        deterministic_config.deterministic = true;
+        let mut shuffle_config = deterministic_config.clone();
+        shuffle_config.shuffle_clauses = true;
+        shuffle_config.seed = 2;
+        let matrix = Matrix::new(filename.clone(), deterministic_config.clone());
+        let shuffled_matrix = Matrix::new(filename.clone(), shuffle_config.clone());
+        assert_ne!(matrix.clause_set.clause_list, shuffled_matrix.clause_set.clause_list);
+        assert_eq!(shuffled_matrix.clause_set.clause_list, Matrix::new(filename.clone(), shuffle_config.clone()).clause_set.clause_list);
+        for clause in &matrix.clause_set.clause_list {
+            assert_eq!(true, shuffled_matrix.clause_set.clause_list.contains(clause));
+        }
+        for (index, clause) in shuffled_matrix.clause_set.clause_list.iter().enumerate() {
+            for literal in clause.literals() {
+                assert_eq!(true, shuffled_matrix.clause_references.get_vec(&literal).unwrap().contains(&(index as i32)));
+            }
+        }
+
+        let dpll_result = dpll::solve(&mut Matrix::new(filename.clone(), deterministic_config.clone()), &mut Statistics::new(), Instant::now());
+        let shuffled_dpll_result = dpll::solve(&mut Matrix::new(filename.clone(), shuffle_config.clone()), &mut Statistics::new(), Instant::now());
+        assert_eq!(dpll_result, shuffled_dpll_result);
+        let statistics = &mut Statistics::new();
+        let cdcl_result = solve(&mut CDCLMatrix::new(filename.clone(), deterministic_config), statistics, Instant::now());
+        let shuffled_statistics = &mut Statistics::new();
+        let shuffled_cdcl_result = solve(&mut CDCLMatrix::new(filename, shuffle_config), shuffled_statistics, Instant::now());
+        assert_eq!(cdcl_result, shuffled_cdcl_result);
+        assert_ne!(statistics.propagation_count, shuffled_statistics.propagation_count);
+    }
+
+    /*
+    Tests that the seed derived from an instance name is the same across runs and differs between instances, and that
+    the configured seed is only replaced when per-instance seeds are enabled.
+    */
+    #[test]
+    pub fn derive_instance_seed_test() {
+        let instance_name = read_instance_name(&"./test_files/shuffle_clauses_test.qdimacs".to_string());
+        assert_eq!(10250440868732262206, derive_instance_seed(0, &instance_name));
+        assert_eq!(derive_instance_seed(5, &instance_name), derive_instance_seed(5, &instance_name));
+        assert_ne!(derive_instance_seed(5, &instance_name), derive_instance_seed(6, &instance_name));
+        assert_ne!(derive_instance_seed(5, &instance_name), derive_instance_seed(5, "duplicate_clause_test.qdimacs"));
+        let mut seed_config = config();
+        seed_config.seed = 5;
+        assert_eq!(5, seed_config.instance_config(&instance_name).seed);
+        seed_config.per_instance_seed = true;
+        assert_eq!(derive_instance_seed(5, &instance_name), seed_config.instance_config(&instance_name).seed);
+    }
+
+    /*
+    Tests that durations are formatted as milliseconds to three decimal places, and that reports only use milliseconds
+    when times in milliseconds are enabled.
+    */
+    #[test]
+    pub fn format_duration_ms_test() {
+        assert_eq!("0.000", format_duration_ms(Duration::ZERO));
+        assert_eq!("1500.000", format_duration_ms(Duration::from_millis(1500)));
+        assert_eq!("1.235", format_duration_ms(Duration::from_nanos(1234567)));
+        assert_eq!("0.001", format_duration_ms(Duration::from_micros(1)));
+        assert_eq!("30000.000", format_duration_ms(Duration::from_secs(30)));
+        let mut duration_config = config();
+        assert_eq!("1.5s", duration_config.format_duration(Duration::from_millis(1500)));
+        duration_config.times_in_milliseconds = true;
+        assert_eq!("1500.000ms", duration_config.format_duration(Duration::from_millis(1500)));
+    }
+
+    /*
+    Tests that clauses repeated in an instance, including with their literals reordered, are only parsed once when 
+    duplicate clauses are removed, and are all kept otherwise.
+    */
+    #[test]
+    pub fn remove_duplicate_clauses_test() {
+        let filename = "./test_files/duplicate_clause_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename.clone(), config());
+        assert_eq!(2, matrix.clause_set.clause_count);
+        assert_eq!(2, matrix.clause_set.clause_list.len());
+        assert_eq!(vec![0], matrix.clause_references.get_vec(&1).unwrap().clone());
+        assert_eq!(vec![1], matrix.clause_references.get_vec(&-3).unwrap().clone());
+
+        let mut duplicate_config = config();
+        duplicate_config.remove_duplicate_clauses = false;
+        let matrix = &mut Matrix::new(filename, duplicate_config);
+        assert_eq!(4, matrix.clause_set.clause_count);
+        assert_eq!(vec![0, 2], matrix.clause_references.get_vec(&1).unwrap().clone());
+    }
+
+    /*
+    Tests that reserving capacity from the header allocates the clause database up front without changing the parsed 
+    matrix or the result of either solver.
+    */
+    #[test]
+    pub fn reserve_hint_test() {
+        let filename = "./test_files/shuffle_clauses_test.qdimacs".to_string();
+        let mut reserve_config = config();
+        reserve_config.reserve_hint = true;
+        let matrix = Matrix::new(filename.clone(), config());
+        let reserved_matrix = Matrix::new(filename.clone(), reserve_config.clone());
+        assert_eq!(true, reserved_matrix.clause_set.clause_list.capacity() >= 7);
+        assert_eq!(true, reserved_matrix.variable_quantification.capacity() >= 5);
+        assert_eq!(true, matrix == reserved_matrix);
+        assert_eq!(matrix.clause_references, reserved_matrix.clause_references);
+
+        let dpll_result = dpll::solve(&mut Matrix::new(filename.clone(), config()), &mut Statistics::new(), Instant::now());
+        let reserved_dpll_result = dpll::solve(&mut Matrix::new(filename.clone(), reserve_config.clone()), &mut Statistics::new(), Instant::now());
+        assert_eq!(dpll_result, reserved_dpll_result);
+        let cdcl_result = solve(&mut CDCLMatrix::new(filename.clone(), config()), &mut Statistics::new(), Instant::now());
+        let reserved_cdcl_result = solve(&mut CDCLMatrix::new(filename, reserve_config), &mut Statistics::new(), Instant::now());
+        assert_eq!(cdcl_result, reserved_cdcl_result);
+    }
+
+    /*
+    Tests that two instances sharing variables are merged into their conjunction, with the variables only declared by the
+    later instance appended to the prefix, and that the conjunction is solved rather than either instance alone.
+    */
+    #[test]
+    pub fn from_files_conjunction_test() {
+        let filenames = vec!["./test_files/conjunction_base_test.qdimacs".to_string(), "./test_files/conjunction_query_test.qdimacs".to_string()];
+        let matrix = Matrix::from_files(&filenames, config()).unwrap();
+        assert_eq!(vec![1, 2, 3, 4, 5], matrix.quantifiers_sorted().iter().map(|quantifier| quantifier.literal).collect::<Vec<i32>>());
+        assert_eq!(QuantifierType::Universal, matrix.type_of(3));
+        assert_eq!(Some(3), matrix.level_of(5));
+        assert_eq!(5, matrix.clause_set.clause_count);
+        assert_eq!(None, matrix.expected_result());
+        for filename in &filenames {
+            assert_eq!(dpll::Result::SAT, dpll::solve(&mut Matrix::new(filename.clone(), config()), &mut Statistics::new(), Instant::now()));
+        }
+        assert_eq!(dpll::Result::UNSAT, dpll::solve(&mut matrix.clone(), &mut Statistics::new(), Instant::now()));
+        assert_eq!(Result::UNSAT, solve(&mut CDCLMatrix::from_matrix(matrix), &mut Statistics::new(), Instant::now()));
+    }
+
+    /*
+    Tests that merging instances that quantify the same variable as existential and universal is an error.
+    */
+    #[test]
+    pub fn from_files_conflicting_quantifier_test() {
+        let filenames = vec!["./test_files/conjunction_base_test.qdimacs".to_string(), "./test_files/conjunction_conflict_test.qdimacs".to_string()];
+        let error = Matrix::from_files(&filenames, config()).err();
+        assert_eq!(false, error.is_none());
+        assert!(error.unwrap().contains("Variable 2 is quantified inconsistently"));
+    }
+
+    /*
+    Tests that the quantification level and quantifier type of a literal are looked up from its variable, and that an 
+    unquantified variable has no level.
+    */
+    #[test]
+    pub fn level_of_type_of_test() {
+        let filename = "./test_files/get_quantifier_type_test1.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        assert_eq!(Some(1), matrix.level_of(1));
+        assert_eq!(Some(2), matrix.level_of(-5));
+        assert_eq!(Some(3), matrix.level_of(7));
+        assert_eq!(None, matrix.level_of(8));
+        assert_eq!(QuantifierType::Existential, matrix.type_of(-2));
+        assert_eq!(QuantifierType::Universal, matrix.type_of(4));
+        assert_eq!(QuantifierType::Existential, matrix.type_of(7));
+    }
+
+    /*
+    Tests that variables and quantifiers report whether they are universal or existential from their quantifier type.
+    */
+    #[test]
+    pub fn is_universal_is_existential_test() {
+        let filename = "./test_files/get_quantifier_type_test1.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        assert_eq!((false, true), (matrix.variable_of(-2).is_universal(), matrix.variable_of(-2).is_existential()));
+        assert_eq!((true, false), (matrix.variable_of(4).is_universal(), matrix.variable_of(4).is_existential()));
+        for quantifier in &matrix.quantifier_list {
+            assert_eq!(quantifier.q_type.eq(&QuantifierType::Universal), quantifier.is_universal());
+            assert_eq!(quantifier.q_type.eq(&QuantifierType::Existential), quantifier.is_existential());
+            assert_ne!(quantifier.is_universal(), quantifier.is_existential());
+        }
+    }
+
+    /*
+    Tests that the quantifier type and index is found correctly when it exists in the quantifier prefix.
+    */
+    #[test]
+    pub fn get_quantifier_type_test_1() {
+        let filename = "./test_files/get_quantifier_type_test1.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let (quantifier_type, quantifier_index) = get_quantifier_type(&matrix.quantifier_list, 1);
+        assert_eq!(QuantifierType::Existential, quantifier_type);
+        assert_eq!(false, quantifier_index.is_none());
+        assert_eq!(0, quantifier_index.unwrap());
+    }
+
+    /*
+    Tests that quantifier type existential and no index is returned for a literal not appearing in the quanitifer prefix.
+    */
+    #[test]
+    pub fn get_quantifier_type_test_2() {
+        let filename = "./test_files/get_quantifier_type_test2.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let (quantifier_type, quantifier_index) = get_quantifier_type(&matrix.quantifier_list, 4);
+        assert_eq!(QuantifierType::Existential, quantifier_type);
+        assert_eq!(true, quantifier_index.is_none());
+    }
+
+    /*
+    Tests that unit literals are found from the clause database correctly.
+    */
+    #[test]
+    pub fn get_unit_literals_test_1() {
+        let filename = "./test_files/get_unit_literals_test1.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let mut unit_literals = get_unit_literals(&matrix.clause_set);
+        unit_literals.sort();
+        assert_eq!(vec![2,4], unit_literals);
+
+    }
+
+    /*
+    Tests that when no unit literals exist, none are found.
+    */
+    #[test]
+    pub fn get_unit_literals_test_2() {
+        let filename = "./test_files/get_unit_literals_test2.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let unit_literals = get_unit_literals(&matrix.clause_set);
+        assert_eq!(true, unit_literals.is_empty());
+
+    }
+
+    /*
+    Tests that iterating over the active clauses skips removed clauses, and that removed unit clauses are not returned 
+    as unit literals.
+    */
+    #[test]
+    pub fn active_clauses_test() {
+        let filename = "./test_files/get_unit_literals_test1.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        matrix.clause_set.clause_list[0].is_removed = true;
+        matrix.clause_set.clause_list[1].is_removed = true;
+        let active_clause_indices: Vec<usize> = matrix.clause_set.active_clauses().map(|(index, _clause)| index).collect();
+        assert_eq!(vec![2, 3], active_clause_indices);
+        assert_eq!(vec![4], get_unit_literals(&matrix.clause_set));
+    }
+
+    /*
+    Tests that during running benchmarks, the instance name of a file in qdimacs form is extracted properly.
+    */
+    #[test]
+    pub fn read_instance_name_test() {
+        let file_path= "./benchmarks/castellini\\toilet_a_02_01.2.qdimacs".to_string();
+        let instance_name = read_instance_name(&file_path);
+        let expected_instance_name = "toilet_a_02_01.2.qdimacs".to_string();
+        assert_eq!(expected_instance_name, instance_name);
+    }
+
+    /*
+    Tests that the instance name is extracted properly from a file path using forward slashes.
+    */
+    #[test]
+    pub fn read_instance_name_test_2() {
+        let file_path= "./benchmarks/castellini/toilet_a_02_01.2.qdimacs".to_string();
+        let instance_name = read_instance_name(&file_path);
+        let expected_instance_name = "toilet_a_02_01.2.qdimacs".to_string();
+        assert_eq!(expected_instance_name, instance_name);
+    }
+
+    /*
+    Tests that a trail of decisions and implications is formatted with its decision levels in chronological order.
+    */
+    #[test]
+    pub fn format_trail_test() {
+        let trail = Trail(vec![
+            Assignment { value: 3, decision_level: 1, clause_responsible: None },
+            Assignment { value: -2, decision_level: 1, clause_responsible: Some(4) },
+            Assignment { value: -5, decision_level: 2, clause_responsible: None },
+        ]);
+        let expected_output = "Level 1: Decision 3\nLevel 1: Implication -2 (clause 4)\nLevel 2: Decision -5".to_string();
+        assert_eq!(expected_output, format_trail(&trail));
+    }
+
+    /*
+    Tests that an assignment distinguishes between decisions and implications, and only implications have a clause 
+    responsible.
+    */
+    #[test]
+    pub fn assignment_is_implication_test() {
+        let decision = Assignment { value: 3, decision_level: 1, clause_responsible: None };
+        let implication = Assignment { value: -2, decision_level: 1, clause_responsible: Some(4) };
+        assert_eq!(false, decision.is_implication());
+        assert_eq!(None, decision.responsible_clause());
+        assert_eq!(true, implication.is_implication());
+        assert_eq!(Some(4), implication.responsible_clause());
+    }
+
+    /*
+    Tests that the alternation depth counts the changes of quantifier type through the prefix, so a prefix with four
+    quantifier blocks has three alternations, one less than its maximum quantification level.
+    */
+    #[test]
+    pub fn alternation_depth_test() {
+        let filename = "./test_files/alternation_depth_test3.qdimacs".to_string();
+        let matrix = Matrix::new(filename, config());
+        assert_eq!(4, matrix.quantifier_list.iter().map(|quantifier| quantifier.q_level).max().unwrap());
+        assert_eq!(3, matrix.alternation_depth());
+    }
+
+    /*
+    Tests that an instance with a single quantifier alternation is routed to DPLL when the solver type is Auto.
+    */
+    #[test]
+    pub fn select_solver_type_test_1() {
+        let filename = "./test_files/alternation_depth_test1.qdimacs".to_string();
+        let matrix = Matrix::new(filename, config());
+        assert_eq!(1, matrix.alternation_depth());
+        assert_eq!(SolverType::DPLL, select_solver_type(&matrix));
+    }
+
+    /*
+    Tests that an instance with five quantifier alternations is routed to CDCL when the solver type is Auto.
+    */
+    #[test]
+    pub fn select_solver_type_test_2() {
+        let filename = "./test_files/alternation_depth_test2.qdimacs".to_string();
+        let matrix = Matrix::new(filename, config());
+        assert_eq!(5, matrix.alternation_depth());
+        assert_eq!(SolverType::CDCL, select_solver_type(&matrix));
+    }
+
+    /*
+    Tests that the most recent decision at a decision level is found in the trail, and that levels without a decision
+    return None.
+    */
+    #[test]
+    pub fn trail_last_decision_at_level_test() {
+        let trail = Trail(vec![
+            Assignment { value: 1, decision_level: 0, clause_responsible: Some(0) },
+            Assignment { value: 3, decision_level: 1, clause_responsible: None },
+            Assignment { value: -2, decision_level: 1, clause_responsible: Some(4) },
+            Assignment { value: -5, decision_level: 2, clause_responsible: None },
+        ]);
+        assert_eq!(3, trail.last_decision_at_level(1).unwrap().value);
+        assert_eq!(-5, trail.last_decision_at_level(2).unwrap().value);
+        assert_eq!(true, trail.last_decision_at_level(0).is_none());
+        assert_eq!(true, trail.last_decision_at_level(3).is_none());
+    }
+
+    /*
+    Tests that the literals assigned at a decision level are returned in chronological order.
+    */
+    #[test]
+    pub fn trail_literals_at_level_test() {
+        let trail = Trail(vec![
+            Assignment { value: 3, decision_level: 1, clause_responsible: None },
+            Assignment { value: -2, decision_level: 1, clause_responsible: Some(4) },
+            Assignment { value: -5, decision_level: 2, clause_responsible: None },
+            Assignment { value: 6, decision_level: 2, clause_responsible: Some(1) },
+        ]);
+        assert_eq!(vec![3, -2], trail.literals_at_level(1));
+        assert_eq!(vec![-5, 6], trail.literals_at_level(2));
+        assert_eq!(true, trail.literals_at_level(3).is_empty());
+    }
+
+    /*
+    Tests that quantifiers are sorted by quantification level then prefix position, and that the quantifier prefix
+    remains in this order after quantifiers are removed. Quantifiers at the same quantification level that are out of 
+    prefix order are put back into prefix order.
+    */
+    #[test]
+    pub fn quantifiers_sorted_test() {
+        let filename = "./test_files/sort_literals_order_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        assert_eq!(matrix.quantifier_list, matrix.quantifiers_sorted());
+        matrix.quantifier_list.remove(4);
+        matrix.quantifier_list.remove(1);
+        assert_eq!(matrix.quantifier_list, matrix.quantifiers_sorted());
+        matrix.quantifier_list = vec![
+            Quantifier { q_type: QuantifierType::Universal, literal: 6, q_level: 1, position: 5 },
+            Quantifier { q_type: QuantifierType::Existential, literal: 3, q_level: 0, position: 2 },
+            Quantifier { q_type: QuantifierType::Universal, literal: 4, q_level: 1, position: 3 },
+            Quantifier { q_type: QuantifierType::Existential, literal: 1, q_level: 0, position: 0 },
+            Quantifier { q_type: QuantifierType::Universal, literal: 5, q_level: 1, position: 4 },
+        ];
+        let sorted_literals: Vec<i32> = matrix.quantifiers_sorted().iter().map(|quantifier| quantifier.literal).collect();
+        assert_eq!(vec![1, 3, 4, 5, 6], sorted_literals);
+    }
+
+    /*
+    Tests that adding a clause to a constructed matrix updates the clause list, clause count, and clause references, and 
+    that clauses containing variables not in the prefix are rejected.
+    */
+    #[test]
+    pub fn matrix_add_clause_test() {
+        let filename = "./test_files/convert_literals_to_clause_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let clause_count = matrix.clause_set.clause_count;
+        let clause_index = matrix.add_clause(&[4, -3, 1]);
+        assert_eq!(Some(matrix.clause_set.clause_list.len() - 1), clause_index);
+        assert_eq!(clause_count + 1, matrix.clause_set.clause_count);
+        assert_eq!(Clause::new(&[1, -3, 4], &matrix.variable_quantification, &matrix.quantification_order), matrix.clause_set.clause_list[clause_index.unwrap()]);
+        for literal in [4, -3, 1] {
+            assert_eq!(true, matrix.clause_references.get_vec(&literal).unwrap().contains(&(clause_index.unwrap() as i32)));
+        }
+        assert_eq!(None, matrix.add_clause(&[1, 99]));
+        assert_eq!(clause_count + 1, matrix.clause_set.clause_count);
+    }
+
+    /*
+    Tests that a clause line is parsed into its literals, with or without the trailing 0.
+    */
+    #[test]
+    pub fn clause_literals_from_str_valid_test() {
+        assert_eq!(Ok(ClauseLiterals(vec![1, -2, 3])), "1 -2 3 0".parse::<ClauseLiterals>());
+        assert_eq!(Ok(ClauseLiterals(vec![1, -2, 3])), "  1 -2   3 ".parse::<ClauseLiterals>());
+        assert_eq!(Ok(ClauseLiterals(Vec::new())), "0".parse::<ClauseLiterals>());
+        let filename = "./test_files/convert_literals_to_clause_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let ClauseLiterals(literals) = "4 3 -1 0".parse().unwrap();
+        let clause = Clause::new(&literals, &matrix.variable_quantification, &matrix.quantification_order);
+        assert_eq!(vec![-1, 3], clause.e_literals);
+        assert_eq!(vec![4], clause.a_literals);
+    }
+
+    /*
+    Tests that a clause line with a non-integer literal or a 0 before the end of the line fails to parse.
+    */
+    #[test]
+    pub fn clause_literals_from_str_invalid_test() {
+        assert!("1 x 3 0".parse::<ClauseLiterals>().is_err());
+        assert!("1 2.5 0".parse::<ClauseLiterals>().is_err());
+        assert!("1 0 3 0".parse::<ClauseLiterals>().is_err());
+    }
+
+    /*
+    Tests that the size of the instance reflects the variables and clauses removed by pre-processing.
+    */
+    #[test]
+    pub fn format_instance_size_test() {
+        let filename = "./test_files/compact_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        assert_eq!("Variables: 5, Clauses: 5, Alternations: 2", matrix.format_instance_size());
+        dpll::preprocess(matrix, &mut Statistics::new(), Instant::now());
+        assert_eq!(4, matrix.num_variables());
+        assert_eq!(4, matrix.num_clauses());
+        assert_eq!("Variables: 4, Clauses: 4, Alternations: 2", matrix.format_instance_size());
+    }
+
+    /*
+    Tests that quantifier blocks of the same type left adjacent by removing every quantifier of the block between them 
+    are written as a single block.
+    */
+    #[test]
+    pub fn to_qdimacs_merges_adjacent_blocks_test() {
+        let mut matrix = Matrix::from_string("p cnf 4 1\ne 1 2 0\na 3 0\ne 4 0\n1 4 0\n", config());
+        matrix.quantifier_list.retain(|quantifier| quantifier.literal != 3);
+        assert_eq!("p cnf 4 1\ne 1 2 4 0\n1 4 0\n", matrix.to_qdimacs());
+    }
+
+    /*
+    Tests that a parsed matrix equals the matrix parsed from its QDIMACS output, and that removing a clause or changing 
+    the quantification of a variable makes the matrices unequal.
+    */
+    #[test]
+    pub fn matrix_equality_test() {
+        let filename = "./test_files/get_quantifier_type_test1.qdimacs".to_string();
+        let matrix = Matrix::new(filename, config());
+        let round_trip_matrix = Matrix::from_string(&matrix.to_qdimacs(), config());
+        assert!(matrix == round_trip_matrix);
+
+        let mut removed_matrix = round_trip_matrix.clone();
+        removed_matrix.clause_set.clause_list[1].is_removed = true;
+        assert!(matrix != removed_matrix);
+
+        let requantified_matrix = Matrix::from_string(&matrix.to_qdimacs().replace("e 7", "a 7"), config());
+        assert!(matrix != requantified_matrix);
+    }
+
+    /*
+    Tests that removing an existential literal through Clause::remove_literal removes it from the existential literals 
+    only, leaving the universal literals untouched.
+    */
+    #[test]
+    pub fn clause_remove_existential_literal_test() {
+        let matrix = Matrix::from_string("p cnf 3 1\ne 1 0\na 2 0\ne 3 0\n1 2 -3 0\n", config());
+        let mut clause = matrix.clause_set.clause_list[0].clone();
+        clause.remove_literal(-3, &matrix.variable_quantification);
+        assert_eq!(vec![1], clause.e_literals.iter().cloned().collect::<Vec<i32>>());
+        assert_eq!(vec![2], clause.a_literals.iter().cloned().collect::<Vec<i32>>());
+    }
+
+    /*
+    Tests that removing a universal literal through Clause::remove_literal removes it from the universal literals only, 
+    leaving the existential literals untouched.
+    */
+    #[test]
+    pub fn clause_remove_universal_literal_test() {
+        let matrix = Matrix::from_string("p cnf 3 1\ne 1 0\na 2 0\ne 3 0\n1 2 -3 0\n", config());
+        let mut clause = matrix.clause_set.clause_list[0].clone();
+        clause.remove_literal(2, &matrix.variable_quantification);
+        assert_eq!(vec![1, -3], clause.e_literals.iter().cloned().collect::<Vec<i32>>());
+        assert_eq!(true, clause.a_literals.is_empty());
+    }
+
+    /*
+    Tests that a freshly parsed matrix passes the structural invariant checks, and that a reference to a removed clause,
+    a clause count out of sync with the clauses, or a prefix variable without a stored quantification fails them.
+    */
+    #[test]
+    pub fn verify_structural_invariants_test() {
+        let matrix = Matrix::from_string("p cnf 3 3\ne 1 0\na 2 0\ne 3 0\n1 2 -3 0\n-1 3 0\n1 -2 0\n", config());
+        assert_eq!(Ok(()), matrix.verify_structural_invariants());
+
+        let mut removed_clause_matrix = matrix.clone();
+        removed_clause_matrix.clause_set.clause_list[0].is_removed = true;
+        removed_clause_matrix.clause_set.clause_count -= 1;
+        assert_eq!(true, removed_clause_matrix.verify_structural_invariants().is_err());
+
+        let mut clause_count_matrix = matrix.clone();
+        clause_count_matrix.clause_set.clause_count += 1;
+        assert_eq!(true, clause_count_matrix.verify_structural_invariants().is_err());
+        clause_count_matrix.clause_set.clause_count = -1;
+        assert_eq!(Ok(()), clause_count_matrix.verify_structural_invariants());
+
+        let mut quantification_matrix = matrix.clone();
+        quantification_matrix.variable_quantification.remove(&3);
+        assert_eq!(true, quantification_matrix.verify_structural_invariants().is_err());
+    }
+
+    /*
+    Tests that decrementing the clause count to 0 signals the empty set rather than the empty clause, and that the empty
+    clause sentinel is kept when clauses are removed after a contradiction.
+    */
+    #[test]
+    pub fn clause_count_boundary_test() {
+        let mut matrix = Matrix::from_string("p cnf 2 2\ne 1 2 0\n1 2 0\n-1 2 0\n", config());
+        matrix.clause_set.decrement_counter();
+        matrix.clause_set.decrease_counter(1);
+        assert_eq!(0, matrix.clause_set.clause_count);
+        assert_eq!(true, matrix.clause_set.contains_empty_set());
+        assert_eq!(false, matrix.clause_set.contains_empty_clause());
+
+        matrix.clause_set.set_contradiction();
+        matrix.clause_set.decrement_counter();
+        matrix.clause_set.decrease_counter(2);
+        assert_eq!(true, matrix.clause_set.contains_empty_clause());
+        assert_eq!(true, matrix.clause_set.check_contradiction(None));
+    }
+
+    /*
+    Tests that decrementing the clause count past 0, as when a clause is removed twice, panics instead of falsely 
+    signalling the empty clause or the empty set.
+    */
+    #[test]
+    #[should_panic(expected = "a clause was removed twice")]
+    pub fn clause_count_double_decrement_test() {
+        let mut matrix = Matrix::from_string("p cnf 2 1\ne 1 2 0\n1 2 0\n", config());
+        matrix.clause_set.decrement_counter();
+        matrix.clause_set.decrement_counter();
+    }
+
+    /*
+    Tests that a pre-processed matrix written to a cache file is reloaded with the same prefix, clauses, and clause 
+    references, and gives the same result, and that a missing cache file is an error.
+    */
+    #[test]
+    pub fn matrix_cache_round_trip_test() {
+        let filename = "./test_files/compact_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        dpll::preprocess(matrix, &mut Statistics::new(), Instant::now());
+        let cache_path = std::env::temp_dir().join("matrix_cache_round_trip_test.cache");
+        let cache_path = cache_path.to_str().unwrap();
+        matrix.save_cache(cache_path).unwrap();
+        let loaded_matrix = &mut Matrix::load_cache(cache_path, config()).unwrap();
+        fs::remove_file(cache_path).expect("Unable to remove file");
+
+        assert_eq!(matrix.quantifier_list, loaded_matrix.quantifier_list);
+        assert_eq!(matrix.clause_set.clause_list, loaded_matrix.clause_set.clause_list);
+        assert_eq!(matrix.clause_set.clause_count, loaded_matrix.clause_set.clause_count);
+        assert_eq!(matrix.quantification_order.existential_literal_order, loaded_matrix.quantification_order.existential_literal_order);
+        assert_eq!(matrix.quantification_order.universal_literal_order, loaded_matrix.quantification_order.universal_literal_order);
+        assert_eq!(matrix.expected_result(), loaded_matrix.expected_result());
+        for literal in 1..=5 {
+            assert_eq!(matrix.level_of(literal), loaded_matrix.level_of(literal));
+            for signed_literal in [literal, -literal] {
+                assert_eq!(matrix.clause_references.get_vec(&signed_literal), loaded_matrix.clause_references.get_vec(&signed_literal));
+            }
+        }
+        assert_eq!(matrix.format_instance_size(), loaded_matrix.format_instance_size());
+        assert_eq!(dpll::solve(&mut matrix.clone(), &mut Statistics::new(), Instant::now()), dpll::solve(loaded_matrix, &mut Statistics::new(), Instant::now()));
+
+        assert_eq!(true, Matrix::load_cache("./test_files/missing.cache", config()).is_err());
+    }
+
+    /*
+    Tests that the clause length histogram counts the active clauses of each length, and is formatted in increasing
+    order of length.
+    */
+    #[test]
+    pub fn clause_length_histogram_test() {
+        let filename = "./test_files/preprocess_statistics_test.qdimacs".to_string();
+        let matrix = &mut Matrix::new(filename, config());
+        let histogram = get_clause_length_histogram(&matrix.clause_set);
+        assert_eq!(BTreeMap::from([(1, 1), (2, 3), (3, 1)]), histogram);
+        assert_eq!("Clause Lengths: [1: 1, 2: 3, 3: 1]", format_clause_length_histogram(&histogram));
+        matrix.clause_set.clause_list[1].is_removed = true;
+        assert_eq!(BTreeMap::from([(1, 1), (2, 3)]), get_clause_length_histogram(&matrix.clause_set));
+    }
+
+    /*
+    Tests that listing a benchmark directory is an error when the directory is missing or has no QDIMACS instances, and 
+    that other files in the directory are skipped.
+    */
+    #[test]
+    pub fn read_benchmark_paths_test() {
+        let missing_error = read_benchmark_paths(&"./benchmarks/missing".to_string(), Verbosity::Normal).err().unwrap();
+        assert_eq!(true, missing_error.starts_with("Unable to read benchmark directory ./benchmarks/missing"));
+
+        let benchmark_dir = std::env::temp_dir().join("read_benchmark_paths_test");
+        let _ = fs::remove_dir_all(&benchmark_dir);
+        fs::create_dir_all(&benchmark_dir).expect("Unable to create directory");
+        fs::write(benchmark_dir.join("README"), "Not an instance").unwrap();
+        fs::write(benchmark_dir.join(".DS_Store"), "").unwrap();
+        let group = benchmark_dir.to_str().unwrap().to_string();
+        assert_eq!(Some(format!("Benchmark directory {} contains no .qdimacs files", group)), read_benchmark_paths(&group, Verbosity::Normal).err());
+
+        fs::copy("./benchmarks/samples/example.qdimacs", benchmark_dir.join("example.qdimacs")).unwrap();
+        let paths = read_benchmark_paths(&group, Verbosity::Normal);
+        fs::remove_dir_all(&benchmark_dir).expect("Unable to remove directory");
+        assert_eq!(vec![benchmark_dir.join("example.qdimacs").display().to_string()], paths.unwrap());
+    }
+
+    /* END OF UTIL TESTS */
+
+    /* START OF MAXQBF TESTS */
+
+    /*
+    Tests that soft clauses are parsed with their weight and hard clauses have no weight.
+    */
+    #[test]
+    pub fn weighted_clause_parse_test() {
+        let filename = "./test_files/maxqbf_test1.qdimacs".to_string();
+        let matrix = Matrix::new(filename, config());
+        assert_eq!(None, matrix.clause_set.clause_list[0].weight);
+        assert_eq!(Some(4), matrix.clause_set.clause_list[1].weight);
+        assert_eq!(vec![2], matrix.clause_set.clause_list[1].literals());
+    }
+
+    /*
+    Tests that a soft clause which can be satisfied alongside the hard clauses counts towards the optimum.
+    */
+    #[test]
+    pub fn maxqbf_satisfiable_soft_clause_test() {
+        let filename = "./test_files/maxqbf_test1.qdimacs".to_string();
+        let matrix = Matrix::new(filename, config());
+        let result = maxqbf(&matrix, Instant::now());
+        assert_eq!(MaxQBFResult::Optimum(4, vec![1]), result);
+    }
+
+    /*
+    Tests that a soft clause which conflicts with the hard clauses for some universal assignment is not satisfied.
+    */
+    #[test]
+    pub fn maxqbf_unsatisfiable_soft_clause_test() {
+        let filename = "./test_files/maxqbf_test2.qdimacs".to_string();
+        let matrix = Matrix::new(filename, config());
+        let result = maxqbf(&matrix, Instant::now());
+        assert_eq!(MaxQBFResult::Optimum(0, Vec::new()), result);
+    }
+
+    /*
+    Tests that the optimum is found by tightening the weight bound, rather than by solving each subset of the soft clauses,
+    on an instance with too many soft clauses to enumerate. Of x1 and x2, only one can be true, so the soft clause -x2
+    is kept instead of x2.
+    */
+    #[test]
+    pub fn maxqbf_weight_bound_test() {
+        let filename = "./test_files/maxqbf_test3.qdimacs".to_string();
+        let matrix = Matrix::new(filename, config());
+        let result = maxqbf(&matrix, Instant::now());
+        let mut expected_soft_clauses = vec![1, 4];
+        expected_soft_clauses.extend((3..=12).map(|variable| 2 * variable - 1));
+        assert_eq!(MaxQBFResult::Optimum(23, expected_soft_clauses), result);
+    }
+
+    /* END OF MAXQBF TESTS */
+
+    /* START OF VERIFY TESTS */
+
+    /*
+    Tests that the assignment found by CDCL on a satisfiable instance passes verification.
+    */
+    #[test]
+    pub fn verify_satisfying_assignment_test() {
+        let filename = "./benchmarks/samples/example.qdimacs".to_string();
+        let mut verify_config = config();
+        verify_config.verify = true;
+        let matrix = &mut CDCLMatrix::new(filename, verify_config);
+        let result = solve(matrix, &mut Statistics::new(), Instant::now());
+        assert_eq!(Result::SAT, result);
+        assert_eq!(false, matrix.sat_assignments.is_empty());
+        assert_eq!(true, verify_assignment(matrix, &matrix.sat_assignments));
+    }
+
+    /*
+    Tests that an assignment falsifying every literal in a clause fails verification.
+    */
+    #[test]
+    pub fn verify_falsifying_assignment_test() {
+        let filename = "./benchmarks/samples/example.qdimacs".to_string();
+        let matrix = &mut CDCLMatrix::new(filename, config());
+        let mut assignments = HashMap::new();
+        for literal in matrix.original_clause_list[0].literals() {
+            assignments.insert(literal.abs(), Assignment {
+                value: -literal,
+                decision_level: 1,
+                clause_responsible: None,
+            });
+        }
+        assert_eq!(false, verify_assignment(matrix, &assignments));
+    }
+
+    /*
+    Tests that the assignment found by CDCL with variable elimination enabled passes verification against the parsed 
+    clauses, with the eliminated variables given values that satisfy the clauses they were eliminated from.
+    */
+    #[test]
+    pub fn verify_variable_elimination_assignment_test() {
+        let filename = "./test_files/variable_elimination_test.qdimacs".to_string();
+        let mut verify_config = config();
+        verify_config.verify = true;
+        verify_config.variable_elimination = (true, 0);
+        let matrix = &mut CDCLMatrix::new(filename, verify_config);
+        let result = solve(matrix, &mut Statistics::new(), Instant::now());
+        assert_eq!(Result::SAT, result);
+        assert_eq!(vec![5, 4], matrix.eliminated_variables.iter().map(|(variable, _)| *variable).collect::<Vec<i32>>());
+        assert_eq!(true, verify_assignment(matrix, &matrix.sat_assignments));
+    }
+
+    /*
+    Tests that a clause is satisfied when any literal is assigned true, falsified when every literal is assigned false,
+    and undetermined otherwise.
+    */
+    #[test]
+    pub fn clause_satisfied_by_test() {
+        let filename = "./benchmarks/samples/example.qdimacs".to_string();
+        let matrix = Matrix::new(filename, config());
+        let clause = Clause::new(&[1, -2], &matrix.variable_quantification, &matrix.quantification_order);
+        let assignment = |value: i32| Assignment { value, decision_level: 1, clause_responsible: None };
+        let mut assignments = HashMap::new();
+        assert_eq!(None, clause.satisfied_by(&assignments));
+        assignments.insert(1, assignment(-1));
+        assert_eq!(None, clause.satisfied_by(&assignments));
+        assignments.insert(2, assignment(2));
+        assert_eq!(Some(false), clause.satisfied_by(&assignments));
+        assignments.insert(2, assignment(-2));
+        assert_eq!(Some(true), clause.satisfied_by(&assignments));
+        assignments.remove(&1);
+        assert_eq!(Some(true), clause.satisfied_by(&assignments));
+    }
+
+    /* END OF VERIFY TESTS */
+
+    /* START OF CERTIFICATE TESTS */
+
+    /*
+    Tests that an outermost existential variable that is never assigned is omitted from the certificate by default.
+    */
+    #[test]
+    pub fn certificate_omits_dont_cares_test() {
+        let filename = "./test_files/certificate_test.qdimacs".to_string();
+        let mut certificate_config = config();
+        certificate_config.certificate = true;
+        let matrix = &mut CDCLMatrix::new(filename, certificate_config);
+        let result = solve(matrix, &mut Statistics::new(), Instant::now());
+        assert_eq!(Result::SAT, result);
+        let certificate = format_certificate(matrix, &result);
+        let lines: Vec<&str> = certificate.lines().collect();
+        assert_eq!(2, lines.len());
+        assert_eq!("s cnf 1", lines[0]);
+        assert!(lines[1] == "V 1 0" || lines[1] == "V -1 0");
+    }
+
+    /*
+    Tests that an outermost existential variable that is never assigned is marked as a don't-care in the certificate.
+    */
+    #[test]
+    pub fn certificate_marks_dont_cares_test() {
+        let filename = "./test_files/certificate_test.qdimacs".to_string();
+        let mut certificate_config = config();
+        certificate_config.certificate = true;
+        certificate_config.certificate_dont_cares = true;
+        let matrix = &mut CDCLMatrix::new(filename, certificate_config);
+        let result = solve(matrix, &mut Statistics::new(), Instant::now());
+        assert_eq!(Result::SAT, result);
+        let certificate = format_certificate(matrix, &result);
+        let lines: Vec<&str> = certificate.lines().collect();
+        assert_eq!(3, lines.len());
+        assert!(lines[1] == "V 1 0" || lines[1] == "V -1 0");
+        assert_eq!("V *2 0", lines[2]);
+    }
+
+    /* END OF CERTIFICATE TESTS */
+
+    /* START OF ENUMERATION TESTS */
+
+    /*
+    Tests that every distinct assignment of the outermost existential block is enumerated once, and that the 
+    enumeration stops as soon as the callback returns false.
+    */
+    #[test]
+    pub fn solve_all_test() {
+        let matrix = Matrix::from_string("p cnf 4 4\ne 1 2 0\na 3 0\ne 4 0\n1 2 0\n-1 -2 0\n3 4 0\n-3 -4 0\n", config());
+        let mut solutions = Vec::new();
+        let result = solve_all(&matrix, &config(), |solution| { solutions.push(solution.to_vec()); true });
+        assert_eq!(Result::UNSAT, result);
+        solutions.sort();
+        assert_eq!(vec![vec![-1, 2], vec![1, -2]], solutions);
+
+        let mut solutions = Vec::new();
+        let result = solve_all(&matrix, &config(), |solution| { solutions.push(solution.to_vec()); false });
+        assert_eq!(Result::SAT, result);
+        assert_eq!(1, solutions.len());
+    }
+
+    /* END OF ENUMERATION TESTS */
+
+    /* START OF DIFFERENTIAL TESTS */
+
+    /*
+    A function to generate a small random QBF instance in QDIMACS format from the given seed.
+    */
+    fn random_instance(seed: u64) -> String {
+        let mut state = seed.wrapping_mul(0x9E3779B97F4A7C15) | 1;
+        let variable_count = 3 + next_random(&mut state, 6) as i32;
+        let clause_count = 2 + next_random(&mut state, 12) as i32;
+        let mut quantifier_type = if next_random(&mut state, 2) == 0 { "e" } else { "a" };
+        let mut instance = format!("c For testing DPLL against CDCL with seed {}\np cnf {} {}\n", seed, variable_count, clause_count);
+        let mut block = vec![1];
+        for variable in 2..=variable_count {
+            if next_random(&mut state, 3) == 0 {
+                instance += &format!("{} {} 0\n", quantifier_type, block.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(" "));
+                quantifier_type = if quantifier_type.eq("e") { "a" } else { "e" };
+                block.clear();
+            }
+            block.push(variable);
+        }
+        instance += &format!("{} {} 0\n", quantifier_type, block.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(" "));
+        for _ in 0..clause_count {
+            let clause_length = 1 + next_random(&mut state, 4);
+            let mut clause: Vec<i32> = Vec::new();
+            for _ in 0..clause_length {
+                let variable = 1 + next_random(&mut state, variable_count as u64) as i32;
+                if clause.contains(&variable) || clause.contains(&-variable) { continue };
+                clause.push(if next_random(&mut state, 2) == 0 { variable } else { -variable });
+            }
+            instance += &format!("{} 0\n", clause.iter().map(|l| l.to_string()).collect::<Vec<String>>().join(" "));
+        }
+        return instance;
+    }
+
+    /*
+    Tests that DPLL and CDCL agree on the result of many small random instances, under several CDCL configurations. The
+    seed of each instance is reported on failure so it can be reproduced with random_instance.
+    */
+    #[test]
+    pub fn dpll_cdcl_differential_test() {
+        let mut restarts_config = config();
+        restarts_config.restarts = true;
+        let mut vss_config = config();
+        vss_config.literal_selection = LiteralSelection::VariableStateSum;
+        vss_config.restarts = true;
+        let mut no_pre_process_config = config();
+        no_pre_process_config.pre_process = false;
+        let mut ordered_config = config();
+        ordered_config.literal_selection = LiteralSelection::Ordered;
+        ordered_config.restarts = false;
+        let cdcl_configs = vec![config(), restarts_config, vss_config, no_pre_process_config, ordered_config];
+        for seed in 0..1000 {
+            let instance = random_instance(seed);
+            let dpll_matrix = &mut Matrix::from_string(&instance, config());
+            let dpll_result = dpll::solve(dpll_matrix, &mut Statistics::new(), Instant::now());
+            assert!(dpll_result.eq(&dpll::Result::SAT) || dpll_result.eq(&dpll::Result::UNSAT), "DPLL result {:?} is inconclusive on seed {}", dpll_result, seed);
+            let expected_result = if dpll_result.eq(&dpll::Result::SAT) { Result::SAT } else { Result::UNSAT };
+            for cdcl_config in &cdcl_configs {
+                let cdcl_matrix = &mut CDCLMatrix::from_matrix(Matrix::from_string(&instance, cdcl_config.clone()));
+                let cdcl_result = solve(cdcl_matrix, &mut Statistics::new(), Instant::now());
+                assert_eq!(expected_result, cdcl_result, "DPLL and CDCL disagree on seed {}", seed);
+            }
+        }
+    }
+
+    /*
+    Tests that the expected result is read from the "c result" comment of an instance, and is None without one.
+    */
+    #[test]
+    pub fn expected_result_parse_test() {
+        let matrix = Matrix::new("./test_files/repeated_conflict_test.qdimacs".to_string(), config());
+        assert_eq!(Some(false), matrix.expected_result());
+        assert_eq!(false, matrix.matches_expected_result(true));
+        let matrix = Matrix::new("./test_files/compact_test.qdimacs".to_string(), config());
+        assert_eq!(Some(true), matrix.expected_result());
+        let matrix = Matrix::new("./benchmarks/samples/example.qdimacs".to_string(), config());
+        assert_eq!(None, matrix.expected_result());
+        assert_eq!(true, matrix.matches_expected_result(false));
+    }
+
+    /*
+    Tests that DPLL and CDCL both find the expected result of every test instance that states one.
+    */
+    #[test]
+    pub fn expected_result_test() {
+        let mut checked_instances = 0;
+        for path in fs::read_dir("./test_files").unwrap() {
+            let filename = path.unwrap().path().display().to_string();
+            if !filename.ends_with(".qdimacs") { continue };
+            let matrix = &mut Matrix::new(filename.clone(), config());
+            if matrix.expected_result().is_none() { continue };
+            let dpll_result = dpll::solve(matrix, &mut Statistics::new(), Instant::now());
+            assert!(matrix.matches_expected_result(dpll_result.eq(&dpll::Result::SAT)), "DPLL result {:?} is not expected for {}", dpll_result, filename);
+            let cdcl_matrix = &mut CDCLMatrix::new(filename.clone(), config());
+            let cdcl_result = solve(cdcl_matrix, &mut Statistics::new(), Instant::now());
+            assert!(cdcl_matrix.core_data.matches_expected_result(cdcl_result.eq(&Result::SAT)), "CDCL result {:?} is not expected for {}", cdcl_result, filename);
+            checked_instances += 1;
+        }
+        assert!(checked_instances >= 4);
+    }
+
+    /*
+    Tests that both solvers give the expected result of each instance that states one when pre-resolution is run either
+    before or after pre-processing.
+    */
+    #[test]
+    pub fn resolution_before_preprocess_test() {
+        for path in fs::read_dir("./test_files").unwrap() {
+            let filename = path.unwrap().path().display().to_string();
+            if !filename.ends_with(".qdimacs") { continue };
+            for resolution_before_preprocess in [false, true] {
+                let mut order_config = config();
+                order_config.pre_resolution.0 = true;
+                order_config.resolution_before_preprocess = resolution_before_preprocess;
+                let matrix = &mut Matrix::new(filename.clone(), order_config.clone());
+                if matrix.expected_result().is_none() { break };
+                let dpll_result = dpll::solve(matrix, &mut Statistics::new(), Instant::now());
+                assert!(matrix.matches_expected_result(dpll_result.eq(&dpll::Result::SAT)), "DPLL result {:?} is not expected for {}", dpll_result, filename);
+                let cdcl_matrix = &mut CDCLMatrix::new(filename.clone(), order_config);
+                let cdcl_result = solve(cdcl_matrix, &mut Statistics::new(), Instant::now());
+                assert!(cdcl_matrix.core_data.matches_expected_result(cdcl_result.eq(&Result::SAT)), "CDCL result {:?} is not expected for {}", cdcl_result, filename);
+            }
+        }
+    }
+
+    /*
+    Tests that both solvers propagate the initial assignments before the search, so a pre-assignment that conflicts 
+    makes a satisfiable instance unsatisfiable, and that a fixed universal variable is not treated as a contradiction.
     */
     #[test]
-    pub fn invalid_resolve_test() {
-        let literals_1 = vec![1,2,3];
-        let literals_2 = vec![-1,-2,6];
-        let literal = 1;
-        let resolution = resolve(literals_1, literals_2, literal);
-        assert_eq!(true, resolution.is_none());
+    pub fn initial_assignments_test() {
+        let filename = "./test_files/initial_assignments_test.qdimacs".to_string();
+        for (initial_assignments, expected_result) in [(vec![], true), (vec![-1], false), (vec![4], true), (vec![-1, 9], false)] {
+            let mut assignment_config = config();
+            assignment_config.initial_assignments = initial_assignments;
+            let dpll_result = dpll::solve(&mut Matrix::new(filename.clone(), assignment_config.clone()), &mut Statistics::new(), Instant::now());
+            assert_eq!(if expected_result { dpll::Result::SAT } else { dpll::Result::UNSAT }, dpll_result);
+            let cdcl_result = solve(&mut CDCLMatrix::new(filename.clone(), assignment_config), &mut Statistics::new(), Instant::now());
+            assert_eq!(if expected_result { Result::SAT } else { Result::UNSAT }, cdcl_result);
+        }
     }
 
+    /* END OF DIFFERENTIAL TESTS */
+
+    /* START OF TIMEOUT TESTS */
+
     /*
-    Tests that the resolve functionality can perform Q-Resolution correctly.
+    Tests that both solvers report the phase of solving that exceeded the time budget, using a timer that has already 
+    run out.
     */
     #[test]
-    pub fn valid_resolve_test() {
-        let literals_1 = vec![1,2,3];
-        let literals_2 = vec![-1,4,6];
-        let literal = 1;
-        let mut resolution = resolve(literals_1, literals_2, literal).unwrap();
-        resolution.sort();
-        assert_eq!(vec![2,3,4,6], resolution);
+    pub fn timeout_phase_test() {
+        let filename = "./benchmarks/samples/example.qdimacs".to_string();
+        let expired_timer = Instant::now().checked_sub(Duration::from_secs(31)).unwrap();
+        for (pre_process, pre_resolution, expected_phase) in [(true, true, TimeoutPhase::Preprocess), (false, true, TimeoutPhase::PreResolution), (false, false, TimeoutPhase::Search)] {
+            let mut timeout_config = config();
+            timeout_config.pre_process = pre_process;
+            timeout_config.pre_resolution.0 = pre_resolution;
+            let dpll_result = dpll::solve(&mut Matrix::new(filename.clone(), timeout_config.clone()), &mut Statistics::new(), expired_timer);
+            assert_eq!(dpll::Result::Timeout(expected_phase.clone()), dpll_result);
+            let cdcl_result = solve(&mut CDCLMatrix::new(filename.clone(), timeout_config), &mut Statistics::new(), expired_timer);
+            assert_eq!(Result::Timeout(expected_phase), cdcl_result);
+        }
     }
 
+    /* END OF TIMEOUT TESTS */
+
+    /* START OF COMPARE TESTS */
+
     /*
-    Tests that resolved clauses are added to the clause database correctly.
+    Tests that comparing benchmark results against a baseline categorises each instance correctly.
     */
     #[test]
-    pub fn add_resolved_clauses_test() {
-        let filename = "./test_files/preresolution_test.qdimacs".to_string();
-        let matrix = &mut Matrix::new(filename, config());
-        let resolved_clause = convert_literals_to_clause(&matrix.variable_quantification, &matrix.quantification_order, &vec![2,3]);
-        add_resolved_clauses(matrix, vec![resolved_clause.clone()], 3, &mut Vec::new());
-        assert_eq!(3, matrix.clause_set.clause_count);
-        assert_eq!(matrix.clause_set.clause_list[2], resolved_clause);
+    pub fn compare_results_test() {
+        let baseline = read_results_csv(&fs::read_to_string("./test_files/compare_baseline.csv").unwrap()).unwrap();
+        let current = read_results_csv(&fs::read_to_string("./test_files/compare_current.csv").unwrap()).unwrap();
+        let (deltas, summary) = compare_results(&baseline, &current);
+        let expected_summary = ComparisonSummary { improved: 1, regressed: 1, newly_solved: 1, newly_timed_out: 1, flipped: 1, missing: 2 };
+        assert_eq!(expected_summary, summary);
+        assert_eq!(7, deltas.len());
+        assert!(deltas.contains(&"Instance: improved.qdimacs -- Improved -- Result: SAT -> SAT -- Runtime: 120.5ms -> 60.25ms (-60.25ms)".to_string()));
+        assert!(deltas.contains(&"Instance: added.qdimacs -- Missing from baseline".to_string()));
     }
 
     /*
-    Tests that pre-resolution is performed correctly.
+    Tests that results CSVs without the required columns or with an invalid result are rejected.
     */
     #[test]
-    pub fn pre_resolution_test() {
-        let filename = "./test_files/preresolution_test.qdimacs".to_string();
-        let matrix = &mut Matrix::new(filename, config());
-        pre_resolution(matrix, &mut Vec::new());
-        assert_eq!(3, matrix.clause_set.clause_count);
-        assert_eq!(vec![2,3], matrix.clause_set.clause_list[2].clone().get_literal_list());
+    pub fn read_results_csv_invalid_test() {
+        assert_eq!(None, read_results_csv("instance,runtime_ms\nexample.qdimacs,10"));
+        assert_eq!(None, read_results_csv("instance,result,runtime_ms\nexample.qdimacs,Unknown,10"));
+        assert_eq!(None, read_results_csv("instance,result,runtime_ms\nexample.qdimacs,SAT,fast"));
     }
-    /* END OF RESOLUTION TESTS */
-
-    /* START OF LITERAL SELECTION TESTS */
 
     /*
-    Tests that the literals are selected in order and variable quantification is obtained.
+    Tests that running a directory of instances under both solvers gives a record for each solver that agree, and that 
+    the written report puts both results side by side.
     */
     #[test]
-    pub fn ordered_literal_selection_test_1() {
-        let filename = "./test_files/ordered_literal_selection_test.qdimacs".to_string();
-        let matrix = &mut Matrix::new(filename, config());
-        let (literal, quantifier_type) = select_literal(matrix);
-        assert_eq!(2, literal);
-        assert_eq!(QuantifierType::Existential, quantifier_type);
+    pub fn run_both_solvers_test() {
+        let group = "./test_files/run_both_solvers".to_string();
+        let records = run_both_solvers(&group, &config()).unwrap();
+        assert_eq!(2, records.len());
+        let (dpll_record, cdcl_record) = records.get("sat.qdimacs").unwrap();
+        assert_eq!("SAT", dpll_record.result);
+        assert_eq!("SAT", cdcl_record.result);
+        assert_eq!(false, solvers_disagree(dpll_record, cdcl_record));
+        let timed_out = BenchmarkRecord { result: "Timeout".to_string(), runtime_ms: 0.0 };
+        assert_eq!(false, solvers_disagree(dpll_record, &timed_out));
+        assert_eq!(true, solvers_disagree(dpll_record, &BenchmarkRecord { result: "UNSAT".to_string(), runtime_ms: 0.0 }));
+        let (dpll_record, cdcl_record) = records.get("unsat.qdimacs").unwrap();
+        assert_eq!("UNSAT", dpll_record.result);
+        assert_eq!("UNSAT", cdcl_record.result);
+
+        let output_dir = std::env::temp_dir().join("run_both_solvers_test");
+        let _ = fs::remove_dir_all(&output_dir);
+        let mut output_config = config();
+        output_config.output_dir = output_dir.to_str().unwrap().to_string();
+        run_bench_directory(group, output_config, "run_both_solvers_test", false);
+        let output = fs::read_to_string(output_dir.join("output-run_both_solvers_test")).expect("Results file should be written");
+        fs::remove_dir_all(&output_dir).expect("Unable to remove directory");
+        assert_eq!(true, output.starts_with("--- DPLL vs CDCL ---"));
+        assert_eq!(true, output.contains("Total: 2, DPLL Solved: 2, CDCL Solved: 2, Disagreements: 0"));
+        assert_eq!(true, output.contains("Instance: sat.qdimacs -- DPLL: SAT ("));
+        assert_eq!(true, output.contains("Instance: unsat.qdimacs -- DPLL: UNSAT ("));
+        assert_eq!(false, output.contains("DISAGREEMENT"));
     }
 
+    /* END OF COMPARE TESTS */
+
+    /* START OF STATISTICS TESTS */
+
     /*
-    Tests that the literals are selected in order and void quantifiers are ignored and removed.
+    Tests that statistics exported as JSON are imported unchanged, and that invalid JSON is an error.
     */
     #[test]
-    pub fn ordered_literal_selection_test_2() {
-        let filename = "./test_files/ordered_literal_selection_test.qdimacs".to_string();
-        let matrix = &mut Matrix::new(filename, config());
-        select_literal(matrix);
-        let void_quantifier = Quantifier {
-            q_type: QuantifierType::Existential,
-            literal: 1,
-            q_level: 1,
-        };
-        assert_eq!(false, matrix.quantifier_list.contains(&void_quantifier));
+    pub fn statistics_json_round_trip_test() {
+        let filename = "./test_files/shuffle_clauses_test.qdimacs".to_string();
+        let statistics = &mut Statistics::new();
+        solve(&mut CDCLMatrix::new(filename, config()), statistics, Instant::now());
+        statistics.decision_bound = Some(3);
+        assert_eq!(Ok(statistics.clone()), Statistics::from_json(&statistics.to_json()));
+        assert_eq!(true, Statistics::from_json("{\"propagation_count\": 1}").is_err());
     }
 
-    /* 
-    Tests that the literals are selected using variable state sum and void quantifiers are ignored and removed.
+    /*
+    Tests that the statistics files written for several solves are summed and averaged, and that other files in the 
+    directory are skipped.
     */
     #[test]
-    pub fn variable_state_sum_selection_test_1() {
-        let filename = "./test_files/ordered_literal_selection_test.qdimacs".to_string();
-        let matrix = &mut Matrix::new(filename, config());
-        let (literal, quantifier_type) = select_literal_vss(matrix);
-        assert_eq!(3, literal);
-        assert_eq!(QuantifierType::Existential, quantifier_type);
+    pub fn aggregate_statistics_test() {
+        let output_dir = std::env::temp_dir().join("aggregate_statistics_test");
+        let _ = fs::remove_dir_all(&output_dir);
+        let output_dir = output_dir.to_str().unwrap().to_string();
+        let filenames = vec!["./test_files/shuffle_clauses_test.qdimacs".to_string(), "./test_files/propagation_order_test.qdimacs".to_string()];
+        let mut expected_propagations = 0;
+        for filename in &filenames {
+            let statistics = &mut Statistics::new();
+            solve(&mut CDCLMatrix::new(filename.clone(), config()), statistics, Instant::now());
+            expected_propagations += statistics.propagation_count;
+            write_statistics_json(&[filename.clone()], statistics, &output_dir);
+        }
+        fs::write(format!("{}/README.md", output_dir), "Not statistics").expect("Unable to write file");
+        let summary = aggregate_statistics(&output_dir).unwrap();
+        assert_eq!(2, summary.run_count);
+        assert_eq!(expected_propagations, summary.total.propagation_count);
+        assert_eq!(expected_propagations as f64 / 2.0, summary.average(summary.total.propagation_count as f64));
+        assert_eq!(true, format_statistics_summary(&summary).starts_with("Runs: 2\nPropagations -- Total: "));
 
-        let void_quantifier = Quantifier {
-            q_type: QuantifierType::Existential,
-            literal: 1,
-            q_level: 1,
-        };
-        assert_eq!(false, matrix.quantifier_list.contains(&void_quantifier));
+        fs::write(format!("{}/output-invalid.statistics.json", output_dir), "{}").expect("Unable to write file");
+        assert_eq!(true, aggregate_statistics(&output_dir).is_err());
+        fs::remove_dir_all(&output_dir).expect("Unable to remove directory");
     }
 
-    /* END OF LITERAL SELECTION TESTS */
+    /* END OF STATISTICS TESTS */
 
-    /* START OF UTIL TESTS */
+    /* START OF SOLVE OUTCOME TESTS */
 
     /*
-    Tests that literals are sorted in the correct order according to the order they appear in the quantifier prefix.
+    Tests that the library solve gives Sat and Unsat for decided instances with both DPLL and CDCL.
     */
     #[test]
-    pub fn sort_literals_order_test() {
-        let filename = "./test_files/sort_literals_order_test.qdimacs".to_string();
-        let matrix = &mut Matrix::new(filename, config());
-        let literals = vec![7,2,3,1];
-        let sorted_literals = sort_literals_order(&matrix.quantification_order.existential_literal_order, literals);
-        assert_eq!(vec![1,2,3,7], sorted_literals);
+    pub fn solve_outcome_test() {
+        for auto_alternation_threshold in [usize::MAX, 0] {
+            let mut solver_config = config();
+            solver_config.auto_alternation_threshold = auto_alternation_threshold;
+            assert_eq!(SolveOutcome::Sat, crate::solve("./test_files/iterative_deepening_test.qdimacs", solver_config.clone()));
+            assert_eq!(SolveOutcome::Unsat, crate::solve("./test_files/repeated_conflict_test.qdimacs", solver_config));
+        }
     }
 
     /*
-    Tests that the variable state sum value is correct.
+    Tests that the library solve reports a timeout with both DPLL and CDCL.
     */
     #[test]
-    pub fn get_variable_state_sum_test() {
-        let filename = "./test_files/get_variable_state_sum_test.qdimacs".to_string();
-        let matrix = &mut Matrix::new(filename, config());
-        let (vss, positive_sign) = get_variable_state_sum(&matrix.clause_references, 1);
-        assert_eq!(3, vss);
-        assert_eq!(true, positive_sign);
+    pub fn solve_outcome_timeout_test() {
+        for auto_alternation_threshold in [usize::MAX, 0] {
+            let mut timeout_config = config();
+            timeout_config.auto_alternation_threshold = auto_alternation_threshold;
+            timeout_config.timeout = Duration::ZERO;
+            let outcome = crate::solve("./test_files/repeated_conflict_test.qdimacs", timeout_config);
+            assert_eq!(SolveOutcome::Unknown { reason: UnknownReason::Timeout }, outcome);
+        }
     }
 
     /*
-    Tests that literals are converted to a properly formatted clause within covert_literals_to_clause.
+    Tests that the library solve reports an interrupt rather than a timeout with both DPLL and CDCL, when the flag is
+    raised on a clone of the config.
     */
     #[test]
-    pub fn convert_literals_to_clause_test() {
-        let filename = "./test_files/convert_literals_to_clause_test.qdimacs".to_string();
-        let matrix = &mut Matrix::new(filename, config());
-        let converted_clause = convert_literals_to_clause(&matrix.variable_quantification, &matrix.quantification_order, &vec![3, 2, 4, 1]);
-        let expected_clause = Clause {
-            e_literals: vec![1, 2, 3],
-            a_literals: vec![4],
-            is_removed: false,
-        };
-        assert_eq!(expected_clause, converted_clause);
+    pub fn solve_outcome_interrupted_test() {
+        for auto_alternation_threshold in [usize::MAX, 0] {
+            let mut interrupt_config = config();
+            interrupt_config.auto_alternation_threshold = auto_alternation_threshold;
+            interrupt_config.clone().interrupt.interrupt();
+            let outcome = crate::solve("./test_files/repeated_conflict_test.qdimacs", interrupt_config);
+            assert_eq!(SolveOutcome::Unknown { reason: UnknownReason::Interrupted }, outcome);
+        }
     }
 
     /*
-    Tests that the quantifier type and index is found correctly when it exists in the quantifier prefix.
+    Tests that the library solve reports that CDCL reached the conflict limit.
     */
     #[test]
-    pub fn get_quantifier_type_test_1() {
-        let filename = "./test_files/get_quantifier_type_test1.qdimacs".to_string();
-        let matrix = &mut Matrix::new(filename, config());
-        let (quantifier_type, quantifier_index) = get_quantifier_type(&matrix.quantifier_list, 1);
-        assert_eq!(QuantifierType::Existential, quantifier_type);
-        assert_eq!(false, quantifier_index.is_none());
-        assert_eq!(0, quantifier_index.unwrap());
+    pub fn solve_outcome_conflict_limit_test() {
+        let mut limit_config = config();
+        limit_config.auto_alternation_threshold = 0;
+        limit_config.pre_process = false;
+        limit_config.literal_selection = LiteralSelection::VariableStateSum;
+        limit_config.max_conflicts = 1;
+        let outcome = crate::solve("./test_files/conflict_heavy_test.qdimacs", limit_config);
+        assert_eq!(SolveOutcome::Unknown { reason: UnknownReason::ConflictLimit }, outcome);
     }
 
     /*
-    Tests that quantifier type existential and no index is returned for a literal not appearing in the quanitifer prefix.
+    Tests that the library solve reports that DPLL reached the decision limit.
     */
     #[test]
-    pub fn get_quantifier_type_test_2() {
-        let filename = "./test_files/get_quantifier_type_test2.qdimacs".to_string();
-        let matrix = &mut Matrix::new(filename, config());
-        let (quantifier_type, quantifier_index) = get_quantifier_type(&matrix.quantifier_list, 4);
-        assert_eq!(QuantifierType::Existential, quantifier_type);
-        assert_eq!(true, quantifier_index.is_none());
+    pub fn solve_outcome_decision_limit_test() {
+        let mut limit_config = config();
+        limit_config.auto_alternation_threshold = usize::MAX;
+        limit_config.pre_process = false;
+        limit_config.pure_literal_deletion = false;
+        limit_config.max_decisions = 1;
+        let outcome = crate::solve("./test_files/iterative_deepening_test.qdimacs", limit_config);
+        assert_eq!(SolveOutcome::Unknown { reason: UnknownReason::DecisionLimit }, outcome);
     }
 
+    /* END OF SOLVE OUTCOME TESTS */
+
+    /* START OF LOGGING TESTS */
+
     /*
-    Tests that unit literals are found from the clause database correctly.
+    A writer that appends to a buffer shared with the test, so that log messages can be captured.
     */
-    #[test]
-    pub fn get_unit_literals_test_1() {
-        let filename = "./test_files/get_unit_literals_test1.qdimacs".to_string();
-        let matrix = &mut Matrix::new(filename, config());
-        let mut unit_literals = get_unit_literals(&matrix.clause_set.clause_list);
-        unit_literals.sort();
-        assert_eq!(vec![2,4], unit_literals);
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            return Ok(buf.len());
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            return Ok(());
+        }
+    }
 
+    /*
+    A function to solve an instance with DPLL at the given verbosity, returning everything written to the logger.
+    */
+    fn capture_run_instance(verbosity: Verbosity) -> String {
+        let buffer = Rc::new(RefCell::new(Vec::new()));
+        set_writer(Box::new(SharedBuffer(buffer.clone())));
+        let mut config = config();
+        config.verbosity = verbosity;
+        dpll::run_instance(&["./test_files/pure_sat_test.qdimacs".to_string()], config);
+        reset_writer();
+        let output = String::from_utf8(buffer.borrow().clone()).unwrap();
+        return output;
     }
 
     /*
-    Tests that when no unit literals exist, none are found.
+    Testing the quiet verbosity suppresses all output, including the result, while the normal verbosity writes the 
+    result to the injected writer without the per-decision messages of the debug verbosity.
     */
     #[test]
-    pub fn get_unit_literals_test_2() {
-        let filename = "./test_files/get_unit_literals_test2.qdimacs".to_string();
-        let matrix = &mut Matrix::new(filename, config());
-        let unit_literals = get_unit_literals(&matrix.clause_set.clause_list);
-        assert_eq!(true, unit_literals.is_empty());
-
+    pub fn quiet_verbosity_suppresses_output_test() {
+        assert_eq!("", capture_run_instance(Verbosity::Quiet));
+        let normal_output = capture_run_instance(Verbosity::Normal);
+        assert_eq!(true, normal_output.contains("Satisfiable"));
+        assert_eq!(false, normal_output.contains("Decision:"));
+        let debug_output = capture_run_instance(Verbosity::Debug);
+        assert_eq!(true, debug_output.contains("Satisfiable"));
+        assert_eq!(true, debug_output.contains("Decision:"));
     }
 
     /*
-    Tests that during running benchmarks, the instance name of a file in qdimacs form is extracted properly.
+    Testing the message written when CDCL stops without progress goes through the logger, so the quiet verbosity 
+    suppresses it along with the state dump of the debug option.
     */
     #[test]
-    pub fn read_instance_name_test() {
-        let file_path= "./benchmarks/castellini\\toilet_a_02_01.2.qdimacs".to_string();
-        let instance_name = read_instance_name(&file_path);
-        let expected_instance_name = "toilet_a_02_01.2.qdimacs".to_string();
-        assert_eq!(expected_instance_name, instance_name);
+    pub fn quiet_verbosity_suppresses_abort_test() {
+        let filename = "./test_files/conflict_heavy_test.qdimacs".to_string();
+        let mut abort_config = config();
+        abort_config.restarts = false;
+        abort_config.repeated_conflict_limit = 0;
+        abort_config.dump_first_learned = true;
+        abort_config.debug = true;
+        let matrix = &mut CDCLMatrix::new(filename.clone(), abort_config.clone());
+        assert_eq!(Result::UNSAT, solve(matrix, &mut Statistics::new(), Instant::now()));
+        let (first_learned_clause, backtrack_level, _is_asserting) = matrix.first_learned_clause.clone().unwrap();
+        for verbosity in [Verbosity::Quiet, Verbosity::Normal] {
+            let buffer = Rc::new(RefCell::new(Vec::new()));
+            set_writer(Box::new(SharedBuffer(buffer.clone())));
+            abort_config.verbosity = verbosity;
+            let matrix = &mut CDCLMatrix::new(filename.clone(), abort_config.clone());
+            let clause = Clause::new(&first_learned_clause, &matrix.core_data.variable_quantification, &matrix.core_data.quantification_order);
+            matrix.last_learned_clause = Some(clause);
+            matrix.repeated_conflict_level = backtrack_level + 1;
+            let result = solve(matrix, &mut Statistics::new(), Instant::now());
+            reset_writer();
+            assert_eq!(Result::NoProgress, result);
+            let output = String::from_utf8(buffer.borrow().clone()).unwrap();
+            assert_eq!(verbosity.eq(&Verbosity::Normal), output.contains("CDCL aborted"));
+            assert_eq!(verbosity.eq(&Verbosity::Normal), output.contains("State at abort"));
+        }
     }
 
-    /* END OF UTIL TESTS */
+    /* END OF LOGGING TESTS */
 
     /* START OF CONFIG PARSER TESTS */
 
@@ -542,6 +2431,72 @@ mod test {
         assert_eq!(SolverType::DPLL, solver_type_value.unwrap());
     }
 
+    /*
+    Testing reading solver type allows "MaxQBF".
+    */
+    #[test]
+    pub fn read_solver_type_valid_test_3() {
+        let json_values = json!({"SolverType": "MaxQBF"});
+        let solver_type_value = read_solver_type_json(&json_values["SolverType"]);
+        assert_eq!(false, solver_type_value.is_none());
+        assert_eq!(SolverType::MaxQBF, solver_type_value.unwrap());
+    }
+
+    /*
+    Testing reading solver type allows "Parse".
+    */
+    #[test]
+    pub fn read_solver_type_valid_test_4() {
+        let json_values = json!({"SolverType": "parse"});
+        let solver_type_value = read_solver_type_json(&json_values["SolverType"]);
+        assert_eq!(false, solver_type_value.is_none());
+        assert_eq!(SolverType::Parse, solver_type_value.unwrap());
+    }
+
+    /*
+    Testing reading solver type allows "Auto".
+    */
+    #[test]
+    pub fn read_solver_type_valid_test_5() {
+        let json_values = json!({"SolverType": "auto"});
+        let solver_type_value = read_solver_type_json(&json_values["SolverType"]);
+        assert_eq!(false, solver_type_value.is_none());
+        assert_eq!(SolverType::Auto, solver_type_value.unwrap());
+    }
+
+    /*
+    Testing reading solver type allows "Compare".
+    */
+    #[test]
+    pub fn read_solver_type_valid_test_6() {
+        let json_values = json!({"SolverType": "compare"});
+        let solver_type_value = read_solver_type_json(&json_values["SolverType"]);
+        assert_eq!(false, solver_type_value.is_none());
+        assert_eq!(SolverType::Compare, solver_type_value.unwrap());
+    }
+
+    /*
+    Testing reading solver type allows "Both".
+    */
+    #[test]
+    pub fn read_solver_type_valid_test_7() {
+        let json_values = json!({"SolverType": "both"});
+        let solver_type_value = read_solver_type_json(&json_values["SolverType"]);
+        assert_eq!(false, solver_type_value.is_none());
+        assert_eq!(SolverType::Both, solver_type_value.unwrap());
+    }
+
+    /*
+    Testing reading solver type allows "Aggregate".
+    */
+    #[test]
+    pub fn read_solver_type_valid_test_8() {
+        let json_values = json!({"SolverType": "aggregate"});
+        let solver_type_value = read_solver_type_json(&json_values["SolverType"]);
+        assert_eq!(false, solver_type_value.is_none());
+        assert_eq!(SolverType::Aggregate, solver_type_value.unwrap());
+    }
+
     /*
     Testing reading solver type does not allow any other string.
     */
@@ -574,6 +2529,17 @@ mod test {
         assert_eq!(LiteralSelection::Ordered, literal_selection_value.unwrap());
     }
 
+    /*
+    Testing reading literal selection type allows "DLIS".
+    */
+    #[test]
+    pub fn read_literal_selection_type_valid_test_3() {
+        let json_values = json!({"LiteralSelection": "dlis"});
+        let literal_selection_value = read_literal_selection_json(&json_values["LiteralSelection"]);
+        assert_eq!(false, literal_selection_value.is_none());
+        assert_eq!(LiteralSelection::DLIS, literal_selection_value.unwrap());
+    }
+
     /*
     Testing reading literal selection type does not allow any other string.
     */
@@ -584,5 +2550,136 @@ mod test {
         assert_eq!(true, literal_selection_value.is_none());
     }
 
+    /*
+    Testing reading the fallback literal selection allows "None" to disable the fallback, as well as any literal 
+    selection type.
+    */
+    #[test]
+    pub fn read_fallback_literal_selection_test() {
+        let json_values = json!({"None": "None", "Ordered": "ordered", "Invalid": "literal-selection-type"});
+        assert_eq!(Some(None), read_fallback_literal_selection_json(&json_values["None"]));
+        assert_eq!(Some(Some(LiteralSelection::Ordered)), read_fallback_literal_selection_json(&json_values["Ordered"]));
+        assert_eq!(true, read_fallback_literal_selection_json(&json_values["Invalid"]).is_none());
+    }
+
+    /*
+    Testing reading restart policy allows "Glucose" and uses the given window and margin.
+    */
+    #[test]
+    pub fn read_restart_policy_valid_test() {
+        let json_values = json!({"RestartPolicy": "Glucose"});
+        let restart_policy_value = read_restart_policy_json(&json_values["RestartPolicy"], 50, 1.25);
+        assert_eq!(false, restart_policy_value.is_none());
+        assert_eq!(RestartPolicy::Glucose { window: 50, margin: 1.25 }, restart_policy_value.unwrap());
+    }
+
+    /*
+    Testing reading restart policy does not allow any other string.
+    */
+    #[test]
+    pub fn read_restart_policy_invalid_test() {
+        let json_values = json!({"RestartPolicy": "geometric"});
+        let restart_policy_value = read_restart_policy_json(&json_values["RestartPolicy"], 50, 1.25);
+        assert_eq!(true, restart_policy_value.is_none());
+    }
+
+    /*
+    Testing reading default phase allows "Positive", "Negative", and "Occurrence".
+    */
+    #[test]
+    pub fn read_default_phase_valid_test() {
+        let json_values = json!({"Positive": "Positive", "Negative": "negative", "Occurrence": "OCCURRENCE"});
+        assert_eq!(Some(DefaultPhase::Positive), read_default_phase_json(&json_values["Positive"]));
+        assert_eq!(Some(DefaultPhase::Negative), read_default_phase_json(&json_values["Negative"]));
+        assert_eq!(Some(DefaultPhase::Occurrence), read_default_phase_json(&json_values["Occurrence"]));
+    }
+
+    /*
+    Testing reading default phase does not allow any other string.
+    */
+    #[test]
+    pub fn read_default_phase_invalid_test() {
+        let json_values = json!({"DefaultPhase": "Random"});
+        let default_phase_value = read_default_phase_json(&json_values["DefaultPhase"]);
+        assert_eq!(true, default_phase_value.is_none());
+    }
+
+    /*
+    Testing reading propagation order allows "FIFO" and "LIFO", and does not allow any other string.
+    */
+    #[test]
+    pub fn read_propagation_order_test() {
+        let json_values = json!({"FIFO": "FIFO", "LIFO": "lifo", "Invalid": "Random"});
+        assert_eq!(Some(PropagationOrder::FIFO), read_propagation_order_json(&json_values["FIFO"]));
+        assert_eq!(Some(PropagationOrder::LIFO), read_propagation_order_json(&json_values["LIFO"]));
+        assert_eq!(true, read_propagation_order_json(&json_values["Invalid"]).is_none());
+    }
+
+    /*
+    Testing reading learning scheme allows "Decision".
+    */
+    #[test]
+    pub fn read_learning_scheme_valid_test() {
+        let json_values = json!({"LearningScheme": "Decision"});
+        let learning_scheme_value = read_learning_scheme_json(&json_values["LearningScheme"]);
+        assert_eq!(false, learning_scheme_value.is_none());
+        assert_eq!(LearningScheme::Decision, learning_scheme_value.unwrap());
+    }
+
+    /*
+    Testing reading learning scheme does not allow any other string.
+    */
+    #[test]
+    pub fn read_learning_scheme_invalid_test() {
+        let json_values = json!({"LearningScheme": "AllUIP"});
+        let learning_scheme_value = read_learning_scheme_json(&json_values["LearningScheme"]);
+        assert_eq!(true, learning_scheme_value.is_none());
+    }
+
+    /*
+    Testing reading a literal list allows a list of non-zero integers.
+    */
+    #[test]
+    pub fn read_literal_list_valid_test() {
+        let json_values = json!({"InitialAssignments": [1, -4]});
+        let literal_list_value = read_literal_list_json(&json_values["InitialAssignments"]);
+        assert_eq!(false, literal_list_value.is_none());
+        assert_eq!(vec![1, -4], literal_list_value.unwrap());
+    }
+
+    /*
+    Testing reading a literal list does not allow zero or non-integer literals.
+    */
+    #[test]
+    pub fn read_literal_list_invalid_test() {
+        let json_values = json!({"Zero": [1, 0], "Float": [1.5], "NotList": 1});
+        assert_eq!(true, read_literal_list_json(&json_values["Zero"]).is_none());
+        assert_eq!(true, read_literal_list_json(&json_values["Float"]).is_none());
+        assert_eq!(true, read_literal_list_json(&json_values["NotList"]).is_none());
+    }
+
+
+    /*
+    Testing reading the verbosity allows the levels 0, 1 and 2.
+    */
+    #[test]
+    pub fn read_verbosity_valid_test() {
+        let json_values = json!({"Quiet": 0, "Normal": 1, "Debug": 2});
+        assert_eq!(Some(Verbosity::Quiet), read_verbosity_json(&json_values["Quiet"]));
+        assert_eq!(Some(Verbosity::Normal), read_verbosity_json(&json_values["Normal"]));
+        assert_eq!(Some(Verbosity::Debug), read_verbosity_json(&json_values["Debug"]));
+    }
+
+    /*
+    Testing reading the verbosity does not allow levels above 2, negative levels or names.
+    */
+    #[test]
+    pub fn read_verbosity_invalid_test() {
+        let json_values = json!({"TooHigh": 3, "Negative": -1, "Name": "debug"});
+        assert_eq!(true, read_verbosity_json(&json_values["TooHigh"]).is_none());
+        assert_eq!(true, read_verbosity_json(&json_values["Negative"]).is_none());
+        assert_eq!(true, read_verbosity_json(&json_values["Name"]).is_none());
+    }
+
     /* END OF CONFIG PARSER TESTS */
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
 use multimap::MultiMap;
 
-use crate::{data_structures::{Clause, Variable, Matrix, UniversalReductionClause}, util::sort_literals_order};
+use crate::{log_debug, data_structures::{Clause, ClauseSet, Variable, Matrix, UniversalReductionClause, Statistics}, util::{sort_literals_order, get_unit_literals}};
 
 /*
 A function to get all universal literals that can be removed by universal reduction.
@@ -10,9 +10,9 @@ e_literal so run-time reduced as I can break / continue depending on whether it
 
 Returns a list of UniversalReductionClause data structures.
 */
-pub fn get_universal_literals_for_reduction(clause_list: &Vec<Clause>, variable_quantification: &MultiMap<i32, Variable>) -> Vec<UniversalReductionClause> {
+pub fn get_universal_literals_for_reduction(clause_set: &ClauseSet, variable_quantification: &MultiMap<i32, Variable>) -> Vec<UniversalReductionClause> {
     let mut universal_literals = Vec::new();
-    for (position, clause) in clause_list.iter().enumerate().rev() {
+    for (position, clause) in clause_set.active_clauses().rev() {
         let literals_to_remove = detect_universal_literal(clause, variable_quantification);
         if !literals_to_remove.is_empty() {
             universal_literals.push(UniversalReductionClause {
@@ -25,11 +25,35 @@ pub fn get_universal_literals_for_reduction(clause_list: &Vec<Clause>, variable_
 }
 
 /*
-A function to remove universal literals from a given clause.
+A function to remove universal literals from a given clause. If the clause is emptied by the removal, the clause set is
+marked as containing the empty clause.
+
+Returns true if the clause has been emptied, false otherwise.
 */
-pub fn remove_universal_literal(matrix: &mut Matrix, literals: Vec<i32>, clause_index: i32) {
+pub fn remove_universal_literal(matrix: &mut Matrix, literals: Vec<i32>, clause_index: i32) -> bool {
+    log_debug!(matrix.config.verbosity, "Universal reduction: removed {:?} from clause {}", literals, clause_index);
     matrix.clause_set.clause_list[clause_index as usize].remove_a_literals(literals);
-    matrix.clause_set.check_contradiction(Some(clause_index));
+    return matrix.clause_set.check_contradiction(Some(clause_index));
+}
+
+/*
+A function to apply universal reduction once to every clause straight after parsing, before pre-processing and the 
+search. A clause such as (e1 or a2), with a2 quantified inside e1, is then the unit clause (e1), so it is propagated as
+a forced assignment at the start rather than only after a round of pre-processing. The clause references of the 
+removed literals are dropped.
+
+Returns the unit literals of the reduced clause set, or none if a clause was emptied.
+*/
+pub fn reduce_parsed_clauses(matrix: &mut Matrix, statistics: &mut Statistics) -> Vec<i32> {
+    let literals_for_universal_reduction = get_universal_literals_for_reduction(&matrix.clause_set, &matrix.variable_quantification);
+    if literals_for_universal_reduction.is_empty() { return get_unit_literals(&matrix.clause_set) };
+    for literal_to_remove in literals_for_universal_reduction {
+        statistics.parse_reduction_removed_literals += literal_to_remove.values.len() as i32;
+        remove_universal_literal(matrix, literal_to_remove.values, literal_to_remove.clause_index);
+    }
+    matrix.remove_clauses(&Vec::new());
+    if matrix.clause_set.contains_empty_clause() { return Vec::new() };
+    return get_unit_literals(&matrix.clause_set);
 }
 
 /*
@@ -43,7 +67,9 @@ pub fn readd_universal_literal(matrix: &mut Matrix, literals: Vec<i32>, clause_i
 }
 
 /*
-A function to detect any universal literals in a given clause which can be removed by universal reduction.
+A function to detect any universal literals in a given clause which can be removed by universal reduction. A universal 
+literal is removed if its quantification level is strictly greater than the level of the innermost existential literal. 
+Existential and universal variables are never parsed at the same level, so the comparison never ties.
 
 Returns the list of detected literals.
 */
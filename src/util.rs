@@ -1,7 +1,8 @@
+use std::{fs, path::Path, collections::{BTreeMap, HashMap}, time::{Duration, Instant}};
 use multimap::MultiMap;
 use regex::Regex;
 
-use crate::data_structures::{Clause, QuantifierType, Variable, QuantificationOrder, Quantifier};
+use crate::{log_normal, data_structures::{Clause, ClauseSet, QuantifierType, Variable, QuantificationOrder, Quantifier, Trail, Matrix, Config, SolverType, Verbosity}};
 
 /*
 A function to sort a list of literals into the order in which the variables appear quantified.
@@ -18,11 +19,39 @@ pub fn sort_literals_order(sort_order: &Vec<i32>, literals: Vec<i32>) -> Vec<i32
     return sorted_literals;
 }
 
+/*
+A function to merge two lists of literals that are already sorted into the order in which the variables appear 
+quantified, given the position of each variable in that order. Duplicate literals are removed.
+
+Returns the merged list of literals.
+*/
+pub fn merge_literals_order(literal_positions: &HashMap<i32, usize>, literals_1: &Vec<i32>, literals_2: &Vec<i32>) -> Vec<i32> {
+    let position = |literal: i32| literal_positions.get(&literal.abs());
+    let mut merged_literals = Vec::with_capacity(literals_1.len() + literals_2.len());
+    let (mut index_1, mut index_2) = (0, 0);
+    while index_1 < literals_1.len() && index_2 < literals_2.len() {
+        if position(literals_1[index_1]) <= position(literals_2[index_2]) {
+            merged_literals.push(literals_1[index_1]);
+            index_1 += 1;
+        } else {
+            merged_literals.push(literals_2[index_2]);
+            index_2 += 1;
+        }
+    }
+    merged_literals.extend(&literals_1[index_1..]);
+    merged_literals.extend(&literals_2[index_2..]);
+    merged_literals.dedup();
+    return merged_literals;
+}
+
 /*
 A function to find the number of references a literal has in the current matrix. It also determines the sign priority.
 
 choose_positive determines whether the variable should be decided positively or negatively. If the variable appears more 
 often negatively, we choose the variable negatively. Otherwise, we choose the variable positively.
+
+The occurrence counts are the lengths of the literal's reference lists, which unit propagation and pure literal deletion 
+already maintain incrementally, so this is a constant time lookup.
 */
 pub fn get_variable_state_sum(clause_references: &MultiMap<i32, i32>, literal: i32) -> (i32, bool) {
     let mut pos_appearances = 0;
@@ -40,29 +69,12 @@ pub fn get_variable_state_sum(clause_references: &MultiMap<i32, i32>, literal: i
 
 /*
 A function to convert a list of literals into clause structure, with sorted literals in their quantification ordering.
+Kept for compatibility, Clause::new should be used instead.
 
 Returns the created clause.
 */
 pub fn convert_literals_to_clause(variable_quantification: &MultiMap<i32, Variable>, quantification_order: &QuantificationOrder, literals: &Vec<i32>) -> Clause {
-    let mut e_literals = Vec::new();
-    let mut a_literals = Vec::new();
-    for literal in literals {
-        let literal_quantification = &variable_quantification.get(&literal.abs()).unwrap().q_type;
-        if literal_quantification.eq(&QuantifierType::Existential) {
-            e_literals.push(*literal);
-        } else {
-            a_literals.push(*literal);
-        }
-    }
-    e_literals = sort_literals_order(&quantification_order.existential_literal_order, e_literals);
-    a_literals = sort_literals_order(&quantification_order.universal_literal_order, a_literals);
-
-    let resolved_clause = Clause {
-        e_literals,
-        a_literals,
-        is_removed: false,
-    };
-    return resolved_clause;
+    return Clause::new(literals, variable_quantification, quantification_order);
 }
 
 /*
@@ -86,13 +98,13 @@ pub fn get_quantifier_type(quantifier_list: &Vec<Quantifier>, unit_literal: i32)
 }
 
 /*
-A function to check for unit literals in a list of clauses.
+A function to check for unit literals in the active clauses of a clause set.
 
 Returns a list of unit literals.
 */
-pub fn get_unit_literals(clause_list: &Vec<Clause>) -> Vec<i32> {
+pub fn get_unit_literals(clause_set: &ClauseSet) -> Vec<i32> {
     let mut unit_literals = Vec::new();
-    for clause in clause_list {
+    for (_index, clause) in clause_set.active_clauses() {
         let unit_clause_check = clause.is_unit_clause();
         if !unit_clause_check.is_none() {
             unit_literals.push(unit_clause_check.unwrap());
@@ -102,15 +114,176 @@ pub fn get_unit_literals(clause_list: &Vec<Clause>) -> Vec<i32> {
 }
 
 /*
-A function to get the instance name from a file_path.
+A function to get the instance name from a file_path. Both Windows (\) and Unix (/) path separators are handled.
 
 Example: file_path = ./benchmarks/castellini\toilet_a_02_10.2.qdimacs
             => instance_name = toilet_a_02_10.2.qdimacs
+Example: file_path = ./benchmarks/castellini/toilet_a_02_10.2.qdimacs
+            => instance_name = toilet_a_02_10.2.qdimacs
 
 Returns the instance name.
 */
 pub fn read_instance_name(file_path: &String) -> String {
-    let re_get_instance = Regex::new(r"[^\\]+$").unwrap();
+    let re_get_instance = Regex::new(r"[^\\/]+$").unwrap();
     let instance_name = re_get_instance.find(&file_path).map(|m| m.as_str()).unwrap().to_string();
     return instance_name;
-}
\ No newline at end of file
+}
+
+/*
+A function to format a trail of assignments, one assignment per line in chronological order. Each line shows the 
+decision level, whether the assignment was a decision or an implication, the literal assigned, and for implications 
+the index of the clause responsible.
+
+Returns the formatted trail.
+*/
+pub fn format_trail(trail: &Trail) -> String {
+    let mut lines = Vec::new();
+    for assignment in trail.iter() {
+        if assignment.is_decision() {
+            lines.push(format!("Level {}: Decision {}", assignment.decision_level, assignment.value));
+        } else {
+            lines.push(format!("Level {}: Implication {} (clause {})", assignment.decision_level, assignment.value, assignment.responsible_clause().unwrap()));
+        }
+    }
+    return lines.join("\n");
+}
+
+/*
+A function to count the active clauses of a clause set by their length.
+
+Returns the number of clauses of each length, keyed by length.
+*/
+pub fn get_clause_length_histogram(clause_set: &ClauseSet) -> BTreeMap<usize, usize> {
+    let mut histogram = BTreeMap::new();
+    for (_index, clause) in clause_set.active_clauses() {
+        *histogram.entry(clause.get_clause_length()).or_insert(0) += 1;
+    }
+    return histogram;
+}
+
+/*
+A function to format a clause length histogram in increasing order of length, e.g. "Clause Lengths: [2: 10, 3: 40]".
+
+Returns the formatted histogram.
+*/
+pub fn format_clause_length_histogram(histogram: &BTreeMap<usize, usize>) -> String {
+    let buckets: Vec<String> = histogram.iter().map(|(length, count)| format!("{}: {}", length, count)).collect();
+    return format!("Clause Lengths: [{}]", buckets.join(", "));
+}
+
+/*
+A function to list the QDIMACS instances in a benchmark directory, in order of path. Subdirectories and other files in 
+the directory, such as a README, are skipped and logged.
+
+Returns the instance paths, or an error if the directory can't be read or contains no QDIMACS instances.
+*/
+pub fn read_benchmark_paths(group: &String, verbosity: Verbosity) -> Result<Vec<String>, String> {
+    let entries = fs::read_dir(group).map_err(|error| format!("Unable to read benchmark directory {}: {}", group, error))?;
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|error| format!("Unable to read benchmark directory {}: {}", group, error))?.path();
+        if path.is_file() && path.extension().map_or(false, |extension| extension.eq_ignore_ascii_case("qdimacs")) {
+            paths.push(path.display().to_string());
+        } else {
+            log_normal!(verbosity, "Skipping {} as it is not a QDIMACS instance.", path.display());
+        }
+    }
+    if paths.is_empty() {
+        return Err(format!("Benchmark directory {} contains no .qdimacs files", group));
+    }
+    paths.sort();
+    return Ok(paths);
+}
+
+/*
+A function to format a duration as milliseconds to three decimal places, e.g. "1500.000" for 1.5 seconds, so that
+durations in reports share a unit and parse as numbers.
+*/
+pub fn format_duration_ms(duration: Duration) -> String {
+    return format!("{:.3}", duration.as_secs_f64() * 1000.0);
+}
+
+/*
+A function to get the path of a results file within the output directory, creating the directory if it doesn't exist.
+
+Returns the path "<output_dir>/output-<filename>".
+*/
+pub fn get_output_pathname(output_dir: &str, filename: &str) -> String {
+    fs::create_dir_all(output_dir).expect("Unable to create output directory");
+    return Path::new(output_dir).join(format!("output-{}", filename)).display().to_string();
+}
+
+/*
+A function to select the solver for a parsed instance when the solver type is Auto. DPLL is selected when the alternation
+depth of the quantifier prefix is below the threshold in the matrix's config, and CDCL otherwise.
+
+Returns the selected solver type.
+*/
+pub fn select_solver_type(matrix: &Matrix) -> SolverType {
+    if (matrix.alternation_depth() as usize) < matrix.config.auto_alternation_threshold {
+        return SolverType::DPLL;
+    } else {
+        return SolverType::CDCL;
+    }
+}
+
+/*
+A function to rebuild the matrix of an instance whose first attempt gave up, having run out of time or hit its decision 
+or conflict limit, so it can be solved again with the fallback literal selection. The retry is only made if a fallback
+is configured and the full timeout, measured from the start of the first attempt, hasn't been reached.
+
+Returns the matrix for the retry, None if no retry is made, or an error if an instance can no longer be read.
+*/
+pub fn build_fallback_matrix(filenames: &[String], config: &Config, gave_up: bool, timer: Instant) -> Result<Option<Matrix>, String> {
+    if !gave_up || !config.fallback_enabled() || config.timed_out(timer) {
+        return Ok(None);
+    }
+    // A single instance that can't be read is parsed as an empty instance, so the instances are checked first.
+    for filename in filenames {
+        Matrix::read_lines(filename).map_err(|error| format!("Unable to read {} for the retry, keeping the first result: {}", filename, error))?;
+    }
+    let fallback_config = config.fallback_config();
+    let matrix = Matrix::from_files(filenames, fallback_config).map_err(|error| format!("Unable to parse the instance for the retry, keeping the first result: {}", error))?;
+    log_normal!(config.verbosity, "{:?} literal selection gave up, retrying with {:?}.", config.literal_selection, matrix.config.literal_selection);
+    return Ok(Some(matrix));
+}
+
+/*
+A function to generate the next pseudo-random number below the bound from a xorshift generator, so randomised runs can 
+be reproduced from their seed. The state must be non-zero.
+*/
+pub fn next_random(state: &mut u64, bound: u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    return *state % bound;
+}
+
+/*
+A function to hash the contents of an instance file, using the same FNV-1a hash as derive_instance_seed, so a file 
+written for an instance can be checked against the instance it was written for.
+
+Returns the hash, or an error if the file can't be read.
+*/
+pub fn hash_instance_file(filename: &str) -> Result<u64, String> {
+    let contents = fs::read(filename).map_err(|error| format!("Unable to read instance file {}: {}", filename, error))?;
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in contents {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    return Ok(hash);
+}
+
+/*
+A function to derive the seed of an instance from its name and the configured seed, using an FNV-1a hash of the name. 
+The hash is fixed rather than the standard library's hasher, so the derived seed is the same across runs and builds.
+*/
+pub fn derive_instance_seed(seed: u64, instance_name: &str) -> u64 {
+    let mut hash = 0xcbf29ce484222325 ^ seed;
+    for byte in instance_name.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    return hash;
+}
@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+
+use crate::{data_structures::Matrix, resolution::resolve};
+
+/*
+A function to perform bounded variable elimination on the clause database. Each existential variable is eliminated by
+replacing the clauses it appears in with all of their non-tautological resolvents on it, as long as this doesn't
+increase the number of clauses by more than the given bound.
+
+Note: Eliminating a variable by resolution is only sound when no universal variable is quantified after it, so only
+variables in the innermost existential block are eliminated, starting from the innermost variable. Variables that
+appear in soft clauses are kept so the weight of an assignment doesn't change. Eliminated clauses are only marked as
+removed, so the clause database should be simplified afterwards.
+
+Returns the list of eliminated variables.
+*/
+pub fn variable_elimination(matrix: &mut Matrix, bound: usize) -> Vec<i32> {
+    return eliminate_variables(matrix, bound).into_iter().map(|(variable, _)| variable).collect();
+}
+
+/*
+A function to perform bounded variable elimination as above, keeping the clauses each variable was eliminated from so
+a value can be reconstructed for it from a satisfying assignment of the remaining clauses.
+
+Returns the list of eliminated variables paired with the clauses removed when eliminating them, in elimination order.
+*/
+pub fn eliminate_variables(matrix: &mut Matrix, bound: usize) -> Vec<(i32, Vec<Vec<i32>>)> {
+    let mut eliminated_variables = Vec::new();
+    if matrix.check_solved() { return eliminated_variables };
+    let innermost_universal_level = matrix.quantifier_list.iter()
+                                        .filter(|quantifier| quantifier.is_universal())
+                                        .map(|quantifier| quantifier.q_level)
+                                        .max();
+    let candidate_variables: Vec<i32> = matrix.quantifiers_sorted().iter().rev()
+                                        .filter(|quantifier| quantifier.is_existential())
+                                        .filter(|quantifier| innermost_universal_level.map_or(true, |level| quantifier.q_level > level))
+                                        .map(|quantifier| quantifier.literal)
+                                        .collect();
+    for variable in candidate_variables {
+        let pos_references = get_active_references(matrix, variable);
+        let neg_references = get_active_references(matrix, -variable);
+        let references: Vec<usize> = pos_references.iter().chain(neg_references.iter()).copied().collect();
+        if references.iter().any(|&reference| matrix.clause_set.clause_list[reference].is_soft()) { continue };
+
+        let resolvents = get_resolvents(matrix, variable, &pos_references, &neg_references);
+        if resolvents.is_none() { continue };
+        let resolvents = resolvents.unwrap();
+        if resolvents.len() > references.len().saturating_add(bound) { continue };
+
+        let mut eliminated_clauses = Vec::new();
+        for reference in references {
+            eliminated_clauses.push(matrix.clause_set.clause_list[reference].literals());
+            matrix.clause_set.clause_list[reference].is_removed = true;
+            matrix.clause_set.decrement_counter();
+        }
+        for resolvent in resolvents {
+            matrix.add_clause(&resolvent);
+        }
+        matrix.quantifier_list.retain(|quantifier| quantifier.literal != variable);
+        eliminated_variables.push((variable, eliminated_clauses));
+    }
+    return eliminated_variables;
+}
+
+/*
+A function to get the indices of the clauses that have not been removed which contain the given literal.
+*/
+fn get_active_references(matrix: &Matrix, literal: i32) -> Vec<usize> {
+    let references = matrix.clause_references.get_vec(&literal);
+    if references.is_none() {
+        return Vec::new();
+    }
+    return references.unwrap().iter()
+            .map(|reference| *reference as usize)
+            .filter(|&reference| !matrix.clause_set.clause_list[reference].is_removed)
+            .collect();
+}
+
+/*
+A function to get every non-tautological resolvent on the given variable between the positive and negative clauses,
+without duplicates.
+
+Returns the list of resolvents, or None if a resolvent is empty as the variable can't be eliminated.
+*/
+fn get_resolvents(matrix: &Matrix, variable: i32, pos_references: &Vec<usize>, neg_references: &Vec<usize>) -> Option<Vec<Vec<i32>>> {
+    let mut resolvents = Vec::new();
+    let mut resolvent_hashtable = HashSet::new();
+    for pos_reference in pos_references {
+        let pos_literals = matrix.clause_set.clause_list[*pos_reference].literals();
+        for neg_reference in neg_references {
+            let neg_literals = matrix.clause_set.clause_list[*neg_reference].literals();
+            let resolution = resolve(pos_literals.clone(), neg_literals, variable);
+            if resolution.is_none() { continue };
+            let mut resolvent = resolution.unwrap();
+            if resolvent.is_empty() { return None };
+            resolvent.sort();
+            if resolvent_hashtable.insert(resolvent.clone()) {
+                resolvents.push(resolvent);
+            }
+        }
+    }
+    return Some(resolvents);
+}
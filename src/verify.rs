@@ -0,0 +1,39 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::data_structures::{CDCLMatrix, Assignment};
+
+/*
+A function to verify that an assignment satisfies every clause of the instance as it was parsed, before any clause was
+simplified by universal reduction, pre-processing, or pre-resolution. Literals fixed during pre-processing are not in
+the assignments, so they are added, with pure universal literals set false. Every other variable left unassigned is set
+false, except those removed by variable elimination, which are given a value satisfying the clauses they were
+eliminated from, in the reverse of the order they were eliminated.
+
+Returns true if every clause is satisfied by the assignment, false otherwise.
+*/
+pub fn verify_assignment(matrix: &CDCLMatrix, assignments: &HashMap<i32, Assignment>) -> bool {
+    let mut true_literals: HashSet<i32> = assignments.values().map(|assignment| assignment.value).collect();
+    for literal in &matrix.fixed_literals {
+        let is_existential = matrix.core_data.variable_of(*literal).is_existential();
+        true_literals.insert(if is_existential { *literal } else { -literal });
+    }
+    let eliminated_variables: HashSet<i32> = matrix.eliminated_variables.iter().map(|(variable, _)| *variable).collect();
+    for clause in &matrix.parsed_clause_list {
+        for literal in clause.literals() {
+            let variable = literal.abs();
+            if eliminated_variables.contains(&variable) || true_literals.contains(&variable) { continue };
+            true_literals.insert(-variable);
+        }
+    }
+    for (variable, eliminated_clauses) in matrix.eliminated_variables.iter().rev() {
+        let needs_positive = eliminated_clauses.iter().any(|literals| literals.contains(variable)
+            && !literals.iter().any(|literal| true_literals.contains(literal)));
+        true_literals.insert(if needs_positive { *variable } else { -variable });
+    }
+    for clause in &matrix.parsed_clause_list {
+        if !clause.literals().iter().any(|literal| true_literals.contains(literal)) {
+            return false;
+        }
+    }
+    return true;
+}